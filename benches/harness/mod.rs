@@ -0,0 +1,75 @@
+//! Minimal benchmark harness, without dependencies.
+//! Each case is run in batches of about 10 ms, and the median time per iteration is reported,
+//! with the number of allocations and allocated bytes per iteration.
+//! Arguments not starting with '-' filter the cases by name, like `cargo bench --bench perft -- kiwipete`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+const SAMPLES: usize = 21;
+const SAMPLE_TIME: Duration = Duration::from_millis(10);
+
+// System allocator counting the allocations.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn is_selected(name: &str) -> bool {
+    let filters: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .collect();
+    filters.is_empty() || filters.iter().any(|filter| name.contains(filter.as_str()))
+}
+
+#[allow(clippy::cast_possible_truncation)]
+pub fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+    if !is_selected(name) {
+        return;
+    }
+    // Number of iterations per sample, so that the timer resolution doesn't matter.
+    // The first run also gives the allocations, which are the same for each iteration.
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+    black_box(f());
+    let once = start.elapsed().max(Duration::from_nanos(1));
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes;
+    let iterations = (SAMPLE_TIME.as_nanos() / once.as_nanos()).clamp(1, 1_000_000) as u32;
+
+    let mut samples: Vec<Duration> = (0..SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                black_box(f());
+            }
+            start.elapsed() / iterations
+        })
+        .collect();
+    samples.sort();
+    println!(
+        "{name:<40} {:>12.2?} {allocations:>10} allocs {bytes:>12} bytes",
+        samples[SAMPLES / 2]
+    );
+}
@@ -0,0 +1,30 @@
+//! Captures generation, as used by the quiescence search: `cargo bench --bench movegen`
+//! Compares `generate_captures` with filtering the captures out of all the legal moves.
+
+mod harness;
+
+use std::hint::black_box;
+
+use kaik::{board::Board, fen, moves::Move};
+
+fn captures_from_legal_moves(board: &Board) -> Vec<Move> {
+    board
+        .generate_legal_moves()
+        .into_iter()
+        .filter(|mv| mv.is_capture())
+        .collect()
+}
+
+fn main() {
+    for (name, fen) in [("kiwipete", fen::KIWIPETE), ("position 4", fen::POSITION_4)] {
+        let board: Board = fen.into();
+        assert_eq!(board.generate_captures(), captures_from_legal_moves(&board));
+
+        harness::bench(&format!("{name} legal moves then filter"), || {
+            captures_from_legal_moves(black_box(&board))
+        });
+        harness::bench(&format!("{name} generate_captures"), || {
+            black_box(&board).generate_captures()
+        });
+    }
+}
@@ -0,0 +1,33 @@
+//! Move generation benchmark: `cargo bench --bench perft`
+
+mod harness;
+
+use std::hint::black_box;
+
+use kaik::{board::Board, fen};
+
+fn main() {
+    let start_position = Board::initial_board();
+    let kiwipete: Board = fen::KIWIPETE.into();
+
+    harness::bench("perft 4 start position", || {
+        black_box(&start_position).perft(4)
+    });
+    harness::bench("perft 3 kiwipete", || black_box(&kiwipete).perft(3));
+
+    // Where perft spends its time: Generating the pseudo-legal moves,
+    // or making them to check that they don't leave the king in check.
+    let moves = kiwipete.generate_moves();
+    harness::bench("kiwipete generate_moves", || {
+        black_box(&kiwipete).generate_moves()
+    });
+    harness::bench("kiwipete copy_with_move of all moves", || {
+        moves
+            .iter()
+            .filter(|&&mv| black_box(&kiwipete).copy_with_move(mv).is_some())
+            .count()
+    });
+    harness::bench("kiwipete count_legal_moves", || {
+        black_box(&kiwipete).count_legal_moves()
+    });
+}
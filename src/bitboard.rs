@@ -3,10 +3,15 @@
 
 mod constants;
 mod debug;
+mod magic;
+mod rays;
+mod shifts;
 mod sliding_pieces_with_hq;
 
 pub mod movements;
 
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
 use crate::common::Square;
 
 pub type BitBoard = u64;
@@ -29,10 +34,46 @@ pub fn from_square(square: Square) -> BitBoard {
     1 << square as u8
 }
 
+pub fn from_squares(squares: &[Square]) -> BitBoard {
+    squares.iter().fold(0, |bb, &sq| bb | sq)
+}
+
+// So that squares can be combined with bitboards directly, like `occupied | sq`.
+impl BitOr<Square> for BitBoard {
+    type Output = BitBoard;
+
+    fn bitor(self, sq: Square) -> BitBoard {
+        self | from_square(sq)
+    }
+}
+
+impl BitAnd<Square> for BitBoard {
+    type Output = BitBoard;
+
+    fn bitand(self, sq: Square) -> BitBoard {
+        self & from_square(sq)
+    }
+}
+
+impl BitOrAssign<Square> for BitBoard {
+    fn bitor_assign(&mut self, sq: Square) {
+        *self |= from_square(sq);
+    }
+}
+
 pub const fn is_set(bitboard: BitBoard, index: u8) -> bool {
     bitboard & (1 << index) != 0
 }
 
+// If any of the bits of other are set in the bitboard.
+pub const fn contains(bitboard: BitBoard, other: BitBoard) -> bool {
+    bitboard & other != 0
+}
+
+pub const fn contains_square(bitboard: BitBoard, sq: Square) -> bool {
+    is_set(bitboard, sq as u8)
+}
+
 pub fn set(bitboard: &mut BitBoard, index: u8) {
     *bitboard |= 1 << index;
 }
@@ -41,17 +82,40 @@ pub fn clear(bitboard: &mut BitBoard, index: u8) {
     *bitboard &= !(1 << index);
 }
 
+pub const fn is_empty(bitboard: BitBoard) -> bool {
+    bitboard == 0
+}
+
+// True if exactly one bit is set, like the bitboard of a single piece.
+pub const fn is_one(bitboard: BitBoard) -> bool {
+    bitboard.is_power_of_two()
+}
+
 pub const fn neg(bitboard: BitBoard) -> BitBoard {
     bitboard.wrapping_neg()
 }
 
 // Returns the index of lowest bit in the bitboard.
+// The bitboard must not be empty: This is checked in debug builds, in release it returns 64.
 #[allow(clippy::cast_possible_truncation)]
 pub const fn get_index(bitboard: BitBoard) -> u8 {
+    debug_assert!(bitboard != 0, "Empty bitboard has no least significant bit");
     // Should be one CPU instruction.
     bitboard.trailing_zeros() as u8
 }
 
+// Square of the lowest bit in the bitboard, which must not be empty.
+pub fn get_square(bitboard: BitBoard) -> Square {
+    Square::from_index(get_index(bitboard)).expect("Empty bitboard has no square")
+}
+
+// Number of bits set, population count.
+// <https://www.chessprogramming.org/Population_Count>
+pub const fn count_ones(bitboard: BitBoard) -> u32 {
+    // Should be one CPU instruction, if the target supports it.
+    bitboard.count_ones()
+}
+
 // Least Significant One
 // <https://www.chessprogramming.org/General_Setwise_Operations#Least_Significant_One>
 pub fn get_ls1b(bitboard: BitBoard) -> BitBoard {
@@ -62,11 +126,40 @@ pub fn reset_ls1b(bitboard: BitBoard) -> BitBoard {
     bitboard & (bitboard - 1)
 }
 
+// Most Significant One
+// <https://www.chessprogramming.org/General_Setwise_Operations#Most_Significant_One>
+
+// Returns the index of the highest bit in the bitboard. Panics if the bitboard is empty.
+#[allow(clippy::cast_possible_truncation)]
+pub const fn get_ms1b_index(bitboard: BitBoard) -> u8 {
+    assert!(bitboard != 0, "Empty bitboard has no most significant bit");
+    // Same as 63 - leading_zeros(), one CPU instruction.
+    bitboard.ilog2() as u8
+}
+
+// Returns an empty bitboard if the bitboard is empty.
+pub fn get_ms1b(bitboard: BitBoard) -> BitBoard {
+    if bitboard == 0 {
+        0
+    } else {
+        1 << get_ms1b_index(bitboard)
+    }
+}
+
+pub fn reset_ms1b(bitboard: BitBoard) -> BitBoard {
+    bitboard & !get_ms1b(bitboard)
+}
+
 // Creates an iterator that yields each set bit as a separate bitboard.
 pub fn into_iter(bitboard: BitBoard) -> BitBoardIterator {
     BitBoardIterator(bitboard)
 }
 
+// Creates an iterator that yields the square of each set bit, from A1 to H8.
+pub fn iter_squares(bitboard: BitBoard) -> impl Iterator<Item = Square> {
+    into_iter(bitboard).map(get_square)
+}
+
 pub struct BitBoardIterator(u64);
 
 impl Iterator for BitBoardIterator {
@@ -84,15 +177,24 @@ impl Iterator for BitBoardIterator {
     }
 }
 
-pub use constants::INITIAL_BOARD;
+pub use constants::{
+    anti_diagonal_mask, diagonal_mask, file_mask, rank_mask, DARK_SQUARES, FILE_A, FILE_B, FILE_C,
+    FILE_D, FILE_E, FILE_F, FILE_G, FILE_H, INITIAL_BOARD, LIGHT_SQUARES, RANK_1, RANK_2, RANK_3,
+    RANK_4, RANK_5, RANK_6, RANK_7, RANK_8, UNIVERSAL,
+};
 pub use debug::from_str;
 pub use debug::print;
+pub use rays::{ray_between, ray_from, Direction};
+pub use shifts::{
+    shift_east, shift_north, shift_north_east, shift_north_west, shift_south, shift_south_east,
+    shift_south_west, shift_west,
+};
 
 #[cfg(test)]
 mod tests {
     use crate::{
         bitboard::{self, constants},
-        common::Square,
+        common::{Piece, Square},
     };
 
     use super::*;
@@ -106,6 +208,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_squares() {
+        assert_eq!(bitboard::from_squares(&[]), 0);
+        let squares = [Square::C3, Square::B4, Square::F4, Square::C6, Square::E6];
+        let bb = bitboard::from_squares(&squares);
+        assert_eq!(
+            bb,
+            bitboard::from_str(
+                r"
+            . . . . . . . .
+            . . . . . . . .
+            . . 1 . 1 . . .
+            . . . . . . . .
+            . 1 . . . 1 . .
+            . . 1 . . . . .
+            . . . . . . . .
+            . . . . . . . ."
+            )
+        );
+        // Duplicates are ignored, and iter_squares gives them back in order.
+        assert_eq!(
+            bitboard::from_squares(&[Square::C3, Square::C3]),
+            bitboard::from_square(Square::C3)
+        );
+        assert_eq!(
+            bitboard::iter_squares(bb).collect::<Vec<_>>(),
+            [Square::C3, Square::B4, Square::F4, Square::C6, Square::E6]
+        );
+    }
+
+    #[test]
+    fn test_square_operators() {
+        let bb = bitboard::from_square(Square::C3);
+        assert_eq!(
+            bb | Square::B4,
+            bitboard::from_squares(&[Square::C3, Square::B4])
+        );
+        assert_eq!(bb | Square::C3, bb);
+        assert_eq!(bb & Square::C3, bb);
+        assert_eq!(bb & Square::B4, 0);
+        let mut bb = 0;
+        bb |= Square::H8;
+        bb |= Square::A1;
+        assert_eq!(bb, bitboard::from_squares(&[Square::A1, Square::H8]));
+    }
+
     const SAMPLE_BB: &str = r"
         . . . . . . . .
         . . 1 . 1 . . .
@@ -120,6 +268,103 @@ mod tests {
     fn test_get_index() {
         let bb: BitBoard = bitboard::from_str(SAMPLE_BB);
         assert_eq!(bitboard::get_index(bb), 18);
+        assert_eq!(bitboard::get_index(1), 0);
+        assert_eq!(bitboard::get_index(1 << 63), 63);
+        assert_eq!(bitboard::get_index(UNIVERSAL), 0);
+    }
+
+    #[test]
+    fn test_get_square() {
+        let bb: BitBoard = bitboard::from_str(SAMPLE_BB);
+        assert_eq!(bitboard::get_square(bb), Square::C3);
+        assert_eq!(bitboard::get_square(1), Square::A1);
+        assert_eq!(bitboard::get_square(1 << 63), Square::H8);
+    }
+
+    #[test]
+    #[should_panic(expected = "Empty bitboard")]
+    fn test_get_square_empty() {
+        bitboard::get_square(0);
+    }
+
+    #[test]
+    fn test_contains() {
+        let bb: BitBoard = bitboard::from_str(SAMPLE_BB);
+        assert!(bitboard::contains(bb, bitboard::from_square(Square::C3)));
+        assert!(bitboard::contains(bb, RANK_3));
+        assert!(!bitboard::contains(bb, RANK_1));
+        assert!(!bitboard::contains(bb, 0));
+
+        assert!(bitboard::contains_square(bb, Square::C3));
+        assert!(bitboard::contains_square(bb, Square::F6));
+        assert!(!bitboard::contains_square(bb, Square::D4));
+    }
+
+    #[test]
+    fn test_ms1b() {
+        let bb: BitBoard = bitboard::from_squares(&[Square::B2, Square::E4, Square::G6]);
+        assert_eq!(bitboard::get_ms1b_index(bb), Square::G6 as u8);
+        assert_eq!(bitboard::get_ms1b(bb), bitboard::from_square(Square::G6));
+        assert_eq!(
+            bitboard::reset_ms1b(bb),
+            bitboard::from_squares(&[Square::B2, Square::E4])
+        );
+
+        assert_eq!(bitboard::get_ms1b_index(1), 0);
+        assert_eq!(bitboard::get_ms1b_index(UNIVERSAL), 63);
+        assert_eq!(bitboard::get_ms1b(0), 0);
+        assert_eq!(bitboard::reset_ms1b(0), 0);
+        assert_eq!(bitboard::reset_ms1b(1 << 63), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Empty bitboard")]
+    fn test_get_ms1b_index_empty() {
+        bitboard::get_ms1b_index(0);
+    }
+
+    #[test]
+    fn test_iter_squares() {
+        let black_pawns = INITIAL_BOARD[Piece::BlackPawn as usize];
+        assert_eq!(
+            bitboard::iter_squares(black_pawns).collect::<Vec<_>>(),
+            [
+                Square::A7,
+                Square::B7,
+                Square::C7,
+                Square::D7,
+                Square::E7,
+                Square::F7,
+                Square::G7,
+                Square::H7
+            ]
+        );
+        assert_eq!(bitboard::iter_squares(0).count(), 0);
+    }
+
+    #[test]
+    fn test_count_ones() {
+        assert_eq!(bitboard::count_ones(0), 0);
+        assert_eq!(bitboard::count_ones(bitboard::from_str(SAMPLE_BB)), 8);
+        assert_eq!(
+            bitboard::count_ones(INITIAL_BOARD[Piece::WhitePawn as usize]),
+            8
+        );
+        assert_eq!(bitboard::count_ones(constants::UNIVERSAL), 64);
+    }
+
+    #[test]
+    fn test_is_empty_is_one() {
+        assert!(bitboard::is_empty(0));
+        assert!(!bitboard::is_empty(bitboard::from_square(Square::A1)));
+        assert!(!bitboard::is_one(0));
+        assert!(bitboard::is_one(bitboard::from_square(Square::A1)));
+        assert!(bitboard::is_one(bitboard::from_square(Square::H8)));
+        assert!(!bitboard::is_one(bitboard::from_squares(&[
+            Square::A1,
+            Square::H8
+        ])));
+        assert!(!bitboard::is_one(constants::UNIVERSAL));
     }
 
     #[test]
@@ -1,6 +1,6 @@
 #![allow(clippy::unreadable_literal)]
 
-use crate::bitboard::BitBoard;
+use crate::{bitboard::BitBoard, common::Square};
 
 pub const EMPTY: BitBoard = u64::MIN;
 pub const UNIVERSAL: BitBoard = u64::MAX;
@@ -36,8 +36,71 @@ pub const NOT_A_FILE: BitBoard = 18374403900871474942;
 pub const NOT_H_FILE: BitBoard = 9187201950435737471;
 pub const NOT_HG_FILE: BitBoard = 4557430888798830399;
 pub const NOT_AB_FILE: BitBoard = 18229723555195321596;
-pub const MASK_RANK_3: BitBoard = 16711680;
-pub const MASK_RANK_6: BitBoard = 280375465082880;
+pub const MASK_RANK_3: BitBoard = RANK_3;
+pub const MASK_RANK_6: BitBoard = RANK_6;
+
+// Masks for lines, ranks, diagonals.
+// <https://www.chessprogramming.org/On_an_empty_Board#By_Calculation_3>
+
+// Rank is 0 based, 0 for rank 1.
+pub const fn rank_mask(rank: u8) -> BitBoard {
+    assert!(rank < 8);
+    0xFF << (rank * 8)
+}
+
+// File is 0 based, 0 for file A.
+pub const fn file_mask(file: u8) -> BitBoard {
+    assert!(file < 8);
+    0x0101010101010101 << file
+}
+
+// Diagonal going through the square, from bottom left to top right (like A1-H8).
+#[allow(clippy::cast_possible_wrap)]
+pub const fn diagonal_mask(sq: Square) -> BitBoard {
+    const MAIN_DIAG: BitBoard = 0x8040201008040201;
+    let sq = sq as u8;
+    let diag = (sq & 7) as i8 - (sq >> 3) as i8;
+    if diag >= 0 {
+        MAIN_DIAG >> (diag * 8)
+    } else {
+        MAIN_DIAG << (-diag * 8)
+    }
+}
+
+// Anti-diagonal going through the square, from top left to bottom right (like A8-H1).
+#[allow(clippy::cast_possible_wrap)]
+pub const fn anti_diagonal_mask(sq: Square) -> BitBoard {
+    const MAIN_ANTI_DIAG: BitBoard = 0x0102040810204080;
+    let sq = sq as u8;
+    let diag = 7 - (sq & 7) as i8 - (sq >> 3) as i8;
+    if diag >= 0 {
+        MAIN_ANTI_DIAG >> (diag * 8)
+    } else {
+        MAIN_ANTI_DIAG << (-diag * 8)
+    }
+}
+
+pub const RANK_1: BitBoard = rank_mask(0);
+pub const RANK_2: BitBoard = rank_mask(1);
+pub const RANK_3: BitBoard = rank_mask(2);
+pub const RANK_4: BitBoard = rank_mask(3);
+pub const RANK_5: BitBoard = rank_mask(4);
+pub const RANK_6: BitBoard = rank_mask(5);
+pub const RANK_7: BitBoard = rank_mask(6);
+pub const RANK_8: BitBoard = rank_mask(7);
+
+pub const FILE_A: BitBoard = file_mask(0);
+pub const FILE_B: BitBoard = file_mask(1);
+pub const FILE_C: BitBoard = file_mask(2);
+pub const FILE_D: BitBoard = file_mask(3);
+pub const FILE_E: BitBoard = file_mask(4);
+pub const FILE_F: BitBoard = file_mask(5);
+pub const FILE_G: BitBoard = file_mask(6);
+pub const FILE_H: BitBoard = file_mask(7);
+
+// A1 is a dark square.
+pub const LIGHT_SQUARES: BitBoard = 0x55AA_55AA_55AA_55AA;
+pub const DARK_SQUARES: BitBoard = !LIGHT_SQUARES;
 
 pub const CASTLING_KING_SIDE_MASKS: [BitBoard; 2] = [
     0b0000000000000000000000000000000000000000000000000000000001100000,
@@ -51,7 +114,10 @@ pub const CASTLING_QUEEN_SIDE_MASKS: [BitBoard; 2] = [
 
 #[cfg(test)]
 mod tests {
-    use crate::bitboard::{self, constants::*};
+    use crate::{
+        bitboard::{self, constants::*},
+        common::Square,
+    };
 
     #[test]
     fn test_clipping_bitboards() {
@@ -102,4 +168,59 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_rank_file_masks() {
+        assert_eq!(RANK_1, 0xFF);
+        assert_eq!(RANK_8, 0xFF00_0000_0000_0000);
+        assert_eq!(MASK_RANK_6, 280_375_465_082_880);
+        assert_eq!(FILE_A, !NOT_A_FILE);
+        assert_eq!(FILE_H, !NOT_H_FILE);
+        assert_eq!(FILE_G | FILE_H, !NOT_HG_FILE);
+        assert_eq!(
+            FILE_D,
+            bitboard::from_str(
+                r"
+              0 0 0 1 0 0 0 0
+              0 0 0 1 0 0 0 0
+              0 0 0 1 0 0 0 0
+              0 0 0 1 0 0 0 0
+              0 0 0 1 0 0 0 0
+              0 0 0 1 0 0 0 0
+              0 0 0 1 0 0 0 0
+              0 0 0 1 0 0 0 0"
+            )
+        );
+    }
+
+    #[test]
+    fn test_square_colors() {
+        assert!(bitboard::is_set(DARK_SQUARES, Square::A1 as u8));
+        assert!(bitboard::is_set(DARK_SQUARES, Square::H8 as u8));
+        assert!(bitboard::is_set(LIGHT_SQUARES, Square::H1 as u8));
+        assert!(bitboard::is_set(LIGHT_SQUARES, Square::D1 as u8));
+        assert_eq!(bitboard::count_ones(LIGHT_SQUARES), 32);
+    }
+
+    #[test]
+    fn test_diagonal_masks() {
+        assert_eq!(diagonal_mask(Square::A1), 0x8040_2010_0804_0201);
+        assert_eq!(diagonal_mask(Square::H8), 0x8040_2010_0804_0201);
+        assert_eq!(anti_diagonal_mask(Square::H1), 0x0102_0408_1020_4080);
+        assert_eq!(diagonal_mask(Square::H1), bitboard::from_square(Square::H1));
+        assert_eq!(
+            diagonal_mask(Square::C2) | anti_diagonal_mask(Square::C2),
+            bitboard::from_str(
+                r"
+              0 0 0 0 0 0 0 0
+              0 0 0 0 0 0 0 1
+              0 0 0 0 0 0 1 0
+              0 0 0 0 0 1 0 0
+              1 0 0 0 1 0 0 0
+              0 1 0 1 0 0 0 0
+              0 0 1 0 0 0 0 0
+              0 1 0 1 0 0 0 0"
+            )
+        );
+    }
 }
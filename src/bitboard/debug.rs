@@ -61,6 +61,6 @@ mod tests {
             0 1 1 1 1 1 1 1
             0 1 1 1 1 1 1 1",
         );
-        assert_eq!(not_a_file, 18374403900871474942);
+        assert_eq!(not_a_file, 18_374_403_900_871_474_942);
     }
 }
@@ -0,0 +1,289 @@
+//! Generates attack bitboards for sliding pieces.
+//! Magic bitboards approach, the magic numbers are searched at startup.
+//! <https://www.chessprogramming.org/Magic_Bitboards>
+//! <https://www.chessprogramming.org/Looking_for_Magics>
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_sign_loss)]
+
+use std::sync::OnceLock;
+
+use crate::{
+    bitboard::{self, BitBoard},
+    common::Square,
+};
+
+// As (rank, file) deltas.
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+// Xorshift pseudo random number generator, with a fixed seed so that the search is deterministic.
+// <https://www.chessprogramming.org/Pseudorandom_Number_Generator>
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // Magics with few bits set are more likely to work.
+    fn next_sparse(&mut self) -> u64 {
+        self.next() & self.next() & self.next()
+    }
+}
+
+fn offset(sq: u8, (dr, df): (i8, i8)) -> Option<u8> {
+    let rank = (sq >> 3) as i8 + dr;
+    let file = (sq & 7) as i8 + df;
+    if (0..8).contains(&rank) && (0..8).contains(&file) {
+        Some((rank * 8 + file) as u8)
+    } else {
+        None
+    }
+}
+
+// Slow attacks generation, walking each ray until it hits a piece.
+fn ray_attacks(sq: u8, occupied: BitBoard, directions: &[(i8, i8)]) -> BitBoard {
+    let mut attacks = 0;
+    for &direction in directions {
+        let mut current = sq;
+        while let Some(next) = offset(current, direction) {
+            attacks |= 1 << next;
+            if bitboard::is_set(occupied, next) {
+                break;
+            }
+            current = next;
+        }
+    }
+    attacks
+}
+
+// The squares whose occupancy matters: The rays without the edge squares.
+fn relevant_occupancy_mask(sq: u8, directions: &[(i8, i8)]) -> BitBoard {
+    let mut mask = 0;
+    for &direction in directions {
+        let mut current = sq;
+        while let Some(next) = offset(current, direction) {
+            if offset(next, direction).is_none() {
+                break;
+            }
+            mask |= 1 << next;
+            current = next;
+        }
+    }
+    mask
+}
+
+struct MagicEntry {
+    mask: BitBoard,
+    magic: u64,
+    shift: u32,
+    offset: usize, // Where the attacks of this square start in the attacks table.
+}
+
+impl MagicEntry {
+    fn index(&self, occupied: BitBoard) -> usize {
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+struct MagicTables {
+    rooks: Vec<MagicEntry>,
+    bishops: Vec<MagicEntry>,
+    attacks: Vec<BitBoard>,
+}
+
+// Finds a magic for this square and appends its attacks to the table.
+fn find_magic(
+    sq: u8,
+    directions: &[(i8, i8)],
+    rng: &mut Rng,
+    attacks: &mut Vec<BitBoard>,
+) -> MagicEntry {
+    let mask = relevant_occupancy_mask(sq, directions);
+    let bits = bitboard::count_ones(mask);
+    let shift = 64 - bits;
+
+    // All subsets of the mask, with the Carry-Rippler trick.
+    // <https://www.chessprogramming.org/Traversing_Subsets_of_a_Set>
+    let mut occupancies = Vec::new();
+    let mut occupied: BitBoard = 0;
+    loop {
+        occupancies.push((occupied, ray_attacks(sq, occupied, directions)));
+        occupied = occupied.wrapping_sub(mask) & mask;
+        if occupied == 0 {
+            break;
+        }
+    }
+
+    let size = 1 << bits;
+    let mut table = vec![0; size];
+    // Which attempt last wrote the entry, avoids clearing the table on each attempt.
+    let mut used_by = vec![0; size];
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let magic = rng.next_sparse();
+        // Quick rejection of magics that won't map enough bits at the top.
+        if bitboard::count_ones(mask.wrapping_mul(magic) & 0xFF00_0000_0000_0000) < 6 {
+            continue;
+        }
+
+        let candidate = MagicEntry {
+            mask,
+            magic,
+            shift,
+            offset: 0,
+        };
+        let found = occupancies.iter().all(|&(occupied, attack)| {
+            let index = candidate.index(occupied);
+            if used_by[index] == attempt {
+                // Collisions are fine if they have the same attacks.
+                table[index] == attack
+            } else {
+                used_by[index] = attempt;
+                table[index] = attack;
+                true
+            }
+        });
+
+        if found {
+            let offset = attacks.len();
+            attacks.extend_from_slice(&table);
+            return MagicEntry {
+                offset,
+                ..candidate
+            };
+        }
+    }
+}
+
+fn init_magic_tables() -> MagicTables {
+    let mut rng = Rng(0x4B41_494B);
+    let mut attacks = Vec::new();
+    let rooks = (0..64)
+        .map(|sq| find_magic(sq, &ROOK_DIRECTIONS, &mut rng, &mut attacks))
+        .collect();
+    let bishops = (0..64)
+        .map(|sq| find_magic(sq, &BISHOP_DIRECTIONS, &mut rng, &mut attacks))
+        .collect();
+    MagicTables {
+        rooks,
+        bishops,
+        attacks,
+    }
+}
+
+fn get_tables() -> &'static MagicTables {
+    static TABLES: OnceLock<MagicTables> = OnceLock::new();
+    TABLES.get_or_init(init_magic_tables)
+}
+
+pub fn rook_attacks(sq: Square, occupied: BitBoard) -> BitBoard {
+    let tables = get_tables();
+    tables.attacks[tables.rooks[sq as usize].index(occupied)]
+}
+
+pub fn bishop_attacks(sq: Square, occupied: BitBoard) -> BitBoard {
+    let tables = get_tables();
+    tables.attacks[tables.bishops[sq as usize].index(occupied)]
+}
+
+pub fn queen_attacks(sq: Square, occupied: BitBoard) -> BitBoard {
+    rook_attacks(sq, occupied) | bishop_attacks(sq, occupied)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitboard::sliding_pieces_with_hq;
+
+    use super::*;
+
+    #[test]
+    fn test_relevant_occupancy_mask() {
+        assert_eq!(
+            relevant_occupancy_mask(Square::A1 as u8, &ROOK_DIRECTIONS),
+            bitboard::from_str(
+                r"
+            . . . . . . . .
+            1 . . . . . . .
+            1 . . . . . . .
+            1 . . . . . . .
+            1 . . . . . . .
+            1 . . . . . . .
+            1 . . . . . . .
+            . 1 1 1 1 1 1 ."
+            )
+        );
+        assert_eq!(
+            relevant_occupancy_mask(Square::D4 as u8, &BISHOP_DIRECTIONS),
+            bitboard::from_str(
+                r"
+            . . . . . . . .
+            . . . . . . 1 .
+            . 1 . . . 1 . .
+            . . 1 . 1 . . .
+            . . . . . . . .
+            . . 1 . 1 . . .
+            . 1 . . . 1 . .
+            . . . . . . . ."
+            )
+        );
+    }
+
+    #[test]
+    fn test_rook_attacks() {
+        let occupancy = bitboard::from_str(
+            r"
+            . . . . . 1 . .
+            . . . . 1 . . .
+            . 1 . . . . . .
+            . . 1 . . 1 . .
+            . . . . . . . .
+            . . . . 1 . 1 .
+            1 1 1 1 1 . 1 1
+            . . . . . . 1 .",
+        );
+        assert_eq!(
+            rook_attacks(Square::C5, occupancy),
+            bitboard::from_str(
+                r"
+            . . 1 . . . . .
+            . . 1 . . . . .
+            . . 1 . . . . .
+            1 1 . 1 1 1 . .
+            . . 1 . . . . .
+            . . 1 . . . . .
+            . . 1 . . . . .
+            . . . . . . . ."
+            )
+        );
+    }
+
+    #[test]
+    fn test_same_as_hyperbola_quintessence() {
+        let mut rng = Rng(1234);
+        for _ in 0..200 {
+            // Around a quarter of the squares occupied.
+            let occupied = rng.next() & rng.next();
+            for sq in 0..64u8 {
+                let square: Square = sq.into();
+                assert_eq!(
+                    rook_attacks(square, occupied),
+                    sliding_pieces_with_hq::get_rook_attacks(occupied, sq)
+                );
+                assert_eq!(
+                    bishop_attacks(square, occupied),
+                    sliding_pieces_with_hq::get_bishop_attacks(occupied, sq)
+                );
+                assert_eq!(
+                    queen_attacks(square, occupied),
+                    sliding_pieces_with_hq::get_queen_attacks(occupied, sq)
+                );
+            }
+        }
+    }
+}
@@ -4,7 +4,7 @@ use crate::{bitboard, common::Color};
 use super::constants::{self, CASTLING_KING_SIDE_MASKS, CASTLING_QUEEN_SIDE_MASKS};
 use super::{
     constants::{MASK_RANK_3, MASK_RANK_6, NOT_AB_FILE, NOT_A_FILE, NOT_HG_FILE, NOT_H_FILE},
-    sliding_pieces_with_hq,
+    magic,
 };
 
 pub fn get_king_attacks(king_pos: BitBoard) -> BitBoard {
@@ -64,11 +64,7 @@ pub fn get_knight_moves(knights_pos: BitBoard, own_pieces: BitBoard) -> BitBoard
 }
 
 pub fn get_white_pawn_attacks(pawns_pos: BitBoard) -> BitBoard {
-    // Left side of the pawn, minding the underflow File A.
-    let pawn_left_attack = (pawns_pos & NOT_A_FILE) << 7;
-    // Right side
-    let pawn_right_attack = (pawns_pos & NOT_H_FILE) << 9;
-    pawn_left_attack | pawn_right_attack
+    bitboard::shift_north_west(pawns_pos) | bitboard::shift_north_east(pawns_pos)
 }
 
 pub fn get_valid_white_pawn_attacks(pawns_pos: BitBoard, all_other_pieces: BitBoard) -> BitBoard {
@@ -85,11 +81,11 @@ pub fn get_white_pawn_moves(
     // deal with the change in shifting and the opponents color.
 
     // Check the single space in front of the white pawn.
-    let pawn_one_step = (pawns_pos << 8) & !all_pieces;
+    let pawn_one_step = bitboard::shift_north(pawns_pos) & !all_pieces;
 
     // For all moves that came from rank 2 (home row) and passed the above filter,
     // thereby being on rank 3, check and see if I can move forward one more.
-    let pawn_two_steps = ((pawn_one_step & MASK_RANK_3) << 8) & !all_pieces;
+    let pawn_two_steps = bitboard::shift_north(pawn_one_step & MASK_RANK_3) & !all_pieces;
 
     // The union of the movements dictate the possible moves forward available.
     let pawn_valid_moves = pawn_one_step | pawn_two_steps;
@@ -101,9 +97,7 @@ pub fn get_white_pawn_moves(
 }
 
 pub fn get_black_pawn_attacks(pawns_pos: BitBoard) -> BitBoard {
-    let pawn_left_attack = (pawns_pos & NOT_A_FILE) >> 9;
-    let pawn_right_attack = (pawns_pos & NOT_H_FILE) >> 7;
-    pawn_left_attack | pawn_right_attack
+    bitboard::shift_south_west(pawns_pos) | bitboard::shift_south_east(pawns_pos)
 }
 
 pub fn get_valid_black_pawn_attacks(pawns_pos: BitBoard, all_other_pieces: BitBoard) -> BitBoard {
@@ -115,9 +109,9 @@ pub fn get_black_pawn_moves(
     all_pieces: BitBoard,
     all_other_pieces: BitBoard,
 ) -> BitBoard {
-    let pawn_one_step = (pawns_pos >> 8) & !all_pieces;
+    let pawn_one_step = bitboard::shift_south(pawns_pos) & !all_pieces;
     // For all moves that came from rank 7 (home row) and passed the above filter.
-    let pawn_two_steps = ((pawn_one_step & MASK_RANK_6) >> 8) & !all_pieces;
+    let pawn_two_steps = bitboard::shift_south(pawn_one_step & MASK_RANK_6) & !all_pieces;
     let pawn_valid_moves = pawn_one_step | pawn_two_steps;
 
     let pawn_valid_attacks = get_valid_black_pawn_attacks(pawns_pos, all_other_pieces);
@@ -125,11 +119,11 @@ pub fn get_black_pawn_moves(
 }
 
 pub fn get_bishop_attacks(bishops_pos: BitBoard, all_pieces: BitBoard) -> BitBoard {
-    sliding_pieces_with_hq::get_bishop_attacks(all_pieces, bitboard::get_index(bishops_pos))
+    magic::bishop_attacks(bitboard::get_square(bishops_pos), all_pieces)
 }
 
 pub fn get_rook_attacks(rooks_pos: BitBoard, all_pieces: BitBoard) -> BitBoard {
-    sliding_pieces_with_hq::get_rook_attacks(all_pieces, bitboard::get_index(rooks_pos))
+    magic::rook_attacks(bitboard::get_square(rooks_pos), all_pieces)
 }
 
 pub fn get_bishop_moves(
@@ -137,13 +131,11 @@ pub fn get_bishop_moves(
     all_pieces: BitBoard,
     own_pieces: BitBoard,
 ) -> BitBoard {
-    sliding_pieces_with_hq::get_bishop_attacks(all_pieces, bitboard::get_index(bishops_pos))
-        & !own_pieces
+    magic::bishop_attacks(bitboard::get_square(bishops_pos), all_pieces) & !own_pieces
 }
 
 pub fn get_rook_moves(rooks_pos: BitBoard, all_pieces: BitBoard, own_pieces: BitBoard) -> BitBoard {
-    sliding_pieces_with_hq::get_rook_attacks(all_pieces, bitboard::get_index(rooks_pos))
-        & !own_pieces
+    magic::rook_attacks(bitboard::get_square(rooks_pos), all_pieces) & !own_pieces
 }
 
 pub fn get_queen_moves(
@@ -151,8 +143,7 @@ pub fn get_queen_moves(
     all_pieces: BitBoard,
     own_pieces: BitBoard,
 ) -> BitBoard {
-    sliding_pieces_with_hq::get_queen_attacks(all_pieces, bitboard::get_index(queens_pos))
-        & !own_pieces
+    magic::queen_attacks(bitboard::get_square(queens_pos), all_pieces) & !own_pieces
 }
 
 pub fn can_castle_king_side(occupied: BitBoard, side_to_move: Color) -> bool {
@@ -231,7 +222,7 @@ mod tests {
     #[test]
     fn test_king_moves_not_empty_board() {
         let king: BitBoard = bitboard::from_square(E1);
-        let own_pieces: BitBoard = bitboard::from_square(D2) | bitboard::from_square(F1);
+        let own_pieces: BitBoard = bitboard::from_square(D2) | F1;
         let moves = get_king_moves(king, own_pieces);
         assert_eq!(
             moves,
@@ -252,8 +243,7 @@ mod tests {
     #[test]
     fn test_knight_moves() {
         let knight: BitBoard = bitboard::from_square(B4);
-        let own_pieces: BitBoard =
-            bitboard::from_square(D4) | bitboard::from_square(A2) | bitboard::from_square(D1);
+        let own_pieces: BitBoard = bitboard::from_square(D4) | A2 | D1;
         let moves = get_knight_moves(knight, own_pieces);
         assert_eq!(
             moves,
@@ -271,6 +261,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_knight_attacks_on_edges() {
+        // No wrap around to the other side of the board.
+        for (square, expected) in [
+            (A1, vec![B3, C2]),
+            (H1, vec![G3, F2]),
+            (A8, vec![B6, C7]),
+            (H8, vec![G6, F7]),
+            (B1, vec![A3, C3, D2]),
+            (G8, vec![H6, F6, E7]),
+            (A4, vec![B6, C5, C3, B2]),
+            (H5, vec![G7, F6, F4, G3]),
+        ] {
+            assert_eq!(
+                get_knight_attacks(bitboard::from_square(square)),
+                bitboard::from_squares(&expected),
+                "Knight on {square}"
+            );
+        }
+    }
+
     #[test]
     fn test_white_pawn_moves() {
         let pawns: BitBoard = bitboard::from_str(
@@ -0,0 +1,166 @@
+//! Rays and lines between squares, precomputed at compile time.
+//! <https://www.chessprogramming.org/On_an_empty_Board#Rays>
+//! <https://www.chessprogramming.org/Square_Attacked_By#InBetween>
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_sign_loss)]
+
+use crate::{bitboard::BitBoard, common::Square};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    // As (rank, file) delta.
+    const fn delta(self) -> (i8, i8) {
+        match self {
+            Direction::North => (1, 0),
+            Direction::NorthEast => (1, 1),
+            Direction::East => (0, 1),
+            Direction::SouthEast => (-1, 1),
+            Direction::South => (-1, 0),
+            Direction::SouthWest => (-1, -1),
+            Direction::West => (0, -1),
+            Direction::NorthWest => (1, -1),
+        }
+    }
+}
+
+// Next square in that direction, None if we leave the board.
+const fn step(sq: u8, dir: Direction) -> Option<u8> {
+    let (dr, df) = dir.delta();
+    let rank = (sq >> 3) as i8 + dr;
+    let file = (sq & 7) as i8 + df;
+    if rank >= 0 && rank < 8 && file >= 0 && file < 8 {
+        Some((rank * 8 + file) as u8)
+    } else {
+        None
+    }
+}
+
+const fn compute_rays() -> [[BitBoard; 64]; 8] {
+    let mut rays = [[0; 64]; 8];
+    let mut d = 0;
+    while d < 8 {
+        let mut from = 0;
+        while from < 64 {
+            let mut current = from;
+            while let Some(next) = step(current, Direction::ALL[d]) {
+                rays[d][from as usize] |= 1 << next;
+                current = next;
+            }
+            from += 1;
+        }
+        d += 1;
+    }
+    rays
+}
+
+// Only evaluated at compile time, so the array size doesn't matter.
+#[allow(clippy::large_stack_arrays)]
+const fn compute_between() -> [[BitBoard; 64]; 64] {
+    let mut between = [[0; 64]; 64];
+    let mut from = 0;
+    while from < 64 {
+        let mut d = 0;
+        while d < 8 {
+            // Walking the ray, the squares already seen are the ones between.
+            let mut ray = 0;
+            let mut current = from;
+            while let Some(next) = step(current, Direction::ALL[d]) {
+                between[from as usize][next as usize] = ray;
+                ray |= 1 << next;
+                current = next;
+            }
+            d += 1;
+        }
+        from += 1;
+    }
+    between
+}
+
+static RAYS: [[BitBoard; 64]; 8] = compute_rays();
+static BETWEEN: [[BitBoard; 64]; 64] = compute_between();
+
+// All the squares from that square to the edge of the board in that direction, excluding the square.
+pub fn ray_from(from: Square, dir: Direction) -> BitBoard {
+    RAYS[dir as usize][from as usize]
+}
+
+// Squares strictly between the two squares if they are on the same rank, file or diagonal, empty otherwise.
+pub fn ray_between(from: Square, to: Square) -> BitBoard {
+    BETWEEN[from as usize][to as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bitboard::{self, constants::*},
+        common::Square::*,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_ray_from() {
+        assert_eq!(ray_from(A1, Direction::North), FILE_A & !RANK_1);
+        assert_eq!(ray_from(A1, Direction::East), RANK_1 & !FILE_A);
+        assert_eq!(ray_from(A1, Direction::South), 0);
+        assert_eq!(ray_from(A1, Direction::NorthEast), diagonal_mask(A1) & !1);
+        assert_eq!(
+            ray_from(D4, Direction::NorthWest),
+            bitboard::from_str(
+                r"
+            . . . . . . . .
+            1 . . . . . . .
+            . 1 . . . . . .
+            . . 1 . . . . .
+            . . . . . . . .
+            . . . . . . . .
+            . . . . . . . .
+            . . . . . . . ."
+            )
+        );
+        // All directions make the queen attacks on an empty board.
+        let all = Direction::ALL
+            .iter()
+            .fold(0, |bb, &dir| bb | ray_from(E5, dir));
+        assert_eq!(
+            all,
+            (rank_mask(4) | file_mask(4) | diagonal_mask(E5) | anti_diagonal_mask(E5))
+                & !bitboard::from_square(E5)
+        );
+    }
+
+    #[test]
+    fn test_ray_between() {
+        assert_eq!(ray_between(A1, A4), bitboard::from_square(A2) | A3);
+        assert_eq!(ray_between(F6, C3), bitboard::from_square(D4) | E5);
+        assert_eq!(ray_between(H1, A8), ray_between(A8, H1));
+        assert_eq!(bitboard::count_ones(ray_between(H1, A8)), 6);
+        // Adjacent, same or not aligned squares.
+        assert_eq!(ray_between(A1, B2), EMPTY);
+        assert_eq!(ray_between(C3, C3), EMPTY);
+        assert_eq!(ray_between(A1, B3), EMPTY);
+    }
+}
@@ -0,0 +1,77 @@
+//! Shifting all the squares of a bitboard one step in a direction.
+//! Squares that would wrap around to the other side of the board are dropped.
+//! <https://www.chessprogramming.org/General_Setwise_Operations#OneStepOnly>
+
+use super::{
+    constants::{NOT_A_FILE, NOT_H_FILE},
+    BitBoard,
+};
+
+// North and south don't need masks, as squares shifted off the board vanish.
+pub const fn shift_north(bitboard: BitBoard) -> BitBoard {
+    bitboard << 8
+}
+
+pub const fn shift_south(bitboard: BitBoard) -> BitBoard {
+    bitboard >> 8
+}
+
+pub const fn shift_east(bitboard: BitBoard) -> BitBoard {
+    (bitboard & NOT_H_FILE) << 1
+}
+
+pub const fn shift_west(bitboard: BitBoard) -> BitBoard {
+    (bitboard & NOT_A_FILE) >> 1
+}
+
+pub const fn shift_north_east(bitboard: BitBoard) -> BitBoard {
+    (bitboard & NOT_H_FILE) << 9
+}
+
+pub const fn shift_north_west(bitboard: BitBoard) -> BitBoard {
+    (bitboard & NOT_A_FILE) << 7
+}
+
+pub const fn shift_south_east(bitboard: BitBoard) -> BitBoard {
+    (bitboard & NOT_H_FILE) >> 7
+}
+
+pub const fn shift_south_west(bitboard: BitBoard) -> BitBoard {
+    (bitboard & NOT_A_FILE) >> 9
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{bitboard, common::Square::*};
+
+    use super::*;
+
+    #[test]
+    fn test_shifts() {
+        let e4 = bitboard::from_square(E4);
+        assert_eq!(shift_north(e4), bitboard::from_square(E5));
+        assert_eq!(shift_south(e4), bitboard::from_square(E3));
+        assert_eq!(shift_east(e4), bitboard::from_square(F4));
+        assert_eq!(shift_west(e4), bitboard::from_square(D4));
+        assert_eq!(shift_north_east(e4), bitboard::from_square(F5));
+        assert_eq!(shift_north_west(e4), bitboard::from_square(D5));
+        assert_eq!(shift_south_east(e4), bitboard::from_square(F3));
+        assert_eq!(shift_south_west(e4), bitboard::from_square(D3));
+    }
+
+    #[test]
+    fn test_shifts_no_wrap_around() {
+        let a_file = bitboard::from_squares(&[A1, A4, A8]);
+        assert_eq!(shift_west(a_file), 0);
+        assert_eq!(shift_north_west(a_file), 0);
+        assert_eq!(shift_south_west(a_file), 0);
+
+        let h_file = bitboard::from_squares(&[H1, H4, H8]);
+        assert_eq!(shift_east(h_file), 0);
+        assert_eq!(shift_north_east(h_file), 0);
+        assert_eq!(shift_south_east(h_file), 0);
+
+        assert_eq!(shift_north(bitboard::RANK_8), 0);
+        assert_eq!(shift_south(bitboard::RANK_1), 0);
+    }
+}
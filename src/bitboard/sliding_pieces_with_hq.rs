@@ -11,7 +11,7 @@ use itertools::Itertools;
 // <https://www.chessprogramming.org/On_an_empty_Board#By_Calculation_3>
 
 const fn rank_mask(sq: u64) -> u64 {
-    0xFF << (sq & 56)
+    0xFF << (sq & 0x38)
 }
 
 const fn file_mask(sq: u64) -> u64 {
@@ -148,7 +148,7 @@ fn get_rank_attack_mask(i: u64) -> u64 {
 
 fn rank_attacks(occ: u64, sq: u8) -> u64 {
     let file = u64::from(sq & 7);
-    let rkx8 = sq & 56; // rank * 8
+    let rkx8 = sq & 0x38; // rank * 8
     let rank_occ_x2 = (occ >> rkx8) & (2 * 63); // 2 times the inner six bit rank occupancy used as index
     let attacks = get_rank_attack_mask(4 * rank_occ_x2 + file); // 8 * rank occupancy + file
     attacks << rkx8
@@ -195,7 +195,7 @@ mod tests {
             1 1 1 1 1 . 1 1
             . . . . . . 1 .",
         );
-        let attacks = get_bishop_attacks(occupancy.into(), C5);
+        let attacks = get_bishop_attacks(occupancy, C5);
         assert_eq!(
             attacks,
             bitboard::from_str(
@@ -226,7 +226,7 @@ mod tests {
             1 1 1 1 1 . 1 1
             . . . . . . 1 .",
         );
-        let attacks = get_rook_attacks(occupancy.into(), C5);
+        let attacks = get_rook_attacks(occupancy, C5);
         assert_eq!(
             attacks,
             bitboard::from_str(
@@ -7,14 +7,28 @@ mod attacks;
 mod board_type;
 mod castling;
 mod display;
+mod draw;
+mod edit;
+mod flip;
+mod material;
 mod move_gen;
+mod move_history;
 mod perft;
 mod update;
+mod validate;
+mod zobrist;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct CastlingAbility(u8);
+pub use draw::{DrawReason, GameHistory, GameResult};
+pub use move_history::MoveHistory;
+pub use perft::{nodes_per_second, perft_bench};
 
+// Which castling moves are still allowed, as a set of bits:
+// 0b0001 white king side, 0b0010 white queen side, 0b0100 black king side, 0b1000 black queen side.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CastlingAbility(u8);
+
+// Hash is implemented with the Zobrist hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Board {
     // Even indexes are white pieces, odd are black pieces.
     pieces: [BitBoard; 12],
@@ -23,4 +37,24 @@ pub struct Board {
     side_to_move: Color,
     en_passant_target_square: Option<Square>,
     castling_ability: CastlingAbility,
+    // Where the castling rooks start, in the same order as the castling ability bits.
+    // They are on the A and H files, except in Chess960.
+    castling_rooks: [Square; 4],
+    // Chess960 (Fischer Random Chess), where kings and rooks can start on other files.
+    // <https://www.chessprogramming.org/Chess960>
+    chess960: bool,
+    // Number of half moves since the last capture or pawn move, for the fifty-move rule.
+    half_move_clock: u8,
+    // Starts at 1 and is incremented after each black move.
+    full_move_counter: u16,
+    hash: u64,
+}
+
+// The information that a move destroys and that is needed to undo it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardState {
+    en_passant_target_square: Option<Square>,
+    castling_ability: CastlingAbility,
+    half_move_clock: u8,
+    hash: u64,
 }
@@ -4,7 +4,7 @@
 
 use crate::{
     bitboard::{self, movements, BitBoard},
-    common::{Color, Piece, Square},
+    common::{Color, Piece, PieceType, Square},
 };
 
 use super::Board;
@@ -15,21 +15,21 @@ impl Board {
         // From <https://www.chessprogramming.org/Checks_and_Pinned_Pieces_(Bitboards)>
         // Note that the example there doesn't check king creating checks.
 
-        let king_bb = self.pieces[Piece::get_king_of(king_color) as usize];
+        let king_bb = self.pieces_of(Piece::get_king_of(king_color));
         let opp_king_color = king_color.opposite();
 
         // Could be optimized a bit with things like:
         //   let opposite_pawns = self.pieces[Piece::BlackPawn as usize - king_color as usize];
-        let opposite_pawns = self.pieces[Piece::get_pawn_of(opp_king_color) as usize];
-        let opposite_knights = self.pieces[Piece::get_knight_of(opp_king_color) as usize];
-        let opposite_king = self.pieces[Piece::get_king_of(opp_king_color) as usize];
+        let opposite_pawns = self.pieces_of(Piece::get_pawn_of(opp_king_color));
+        let opposite_knights = self.pieces_of(Piece::get_knight_of(opp_king_color));
+        let opposite_king = self.pieces_of(Piece::get_king_of(opp_king_color));
 
-        let opposite_rooks_queens = self.pieces[Piece::get_queen_of(opp_king_color) as usize]
-            | self.pieces[Piece::get_rook_of(opp_king_color) as usize];
-        let opposite_bishops_queens = self.pieces[Piece::get_queen_of(opp_king_color) as usize]
-            | self.pieces[Piece::get_bishop_of(opp_king_color) as usize];
+        let opposite_rooks_queens = self.pieces_of(Piece::get_queen_of(opp_king_color))
+            | self.pieces_of(Piece::get_rook_of(opp_king_color));
+        let opposite_bishops_queens = self.pieces_of(Piece::get_queen_of(opp_king_color))
+            | self.pieces_of(Piece::get_bishop_of(opp_king_color));
 
-        let pawn_attacks = if king_color == Color::White {
+        let pawn_attacks = if king_color.is_white() {
             movements::get_white_pawn_attacks(king_bb)
         } else {
             movements::get_black_pawn_attacks(king_bb)
@@ -44,29 +44,188 @@ impl Board {
 
     // Returns a bitboard indicating which squares attack that square.
     pub fn attacks_to(&self, square: Square) -> BitBoard {
+        self.attacks_to_with_occupancy(square, self.occupied)
+    }
+
+    // Same as attacks_to(), but sliding pieces are blocked only by the occupied squares.
+    // Pieces not in occupied are excluded, which allows to find x-ray attackers.
+    pub fn attacks_to_with_occupancy(&self, square: Square, occupied: BitBoard) -> BitBoard {
         // From <https://www.chessprogramming.org/Square_Attacked_By#AnyAttackBySide>
 
         let bb = bitboard::from_square(square);
 
-        let white_pawns = self.pieces[Piece::WhitePawn as usize];
-        let black_pawns = self.pieces[Piece::BlackPawn as usize];
-        let knights =
-            self.pieces[Piece::WhiteKnight as usize] | self.pieces[Piece::BlackKnight as usize];
-        let kings = self.pieces[Piece::WhiteKing as usize] | self.pieces[Piece::BlackKing as usize];
+        let white_pawns = self.pieces_of(Piece::WhitePawn);
+        let black_pawns = self.pieces_of(Piece::BlackPawn);
+        let knights = self.pieces_of(Piece::WhiteKnight) | self.pieces_of(Piece::BlackKnight);
+        let kings = self.pieces_of(Piece::WhiteKing) | self.pieces_of(Piece::BlackKing);
         let mut rooks_queens =
-            self.pieces[Piece::WhiteQueen as usize] | self.pieces[Piece::BlackQueen as usize];
+            self.pieces_of(Piece::WhiteQueen) | self.pieces_of(Piece::BlackQueen);
         let mut bishops_queens = rooks_queens;
-        rooks_queens |=
-            self.pieces[Piece::WhiteRook as usize] | self.pieces[Piece::BlackRook as usize];
-        bishops_queens |=
-            self.pieces[Piece::WhiteBishop as usize] | self.pieces[Piece::BlackBishop as usize];
+        rooks_queens |= self.pieces_of(Piece::WhiteRook) | self.pieces_of(Piece::BlackRook);
+        bishops_queens |= self.pieces_of(Piece::WhiteBishop) | self.pieces_of(Piece::BlackBishop);
 
-        (movements::get_white_pawn_attacks(bb) & black_pawns)
+        ((movements::get_white_pawn_attacks(bb) & black_pawns)
             | (movements::get_black_pawn_attacks(bb) & white_pawns)
             | (movements::get_knight_attacks(bb) & knights)
             | (movements::get_king_attacks(bb) & kings)
-            | (movements::get_bishop_attacks(bb, self.occupied) & bishops_queens)
-            | (movements::get_rook_attacks(bb, self.occupied) & rooks_queens)
+            | (movements::get_bishop_attacks(bb, occupied) & bishops_queens)
+            | (movements::get_rook_attacks(bb, occupied) & rooks_queens))
+            & occupied
+    }
+
+    // The pieces of that color attacking the square.
+    pub fn attackers(&self, square: Square, color: Color) -> BitBoard {
+        self.attacks_to(square) & self.all_pieces_of_color(color)
+    }
+
+    pub fn count_attackers(&self, square: Square, color: Color) -> u32 {
+        bitboard::count_ones(self.attackers(square, color))
+    }
+
+    // The least valuable piece of that color attacking the square, the first to capture with in an exchange.
+    pub fn smallest_attacker(&self, square: Square, color: Color) -> Option<(Square, Piece)> {
+        self.smallest_attacker_with_occupancy(square, color, self.occupied)
+    }
+
+    // Same as smallest_attacker(), with the occupancy of attacks_to_with_occupancy().
+    pub fn smallest_attacker_with_occupancy(
+        &self,
+        square: Square,
+        color: Color,
+        occupied: BitBoard,
+    ) -> Option<(Square, Piece)> {
+        let attackers = self.attacks_to_with_occupancy(square, occupied);
+        // Pieces are ordered by value, pawns first and king last.
+        Piece::pieces_of_color(color).into_iter().find_map(|piece| {
+            let bb = self.pieces_of(piece) & attackers;
+            (!bitboard::is_empty(bb)).then(|| (bitboard::get_square(bb), piece))
+        })
+    }
+
+    // Checks if any piece of the specified color attacks that square.
+    pub fn is_square_attacked(&self, square: Square, by_color: Color) -> bool {
+        // Same approach as attacks_to(), we generate the attacks from the square
+        // and check if they hit a piece of the requested color.
+        let bb = bitboard::from_square(square);
+
+        let pawns = self.pieces_of(Piece::get_pawn_of(by_color));
+        let knights = self.pieces_of(Piece::get_knight_of(by_color));
+        let king = self.pieces_of(Piece::get_king_of(by_color));
+        let queens = self.pieces_of(Piece::get_queen_of(by_color));
+        let rooks_queens = queens | self.pieces_of(Piece::get_rook_of(by_color));
+        let bishops_queens = queens | self.pieces_of(Piece::get_bishop_of(by_color));
+
+        // Pawns attacks are reversed: A white pawn attacks the square if a black pawn on the square would attack it.
+        let pawn_attacks = if by_color.is_white() {
+            movements::get_black_pawn_attacks(bb)
+        } else {
+            movements::get_white_pawn_attacks(bb)
+        };
+
+        bitboard::contains(pawn_attacks, pawns)
+            || bitboard::contains(movements::get_knight_attacks(bb), knights)
+            || bitboard::contains(movements::get_king_attacks(bb), king)
+            || bitboard::contains(
+                movements::get_bishop_attacks(bb, self.occupied),
+                bishops_queens,
+            )
+            || bitboard::contains(movements::get_rook_attacks(bb, self.occupied), rooks_queens)
+    }
+
+    // Squares attacked by that piece if it was on that square, with the current occupancy.
+    // Squares with own pieces are included, and legality isn't checked. Pawns attack only diagonally.
+    pub fn get_attacks_for_piece(&self, sq: Square, piece: Piece) -> BitBoard {
+        let bb = bitboard::from_square(sq);
+        match piece.piece_type() {
+            PieceType::Pawn if piece.get_color().is_white() => {
+                movements::get_white_pawn_attacks(bb)
+            }
+            PieceType::Pawn => movements::get_black_pawn_attacks(bb),
+            PieceType::Knight => movements::get_knight_attacks(bb),
+            PieceType::Bishop => movements::get_bishop_attacks(bb, self.occupied),
+            PieceType::Rook => movements::get_rook_attacks(bb, self.occupied),
+            PieceType::Queen => {
+                movements::get_bishop_attacks(bb, self.occupied)
+                    | movements::get_rook_attacks(bb, self.occupied)
+            }
+            PieceType::King => movements::get_king_attacks(bb),
+        }
+    }
+
+    // All the squares attacked by the pieces of that color, whether they are empty or occupied.
+    pub fn attacked_squares(&self, color: Color) -> BitBoard {
+        let pawns = self.pieces_of(Piece::get_pawn_of(color));
+        let pawn_attacks = if color.is_white() {
+            movements::get_white_pawn_attacks(pawns)
+        } else {
+            movements::get_black_pawn_attacks(pawns)
+        };
+        let queens = self.pieces_of(Piece::get_queen_of(color));
+        let bishops_queens = queens | self.pieces_of(Piece::get_bishop_of(color));
+        let rooks_queens = queens | self.pieces_of(Piece::get_rook_of(color));
+
+        let sliding_attacks =
+            bitboard::iter_squares(bishops_queens)
+                .map(|sq| movements::get_bishop_attacks(bitboard::from_square(sq), self.occupied))
+                .chain(bitboard::iter_squares(rooks_queens).map(|sq| {
+                    movements::get_rook_attacks(bitboard::from_square(sq), self.occupied)
+                }))
+                .fold(0, |attacks, bb| attacks | bb);
+
+        pawn_attacks
+            | movements::get_knight_attacks(self.pieces_of(Piece::get_knight_of(color)))
+            | movements::get_king_attacks(self.pieces_of(Piece::get_king_of(color)))
+            | sliding_attacks
+    }
+
+    // Pieces of that color that cannot move off the line between their king and an opponent sliding piece.
+    // <https://www.chessprogramming.org/Checks_and_Pinned_Pieces_(Bitboards)#Pinned_Pieces>
+    pub fn pinned_pieces(&self, color: Color) -> BitBoard {
+        let king_bb = self.pieces_of(Piece::get_king_of(color));
+        if bitboard::is_empty(king_bb) {
+            return 0;
+        }
+        let opponent = color.opposite();
+        let queens = self.pieces_of(Piece::get_queen_of(opponent));
+        let rooks_queens = queens | self.pieces_of(Piece::get_rook_of(opponent));
+        let bishops_queens = queens | self.pieces_of(Piece::get_bishop_of(opponent));
+
+        // Opponent sliding pieces that would attack the king on an empty board.
+        let snipers = (movements::get_rook_attacks(king_bb, 0) & rooks_queens)
+            | (movements::get_bishop_attacks(king_bb, 0) & bishops_queens);
+        let king_square = bitboard::get_square(king_bb);
+        let mut pinned = 0;
+        for sniper in bitboard::iter_squares(snipers) {
+            let blockers = bitboard::ray_between(king_square, sniper) & self.occupied;
+            if bitboard::is_one(blockers) {
+                pinned |= blockers & self.all_pieces_of_color(color);
+            }
+        }
+        pinned
+    }
+
+    // The opponent pieces giving check to the king of the side to move.
+    pub fn checkers(&self) -> BitBoard {
+        self.attacks_king(self.side_to_move)
+    }
+
+    // Checks if the king of the side to move is attacked.
+    pub fn is_in_check(&self) -> bool {
+        let king_bb = self.pieces_of(Piece::get_king_of(self.side_to_move));
+        if bitboard::is_empty(king_bb) {
+            // Some test positions have no king.
+            return false;
+        }
+        let king_square = bitboard::get_square(king_bb);
+        self.is_square_attacked(king_square, self.opposite_side())
+    }
+
+    pub fn is_checkmate(&self) -> bool {
+        self.is_in_check() && !self.has_legal_moves()
+    }
+
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_in_check() && !self.has_legal_moves()
     }
 }
 
@@ -84,6 +243,246 @@ mod tests {
         assert_eq!(attacks_king_bb, attacks_bb);
     }
 
+    #[test]
+    fn test_get_attacks_for_piece() {
+        let board: Board = "4k3/8/8/8/3p4/8/1B6/4K3 w - - 0 1".into();
+        // Blocked by the pawn, and own pieces are included.
+        assert_eq!(
+            board.get_attacks_for_piece(Square::B2, Piece::WhiteBishop),
+            bitboard::from_squares(&[Square::A1, Square::C1, Square::A3, Square::C3, Square::D4])
+        );
+        // The piece doesn't need to be on the square.
+        assert_eq!(
+            board.get_attacks_for_piece(Square::D2, Piece::WhiteRook),
+            bitboard::from_squares(&[
+                Square::D1,
+                Square::D3,
+                Square::D4,
+                Square::C2,
+                Square::B2,
+                Square::E2,
+                Square::F2,
+                Square::G2,
+                Square::H2
+            ])
+        );
+        assert_eq!(
+            board.get_attacks_for_piece(Square::D4, Piece::BlackPawn),
+            bitboard::from_squares(&[Square::C3, Square::E3])
+        );
+        assert_eq!(
+            board.get_attacks_for_piece(Square::A1, Piece::WhiteKnight),
+            bitboard::from_squares(&[Square::B3, Square::C2])
+        );
+    }
+
+    #[test]
+    fn test_is_square_attacked() {
+        let board: Board = "4k3/8/8/8/4N3/8/8/3QK3 w - - 0 1".into();
+        // Queen on an open file.
+        assert!(board.is_square_attacked(Square::D8, Color::White));
+        assert!(!board.is_square_attacked(Square::D5, Color::Black));
+        // Knight doesn't attack the square next to it.
+        assert!(!board.is_square_attacked(Square::E5, Color::White));
+        assert!(board.is_square_attacked(Square::F6, Color::White));
+        // Own king.
+        assert!(board.is_square_attacked(Square::F2, Color::White));
+        assert!(board.is_square_attacked(Square::F7, Color::Black));
+
+        // Blocked file.
+        let board: Board = "4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1".into();
+        assert!(board.is_square_attacked(Square::D5, Color::White));
+        assert!(!board.is_square_attacked(Square::D6, Color::White));
+    }
+
+    #[test]
+    fn test_attackers() {
+        // The black queen is behind the rook, so it is not an attacker.
+        let board: Board = "3qk3/3r4/5n2/3P4/8/2N5/8/3QK3 w - - 0 1".into();
+        assert_eq!(
+            board.attackers(Square::D5, Color::White),
+            bitboard::from_squares(&[Square::C3, Square::D1])
+        );
+        assert_eq!(
+            board.attackers(Square::D5, Color::Black),
+            bitboard::from_squares(&[Square::D7, Square::F6])
+        );
+        assert_eq!(board.count_attackers(Square::D5, Color::Black), 2);
+        assert_eq!(board.count_attackers(Square::H8, Color::White), 0);
+        // Defenders count too: queen, king and knight.
+        assert_eq!(board.count_attackers(Square::D7, Color::Black), 3);
+    }
+
+    #[test]
+    fn test_smallest_attacker() {
+        let board: Board = "3qk3/3r4/5n2/3P4/8/2N5/8/3QK3 w - - 0 1".into();
+        assert_eq!(
+            board.smallest_attacker(Square::D5, Color::White),
+            Some((Square::C3, Piece::WhiteKnight))
+        );
+        assert_eq!(
+            board.smallest_attacker(Square::D5, Color::Black),
+            Some((Square::F6, Piece::BlackKnight))
+        );
+        assert_eq!(
+            board.smallest_attacker(Square::E7, Color::Black),
+            Some((Square::D7, Piece::BlackRook))
+        );
+        assert_eq!(
+            board.smallest_attacker(Square::C2, Color::White),
+            Some((Square::D1, Piece::WhiteQueen))
+        );
+        assert_eq!(
+            board.smallest_attacker(Square::F2, Color::White),
+            Some((Square::E1, Piece::WhiteKing))
+        );
+        assert_eq!(board.smallest_attacker(Square::H4, Color::White), None);
+
+        // Once the rook is gone, the queen behind it attacks.
+        let occupied = board.get_occupied() & !bitboard::from_square(Square::D7);
+        assert_eq!(
+            board.smallest_attacker_with_occupancy(Square::D6, Color::Black, occupied),
+            Some((Square::D8, Piece::BlackQueen))
+        );
+    }
+
+    #[test]
+    fn test_is_square_attacked_by_pawns() {
+        let board: Board = "4k3/8/3p4/8/8/4P3/8/4K3 w - - 0 1".into();
+        assert!(board.is_square_attacked(Square::D4, Color::White));
+        assert!(board.is_square_attacked(Square::F4, Color::White));
+        assert!(!board.is_square_attacked(Square::E4, Color::White));
+        assert!(!board.is_square_attacked(Square::D3, Color::White));
+
+        assert!(board.is_square_attacked(Square::C5, Color::Black));
+        assert!(board.is_square_attacked(Square::E5, Color::Black));
+        assert!(!board.is_square_attacked(Square::D5, Color::Black));
+        assert!(!board.is_square_attacked(Square::E6, Color::Black));
+    }
+
+    #[test]
+    fn test_is_in_check() {
+        let board = Board::initial_board();
+        assert!(!board.is_in_check());
+
+        let board: Board = "4k3/8/8/8/8/8/8/4RK2 b - - 0 1".into();
+        assert!(board.is_in_check());
+        // Only the side to move matters.
+        let board: Board = "4k3/8/8/8/8/8/8/4RK2 w - - 0 1".into();
+        assert!(!board.is_in_check());
+
+        let board: Board = "4k3/8/8/8/8/8/3p4/4K3 w - - 0 1".into();
+        assert!(board.is_in_check());
+    }
+
+    #[test]
+    fn test_is_checkmate_is_stalemate() {
+        let board = Board::initial_board();
+        assert!(!board.is_checkmate());
+        assert!(!board.is_stalemate());
+
+        // Fool's mate.
+        let board: Board = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3".into();
+        assert!(board.is_checkmate());
+        assert!(!board.is_stalemate());
+
+        // Back rank mate.
+        let board: Board = "R6k/6pp/8/8/8/8/8/6K1 b - - 0 1".into();
+        assert!(board.is_checkmate());
+
+        // In check but can escape.
+        let board: Board = "4k3/8/8/8/8/8/8/4RK2 b - - 0 1".into();
+        assert!(!board.is_checkmate());
+
+        let board: Board = "k7/8/1Q6/8/8/8/8/7K b - - 0 1".into();
+        assert!(board.is_stalemate());
+        assert!(!board.is_checkmate());
+    }
+
+    #[test]
+    fn test_attacked_squares() {
+        let board = Board::initial_board();
+        let white_attacks = board.attacked_squares(Color::White);
+        // Everything on ranks 2 and 3, and the first rank except the corners.
+        assert_eq!(
+            white_attacks,
+            bitboard::RANK_2
+                | bitboard::RANK_3
+                | (bitboard::RANK_1
+                    & !bitboard::from_square(Square::A1)
+                    & !bitboard::from_square(Square::H1))
+        );
+        // Black attacks are symmetrical.
+        assert_eq!(
+            board.attacked_squares(Color::Black),
+            white_attacks.swap_bytes()
+        );
+
+        // Consistent with is_square_attacked().
+        let board: Board = crate::fen::KIWIPETE.into();
+        for color in [Color::White, Color::Black] {
+            let attacks = board.attacked_squares(color);
+            for sq in 0..64u8 {
+                assert_eq!(
+                    bitboard::is_set(attacks, sq),
+                    board.is_square_attacked(sq.into(), color)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_checkers() {
+        assert_eq!(Board::initial_board().checkers(), 0);
+
+        // Rook gives check, the other rook doesn't.
+        let board: Board = "4k3/8/8/8/8/8/8/r3K2r w - - 0 1".into();
+        assert_eq!(
+            board.checkers(),
+            bitboard::from_square(Square::A1) | Square::H1
+        );
+        let board: Board = "4k3/8/8/8/8/8/8/r2NK2r w - - 0 1".into();
+        assert_eq!(board.checkers(), bitboard::from_square(Square::H1));
+
+        // Double check by a knight and a bishop.
+        let board: Board = "4k3/8/8/8/1b6/3n4/8/4K3 w - - 0 1".into();
+        assert_eq!(
+            board.checkers(),
+            bitboard::from_square(Square::B4) | Square::D3
+        );
+    }
+
+    #[test]
+    fn test_pinned_pieces() {
+        // Bishop pinned by a queen on the diagonal.
+        let board: Board = "4k3/8/8/8/q7/8/2B5/3K4 w - - 0 1".into();
+        assert_eq!(
+            board.pinned_pieces(Color::White),
+            bitboard::from_square(Square::C2)
+        );
+        assert_eq!(board.pinned_pieces(Color::Black), 0);
+
+        // Knight pinned on the file, pawn pinned on the rank, the knight on f3 isn't in line.
+        let board: Board = "4r3/8/8/8/4N3/5N2/8/r1P1K3 w - - 0 1".into();
+        assert_eq!(
+            board.pinned_pieces(Color::White),
+            bitboard::from_square(Square::E4) | Square::C1
+        );
+
+        // Two pieces between the king and the rook, none is pinned.
+        let board: Board = "4r3/8/4p3/8/4N3/8/8/4K3 w - - 0 1".into();
+        assert_eq!(board.pinned_pieces(Color::White), 0);
+
+        // A piece of the attacker color isn't pinned.
+        let board: Board = "4r3/8/8/4p3/8/8/8/4K3 w - - 0 1".into();
+        assert_eq!(board.pinned_pieces(Color::White), 0);
+        assert_eq!(board.pinned_pieces(Color::Black), 0);
+
+        // Bishops don't pin on files.
+        let board: Board = "4b3/8/8/8/4N3/8/8/4K3 w - - 0 1".into();
+        assert_eq!(board.pinned_pieces(Color::White), 0);
+    }
+
     #[test]
     fn test_attacks_king_king_next_to_king() {
         let board: Board = "8/2kp4/1K6/2P4r/8/8/8/8 w - - 1 2".into();
@@ -91,7 +490,7 @@ mod tests {
         // Not allowed to move next to opponent king.
         assert_eq!(
             bb,
-            0b0000000000000100000000000000000000000000000000000000000000000000
+            0b0000_0000_0000_0100_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000
         );
     }
 }
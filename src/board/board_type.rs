@@ -3,11 +3,12 @@ use itertools::Itertools;
 use crate::{
     bitboard::{self, from_array, BitBoard},
     common::{Color, Piece, Square},
-    fen,
+    epd::{self, EpdError, EpdOps},
+    fen::{self, FenError},
     moves::Move,
 };
 
-use super::{Board, CastlingAbility};
+use super::{castling::STANDARD_CASTLING_ROOKS, Board, CastlingAbility};
 
 fn get_all_bitboards(pieces: &[BitBoard]) -> [BitBoard; 2] {
     pieces.iter().enumerate().fold([0, 0], |mut acc, (i, bb)| {
@@ -29,6 +30,11 @@ impl Board {
             side_to_move: Color::White,
             en_passant_target_square: None,
             castling_ability: CastlingAbility::NONE,
+            castling_rooks: STANDARD_CASTLING_ROOKS,
+            chess960: false,
+            half_move_clock: 0,
+            full_move_counter: 1,
+            hash: 0,
         }
     }
 
@@ -36,25 +42,36 @@ impl Board {
         let pieces = bitboard::INITIAL_BOARD;
         let all = get_all_bitboards(&pieces);
         let occupied = get_occupied_bitboard(&all);
-        Self {
+        let mut board = Self {
             pieces,
             all,
             occupied,
             side_to_move: Color::White,
             en_passant_target_square: None,
             castling_ability: CastlingAbility::ALL,
-        }
+            castling_rooks: STANDARD_CASTLING_ROOKS,
+            chess960: false,
+            half_move_clock: 0,
+            full_move_counter: 1,
+            hash: 0,
+        };
+        board.hash = board.compute_hash();
+        board
     }
 
+    // Panics if the FEN string is invalid.
     pub fn from_fen(fen: &str) -> Self {
-        let (
-            piece_placement,
-            side_to_move,
-            castling_ability,
-            en_passant_target_square,
-            _half_move_clock,
-            _full_move_counter,
-        ) = fen::parse(fen);
+        Self::try_from_fen(fen).unwrap_or_else(|err| panic!("Invalid FEN \"{fen}\": {err}"))
+    }
+
+    // Parses an EPD line, returning the board and the operations.
+    pub fn from_epd(epd: &str) -> Result<(Self, EpdOps), EpdError> {
+        epd::parse(epd)
+    }
+
+    pub fn try_from_fen(fen: &str) -> Result<Self, FenError> {
+        let fields = fen::try_parse(fen)?;
+        let piece_placement = fields.piece_placement;
 
         let pieces = Piece::ALL_PIECES
             .iter()
@@ -75,18 +92,27 @@ impl Board {
 
         let all = get_all_bitboards(&pieces);
         let occupied = get_occupied_bitboard(&all);
-        let castling_ability = CastlingAbility::new(&castling_ability);
-        Self {
+        let mut board = Self {
             pieces,
             all,
             occupied,
-            side_to_move,
-            en_passant_target_square,
-            castling_ability,
-        }
+            side_to_move: fields.side_to_move,
+            en_passant_target_square: fields.en_passant,
+            castling_ability: CastlingAbility::NONE,
+            castling_rooks: STANDARD_CASTLING_ROOKS,
+            chess960: false,
+            half_move_clock: fields.half_move_clock,
+            full_move_counter: fields.full_move_counter,
+            hash: 0,
+        };
+        board.set_castling_from_fen(&fields.castling_ability, &fields.castling_rook_files);
+        board.hash = board.compute_hash();
+        Ok(board)
     }
 
-    pub fn as_fen(&self) -> String {
+    // Board is Copy, but too big to be passed by value.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_fen(&self) -> String {
         let piece_placement = (0..8)
             .rev()
             .flat_map(|rank| {
@@ -103,55 +129,86 @@ impl Board {
                 })
             })
             .collect_vec();
+        let (castling_ability, castling_rook_files) = self.castling_for_fen();
         fen::create(
             &piece_placement,
             self.side_to_move,
-            &self.castling_ability.as_pieces_iter().collect_vec(),
+            &castling_ability,
+            &castling_rook_files,
             self.en_passant_target_square,
-            0,
-            1,
+            self.half_move_clock.into(),
+            self.full_move_counter.into(),
         )
     }
 
+    // The piece on each square, index 0 being A1.
+    #[allow(clippy::wrong_self_convention, clippy::cast_possible_truncation)]
+    pub fn to_piece_array(&self) -> [Option<Piece>; 64] {
+        std::array::from_fn(|i| self.get_piece_at(Square::from(i as u8)))
+    }
+
+    // Bitboard of all the pieces of that kind.
+    // The pieces array is indexed by the Piece value, in the order of Piece::ALL_PIECES:
+    // White pieces are at even indexes, black ones at odd indexes. The all array is indexed by Color.
+    // Outside of the code updating the bitboards, use these accessors instead of indexing directly.
+    pub fn pieces_of(&self, piece: Piece) -> BitBoard {
+        self.pieces[piece as usize]
+    }
+
+    // Bitboard of all the pieces of that color.
+    pub fn all_pieces_of_color(&self, color: Color) -> BitBoard {
+        self.all[color as usize]
+    }
+
+    // Bitboard of all the pieces, of both colors.
+    pub fn get_occupied(&self) -> BitBoard {
+        self.occupied
+    }
+
     pub fn get_side_to_move(&self) -> Color {
         self.side_to_move
     }
 
+    pub fn get_half_move_clock(&self) -> u8 {
+        self.half_move_clock
+    }
+
+    pub fn get_castling_ability(&self) -> CastlingAbility {
+        self.castling_ability
+    }
+
     pub fn opposite_side(&self) -> Color {
         self.side_to_move.opposite()
     }
 
-    pub fn find_piece_on(&self, sq: Square) -> Piece {
-        let index = sq as u8;
-        *Piece::ALL_PIECES
+    // The piece on that square, None if it's empty.
+    #[inline]
+    pub fn get_piece_at(&self, sq: Square) -> Option<Piece> {
+        if !bitboard::contains_square(self.occupied, sq) {
+            return None;
+        }
+        // Only look at the pieces of the right color.
+        let color = usize::from(!bitboard::contains_square(
+            self.all_pieces_of_color(Color::White),
+            sq,
+        ));
+        Piece::ALL_PIECES
             .iter()
-            .find(|&&p| bitboard::is_set(self.pieces[p as usize], index))
-            .unwrap()
+            .skip(color)
+            .step_by(2)
+            .find(|&&p| bitboard::contains_square(self.pieces_of(p), sq))
+            .copied()
+    }
+
+    // Same as get_piece_at(), but crashes if the square is empty.
+    pub fn find_piece_on(&self, sq: Square) -> Piece {
+        self.get_piece_at(sq).unwrap()
     }
 
     // Creates a valid move based on this board.
     // If there are no pieces on the from position, the code will crash.
     pub fn new_move_from_pure(&self, s: &str) -> Move {
-        assert!(s.len() >= 4 && s.len() <= 5);
-        let from: Square = s[0..2].try_into().unwrap();
-        let to: Square = s[2..4].try_into().unwrap();
-
-        let piece = self.find_piece_on(from);
-        let to_bb: BitBoard = bitboard::from_square(to);
-        let is_capture = self.occupied & to_bb != 0;
-        let promotion = if piece.is_pawn() && to.is_promotion_rank_for(piece.get_color()) {
-            let promotion_piece = match &s[4..5] {
-                "q" => Piece::get_queen_of(piece.get_color()),
-                "r" => Piece::get_rook_of(piece.get_color()),
-                "b" => Piece::get_bishop_of(piece.get_color()),
-                "n" => Piece::get_knight_of(piece.get_color()),
-                _ => panic!("Invalid promotion flag"),
-            };
-            Some(promotion_piece)
-        } else {
-            None
-        };
-        Move::new(from, to, promotion, piece, is_capture)
+        Move::from_uci(s, self).unwrap()
     }
 }
 
@@ -162,6 +219,21 @@ impl From<&str> for Board {
     }
 }
 
+// Creates the board from the piece on each square, index 0 being A1.
+// White is to move, and there are no castling rights.
+impl From<[Option<Piece>; 64]> for Board {
+    #[allow(clippy::cast_possible_truncation)]
+    fn from(value: [Option<Piece>; 64]) -> Self {
+        let mut board = Board::empty();
+        for (i, piece) in value.into_iter().enumerate() {
+            if let Some(piece) = piece {
+                board.set_piece(Square::from(i as u8), piece);
+            }
+        }
+        board
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,6 +246,7 @@ mod tests {
         assert_eq!(board, fen::START_POSITION.into());
         assert_eq!(board.side_to_move, Color::White);
         assert_eq!(board.en_passant_target_square, None);
+        assert_eq!(board.hash, board.compute_hash());
     }
 
     #[test]
@@ -182,10 +255,105 @@ mod tests {
         assert_eq!(board.pieces, [0; 12]);
         assert_eq!(board.all, [0; 2]);
         assert_eq!(board.occupied, 0);
+        assert_eq!(board.hash, board.compute_hash());
         assert_eq!(board.side_to_move, Color::White);
         assert_eq!(board.en_passant_target_square, None);
     }
 
+    #[test]
+    fn test_pieces_accessors() {
+        let board = Board::initial_board();
+        assert_eq!(
+            board.pieces_of(Piece::WhiteRook),
+            bitboard::from_square(Square::A1) | Square::H1
+        );
+        assert_eq!(
+            board.pieces_of(Piece::BlackKing),
+            bitboard::from_square(Square::E8)
+        );
+        assert_eq!(board.all_pieces_of_color(Color::White), 0xFFFF);
+        assert_eq!(
+            board.all_pieces_of_color(Color::Black),
+            0xFFFF_0000_0000_0000
+        );
+        assert_eq!(
+            board.all_pieces_of_color(Color::White) | board.all_pieces_of_color(Color::Black),
+            board.get_occupied()
+        );
+    }
+
+    #[test]
+    fn test_try_from_fen() {
+        assert_eq!(
+            Board::try_from_fen(fen::START_POSITION),
+            Ok(Board::initial_board())
+        );
+        assert_eq!(
+            Board::try_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1"),
+            Err(FenError::InvalidSideToMove)
+        );
+    }
+
+    #[test]
+    fn test_to_fen_round_trip() {
+        for fen in [
+            fen::START_POSITION,
+            fen::KIWIPETE,
+            fen::EMPTY_BOARD,
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w Kq f6 0 3",
+            "rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b Qk d3 0 3",
+            "8/5k2/8/8/8/2K5/8/8 b - - 37 61",
+        ] {
+            assert_eq!(Board::from_fen(fen).to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_chess960_fen() {
+        let board = Board::from_fen(fen::START_POSITION);
+        assert!(!board.is_chess960());
+
+        // Outermost rooks are written as KQkq (X-FEN).
+        let board: Board =
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9".into();
+        assert!(board.is_chess960());
+        assert_eq!(board.get_castling_rook(Color::White, true), Square::H1);
+        assert_eq!(board.get_castling_rook(Color::White, false), Square::F1);
+        assert_eq!(board.get_castling_rook(Color::Black, false), Square::F8);
+        assert_eq!(
+            board.to_fen(),
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9"
+        );
+        assert_eq!(Board::from_fen(&board.to_fen()), board);
+
+        // Other rooks need the file (Shredder-FEN).
+        let fen = "1r2k1r1/8/8/8/8/8/8/RR2K1R1 w BGbg - 0 1";
+        let board: Board = fen.into();
+        assert_eq!(board.to_fen(), "1r2k1r1/8/8/8/8/8/8/RR2K1R1 w KBkq - 0 1");
+        assert_eq!(Board::from_fen(&board.to_fen()), board);
+    }
+
+    #[test]
+    fn test_to_fen_after_moves() {
+        let mut board = Board::initial_board();
+        board.make_move(board.new_move_from_pure("g1f3"));
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1"
+        );
+        board.make_move(board.new_move_from_pure("e7e5"));
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/8/5N2/PPPPPPPP/RNBQKB1R w KQkq e6 0 2"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid FEN")]
+    fn test_from_fen_invalid() {
+        let _: Board = "invalid".into();
+    }
+
     #[test]
     fn test_from_fen() {
         let board: Board = fen::START_POSITION.into();
@@ -195,4 +363,61 @@ mod tests {
         assert_eq!(board, Board::initial_board());
         assert_eq!(board.en_passant_target_square, None);
     }
+
+    #[test]
+    fn test_piece_array() {
+        let mut pieces = [None; 64];
+        pieces[Square::E1 as usize] = Some(Piece::WhiteKing);
+        pieces[Square::E8 as usize] = Some(Piece::BlackKing);
+        pieces[Square::D2 as usize] = Some(Piece::WhitePawn);
+        let board = Board::from(pieces);
+        assert_eq!(board, "4k3/8/8/8/8/8/3P4/4K3 w - - 0 1".into());
+        assert_eq!(board.to_piece_array(), pieces);
+
+        let board: Board = fen::KIWIPETE.into();
+        assert_eq!(
+            Board::from(board.to_piece_array()).to_fen(),
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w - - 0 1"
+        );
+    }
+
+    #[test]
+    fn test_get_piece_at() {
+        let board = Board::initial_board();
+        assert_eq!(board.get_piece_at(Square::A1), Some(Piece::WhiteRook));
+        assert_eq!(board.get_piece_at(Square::E1), Some(Piece::WhiteKing));
+        assert_eq!(board.get_piece_at(Square::D8), Some(Piece::BlackQueen));
+        assert_eq!(board.get_piece_at(Square::G7), Some(Piece::BlackPawn));
+        assert_eq!(board.get_piece_at(Square::E4), None);
+
+        let board: Board = fen::KIWIPETE.into();
+        for sq in 0..64u8 {
+            let square: Square = sq.into();
+            let expected = Piece::ALL_PIECES
+                .into_iter()
+                .find(|&p| bitboard::is_set(board.pieces_of(p), sq));
+            assert_eq!(board.get_piece_at(square), expected);
+        }
+    }
+
+    #[test]
+    fn test_get_castling_ability() {
+        let mut board: Board = "r3k2r/8/8/8/8/8/6b1/R3K2R b KQkq - 0 1".into();
+        assert_eq!(board.get_castling_ability(), CastlingAbility::ALL);
+
+        // Rook captured.
+        board.make_move(board.new_move_from_pure("g2h1"));
+        let castling_ability = board.get_castling_ability();
+        assert!(!castling_ability.white_can_castle_king_side());
+        assert!(castling_ability.white_can_castle_queen_side());
+        assert!(board.to_fen().contains(" Qkq "));
+
+        // King moved.
+        board.make_move(board.new_move_from_pure("e1d1"));
+        board.make_move(board.new_move_from_pure("a8b8"));
+        assert!(!board
+            .get_castling_ability()
+            .can_castle_queen_side(Color::White));
+        assert!(board.to_fen().contains(" k "));
+    }
 }
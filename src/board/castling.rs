@@ -1,13 +1,26 @@
 use std::fmt::Display;
 
-use crate::common::{Color, Piece, Square};
+use crate::{
+    bitboard,
+    common::{Color, Piece, Square},
+    fen::RookFile,
+    moves::Move,
+};
 
-use super::CastlingAbility;
+use super::{Board, CastlingAbility};
+
+// Where the castling rooks start in standard chess, in the order of the castling ability bits.
+pub const STANDARD_CASTLING_ROOKS: [Square; 4] = [Square::H1, Square::A1, Square::H8, Square::A8];
 
 impl CastlingAbility {
     pub const ALL: CastlingAbility = CastlingAbility(0b1111);
     pub const NONE: CastlingAbility = CastlingAbility(0b0000);
 
+    // Index of the bit for that castling, also used for the castling rooks array.
+    pub fn index(color: Color, king_side: bool) -> usize {
+        color as usize * 2 + usize::from(!king_side)
+    }
+
     fn get_mask_for_piece(piece: Piece) -> u8 {
         match piece {
             Piece::WhiteKing => 0b0001,
@@ -54,6 +67,20 @@ impl CastlingAbility {
         self.0 & (0b0010 << ((color as u8) * 2)) != 0
     }
 
+    pub fn revoke_king_side(&mut self, color: Color) {
+        self.0 &= !(1 << Self::index(color, true));
+    }
+
+    pub fn revoke_queen_side(&mut self, color: Color) {
+        self.0 &= !(1 << Self::index(color, false));
+    }
+
+    // Both sides, when the king moves.
+    pub fn revoke_all(&mut self, color: Color) {
+        self.revoke_king_side(color);
+        self.revoke_queen_side(color);
+    }
+
     pub fn as_pieces_iter(self) -> impl Iterator<Item = Piece> {
         [
             (self.white_can_castle_king_side(), Piece::WhiteKing),
@@ -120,6 +147,122 @@ impl CastlingAbility {
     pub fn clear(&mut self, sq: Square) {
         self.0 &= Self::UPDATE_ARRAY[sq as usize];
     }
+
+    // Chess960 version of clear(), since the king and rooks can start on any square.
+    pub fn clear_chess960(&mut self, mv: Move, castling_rooks: [Square; 4]) {
+        if mv.get_piece().is_king() {
+            self.revoke_all(mv.get_piece().get_color());
+        }
+        for (i, rook) in castling_rooks.into_iter().enumerate() {
+            if rook == mv.get_from() || rook == mv.get_to() {
+                self.0 &= !(1 << i);
+            }
+        }
+    }
+}
+
+impl Board {
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    // Chess960 can only be turned off if the castling rooks are on the A and H files.
+    pub fn set_chess960(&mut self, chess960: bool) {
+        debug_assert!(chess960 || self.castling_rooks == STANDARD_CASTLING_ROOKS);
+        self.chess960 = chess960;
+    }
+
+    // The square where the rook used for that castling starts.
+    pub fn get_castling_rook(&self, color: Color, king_side: bool) -> Square {
+        self.castling_rooks[CastlingAbility::index(color, king_side)]
+    }
+
+    fn back_rank(color: Color) -> u8 {
+        if color.is_white() {
+            0
+        } else {
+            7
+        }
+    }
+
+    // File of the king if it's on its back rank.
+    fn castling_king_file(&self, color: Color) -> Option<u8> {
+        let king_bb =
+            self.pieces_of(Piece::get_king_of(color)) & bitboard::rank_mask(Self::back_rank(color));
+        (king_bb != 0).then(|| bitboard::get_square(king_bb).get_file())
+    }
+
+    // The outermost rook of that color on the back rank, on that side of the king.
+    fn find_outermost_rook(&self, color: Color, king_side: bool) -> Option<Square> {
+        let king_file = self.castling_king_file(color)?;
+        let rooks_bb = self.pieces_of(Piece::get_rook_of(color));
+        let mut rooks = (0..8)
+            .map(|file| Square::new(Self::back_rank(color), file))
+            .filter(|&sq| bitboard::contains_square(rooks_bb, sq));
+        if king_side {
+            rooks.rev().find(|sq| sq.get_file() > king_file)
+        } else {
+            rooks.find(|sq| sq.get_file() < king_file)
+        }
+    }
+
+    // Sets the castling ability and rooks from the FEN castling field.
+    // KQkq indicate the outermost rook on that side (X-FEN), the others are the rook files (Shredder-FEN).
+    // Chess960 mode is turned on if the kings or rooks aren't on their standard squares.
+    pub(super) fn set_castling_from_fen(
+        &mut self,
+        castling_ability: &[Piece],
+        castling_rook_files: &[RookFile],
+    ) {
+        let mut rights = 0;
+        for &piece in castling_ability {
+            let (color, king_side) = (piece.get_color(), piece.is_king());
+            let i = CastlingAbility::index(color, king_side);
+            // Without a rook, we keep the square that standard castling would use.
+            self.castling_rooks[i] = self
+                .find_outermost_rook(color, king_side)
+                .unwrap_or(STANDARD_CASTLING_ROOKS[i]);
+            rights |= 1 << i;
+        }
+        for &(color, file) in castling_rook_files {
+            let king_side = file > self.castling_king_file(color).unwrap_or(4);
+            let i = CastlingAbility::index(color, king_side);
+            self.castling_rooks[i] = Square::new(Self::back_rank(color), file);
+            rights |= 1 << i;
+        }
+        self.castling_ability = CastlingAbility(rights);
+
+        self.chess960 = !castling_rook_files.is_empty()
+            || (0..4).any(|i| {
+                let color = if i < 2 { Color::White } else { Color::Black };
+                rights & (1 << i) != 0
+                    && (self.castling_rooks[i] != STANDARD_CASTLING_ROOKS[i]
+                        || self.castling_king_file(color).is_some_and(|file| file != 4))
+            });
+    }
+
+    // The castling rights for the FEN string, as KQkq when possible (X-FEN), and otherwise as the rook files.
+    pub(super) fn castling_for_fen(&self) -> (Vec<Piece>, Vec<RookFile>) {
+        let mut castling_ability = Vec::new();
+        let mut castling_rook_files = Vec::new();
+        for piece in self.castling_ability.as_pieces_iter() {
+            let (color, king_side) = (piece.get_color(), piece.is_king());
+            let rook = self.get_castling_rook(color, king_side);
+            if !self.chess960 || self.find_outermost_rook(color, king_side) == Some(rook) {
+                castling_ability.push(piece);
+            } else {
+                castling_rook_files.push((color, rook.get_file()));
+            }
+        }
+        (castling_ability, castling_rook_files)
+    }
+}
+
+// Full castling rights, like in the initial position.
+impl Default for CastlingAbility {
+    fn default() -> Self {
+        Self::ALL
+    }
 }
 
 impl Display for CastlingAbility {
@@ -145,6 +288,26 @@ mod tests {
         assert!(!CastlingAbility::NONE.black_can_castle_queen_side());
     }
 
+    #[test]
+    fn test_revoke() {
+        let mut castling_ability = CastlingAbility::default();
+        assert_eq!(castling_ability, CastlingAbility::ALL);
+        assert_eq!(castling_ability.to_string(), "KQkq");
+
+        castling_ability.revoke_king_side(Color::White);
+        assert_eq!(castling_ability.to_string(), "Qkq");
+        castling_ability.revoke_queen_side(Color::Black);
+        assert_eq!(castling_ability.to_string(), "Qk");
+        assert!(castling_ability.can_castle_queen_side(Color::White));
+        assert!(castling_ability.can_castle_king_side(Color::Black));
+
+        castling_ability.revoke_all(Color::Black);
+        assert_eq!(castling_ability.to_string(), "Q");
+        castling_ability.revoke_all(Color::White);
+        assert_eq!(castling_ability, CastlingAbility::NONE);
+        assert_eq!(castling_ability.to_string(), "-");
+    }
+
     #[test]
     fn test_clear_white_king() {
         let mut castling_ability = CastlingAbility::ALL;
@@ -178,6 +341,25 @@ mod tests {
         assert!(!castling_ability.black_can_castle_queen_side());
     }
 
+    #[test]
+    fn test_clear_chess960() {
+        let rooks = [Square::G1, Square::B1, Square::G8, Square::B8];
+        let mut castling_ability = CastlingAbility::ALL;
+        castling_ability
+            .clear_chess960(Move::quiet(Square::B1, Square::B2, Piece::WhiteRook), rooks);
+        assert!(castling_ability.white_can_castle_king_side());
+        assert!(!castling_ability.white_can_castle_queen_side());
+
+        // Standard squares don't matter.
+        castling_ability
+            .clear_chess960(Move::quiet(Square::H8, Square::H7, Piece::BlackRook), rooks);
+        assert!(castling_ability.black_can_castle_king_side());
+
+        castling_ability
+            .clear_chess960(Move::quiet(Square::F8, Square::E8, Piece::BlackKing), rooks);
+        assert_eq!(castling_ability, CastlingAbility(0b0001));
+    }
+
     #[test]
     fn test_clear_black_queen_side() {
         let mut castling_ability = CastlingAbility::ALL;
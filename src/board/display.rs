@@ -11,9 +11,6 @@ use crate::{
 use super::Board;
 
 impl Board {
-    const ASCII_PIECES: [char; 12] = ['P', 'p', 'N', 'n', 'B', 'b', 'R', 'r', 'Q', 'q', 'K', 'k'];
-    const UNICODE_PIECES: [char; 12] = ['♙', '♟', '♘', '♞', '♗', '♝', '♖', '♜', '♕', '♛', '♔', '♚'];
-
     pub fn print(&self) {
         self.print_with_move(None);
     }
@@ -34,7 +31,7 @@ impl Board {
                 let mut piece_char = '.';
                 for (piece, bitboard) in self.pieces.iter().enumerate() {
                     if bitboard::is_set(*bitboard, index) {
-                        piece_char = Self::UNICODE_PIECES[piece];
+                        piece_char = Piece::ALL_PIECES[piece].unicode_symbol();
                         break;
                     }
                 }
@@ -54,14 +51,125 @@ impl Board {
         }
         println!(
             " {}  a b c d e f g h",
-            if self.get_side_to_move() == Color::White {
+            if self.get_side_to_move().is_white() {
                 "=>"
             } else {
                 "  "
             }
         );
         // println!();
-        // println!("FEN: {}", self.as_fen());
+        // println!("FEN: {}", self.to_fen());
+    }
+
+    // Board with some squares replaced by a mark, like '+' for attacked or 'x' for capture.
+    pub fn print_with_annotations(&self, highlights: &[(Square, char)]) {
+        print!("{}", self.render(highlights, false));
+    }
+
+    // Board with the squares of the bitboard marked, like the attacks of a piece.
+    pub fn print_bitboard_overlay(&self, bb: BitBoard, marker: char) {
+        print!("{}", self.render_overlay(bb, marker));
+    }
+
+    fn render_overlay(&self, bb: BitBoard, marker: char) -> String {
+        let highlights: Vec<(Square, char)> =
+            bitboard::iter_squares(bb).map(|sq| (sq, marker)).collect();
+        self.render(&highlights, false)
+    }
+
+    // Board with dark and light squares drawn with ANSI background colors.
+    // Without color, for outputs that aren't an ANSI terminal, it's the plain board.
+    pub fn print_colored(&self, use_color: bool) {
+        print!("{}", self.render(&[], use_color));
+    }
+
+    fn render(&self, highlights: &[(Square, char)], use_color: bool) -> String {
+        const LIGHT_BG: &str = "\x1b[47m";
+        const DARK_BG: &str = "\x1b[100m";
+        const RESET: &str = "\x1b[0m";
+        // Writing to a String never fails.
+        use std::fmt::Write as _;
+        let mut output = String::new();
+        for rank in (0..8).rev() {
+            write!(output, "  {} ", rank + 1).unwrap();
+            for file in 0..8 {
+                let index = rank * 8 + file;
+                let square: Square = index.into();
+
+                let piece_char =
+                    if let Some((_, mark)) = highlights.iter().find(|(sq, _)| *sq == square) {
+                        *mark
+                    } else {
+                        self.get_piece_at(square).map_or('.', Piece::unicode_symbol)
+                    };
+                if use_color {
+                    let bg = if bitboard::is_set(bitboard::LIGHT_SQUARES, index) {
+                        LIGHT_BG
+                    } else {
+                        DARK_BG
+                    };
+                    write!(output, "{bg} {piece_char}{RESET}").unwrap();
+                } else {
+                    write!(output, " {piece_char}").unwrap();
+                }
+            }
+            output.push('\n');
+        }
+        writeln!(
+            output,
+            " {}  a b c d e f g h",
+            if self.get_side_to_move().is_white() {
+                "=>"
+            } else {
+                "  "
+            }
+        )
+        .unwrap();
+        output
+    }
+
+    // Each move in SAN, next to a compact board where the from square is marked 'F' and the to square 'T'.
+    // Handy to check move generation visually.
+    pub fn print_moves(&self, moves: &[Move]) {
+        print!("{}", self.render_moves(moves));
+    }
+
+    fn render_moves(&self, moves: &[Move]) -> String {
+        // Writing to a String never fails.
+        use std::fmt::Write as _;
+        let mut output = String::new();
+        for &mv in moves {
+            let san = mv.to_san(self);
+            for (i, line) in self
+                .render_compact(&[(mv.get_from(), 'F'), (mv.get_to(), 'T')])
+                .iter()
+                .enumerate()
+            {
+                let label = if i == 0 { san.as_str() } else { "" };
+                writeln!(output, "{label:<8} {line}").unwrap();
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    // One string per rank, from rank 8 to rank 1, without spaces between the squares.
+    fn render_compact(&self, highlights: &[(Square, char)]) -> Vec<String> {
+        (0..8u8)
+            .rev()
+            .map(|rank| {
+                (0..8u8)
+                    .map(|file| {
+                        let square = Square::new(rank, file);
+                        if let Some((_, mark)) = highlights.iter().find(|(sq, _)| *sq == square) {
+                            *mark
+                        } else {
+                            self.get_piece_at(square).map_or('.', Piece::ascii_symbol)
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
     }
 
     pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn std::error::Error>> {
@@ -72,7 +180,7 @@ impl Board {
                 let mut piece_char = '.';
                 for (piece, bitboard) in self.pieces.iter().enumerate() {
                     if bitboard::is_set(*bitboard, index) {
-                        piece_char = Self::ASCII_PIECES[piece];
+                        piece_char = Piece::ALL_PIECES[piece].ascii_symbol();
                         break;
                     }
                 }
@@ -83,26 +191,26 @@ impl Board {
         writeln!(
             writer,
             " {}  a b c d e f g h",
-            if self.get_side_to_move() == Color::White {
+            if self.get_side_to_move().is_white() {
                 "=>"
             } else {
                 "  "
             }
         )?;
         writeln!(writer)?;
-        writeln!(writer, "FEN: {}", self.as_fen())?;
+        writeln!(writer, "FEN: {}", self.to_fen())?;
         Ok(())
     }
 
     pub fn print_bitboards(&self) {
         for piece in Piece::ALL_PIECES {
             println!("Bitboard for {piece}");
-            bitboard::print(self.pieces[piece as usize]);
+            bitboard::print(self.pieces_of(piece));
         }
         println!("Bitboard for occupied white");
-        bitboard::print(self.all[Color::White as usize]);
+        bitboard::print(self.all_pieces_of_color(Color::White));
         println!("Bitboard for occupied black");
-        bitboard::print(self.all[Color::Black as usize]);
+        bitboard::print(self.all_pieces_of_color(Color::Black));
         println!("Bitboard for occupied");
         bitboard::print(self.occupied);
     }
@@ -112,20 +220,19 @@ impl Board {
     // If there are no pieces on the from position, the code will crash.
     pub fn new_move(&self, from: Square, to: Square) -> Move {
         let piece = self.find_piece_on(from);
-        let to_bb: BitBoard = bitboard::from_square(to);
-        let is_capture = self.occupied & to_bb != 0;
+        let captured = self.get_piece_at(to);
         let promotion = if piece.is_pawn() && to.is_promotion_rank_for(piece.get_color()) {
             Some(Piece::get_queen_of(piece.get_color()))
         } else {
             None
         };
-        Move::new(from, to, promotion, piece, is_capture)
+        Move::new(from, to, promotion, piece, captured)
     }
 }
 
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.as_fen())
+        write!(f, "{}", self.to_fen())
     }
 }
 
@@ -171,4 +278,84 @@ mod tests {
         assert!(!mv.is_capture());
         assert_eq!(mv.get_promotion(), Some(Piece::WhiteQueen));
     }
+
+    #[test]
+    fn test_render_with_annotations() {
+        let board: Board = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1".into();
+        let output = board.render(&[(Square::A1, 'x'), (Square::A2, '+')], false);
+        assert_eq!(
+            output,
+            "  8  . . . . ♚ . . .
+  7  . . . . . . . .
+  6  . . . . . . . .
+  5  . . . . . . . .
+  4  . . . . . . . .
+  3  . . . . . . . .
+  2  + . . . . . . .
+  1  x . . . ♔ . . .
+ =>  a b c d e f g h
+"
+        );
+    }
+
+    #[test]
+    fn test_render_moves() {
+        let board: Board = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1".into();
+        let output = board.render_moves(&[
+            Move::quiet(Square::A1, Square::A8, Piece::WhiteRook),
+            Move::quiet(Square::E1, Square::D2, Piece::WhiteKing),
+        ]);
+        assert_eq!(
+            output,
+            "Ra8+     T...k...
+         ........
+         ........
+         ........
+         ........
+         ........
+         ........
+         F...K...
+
+Kd2      ....k...
+         ........
+         ........
+         ........
+         ........
+         ........
+         ...T....
+         R...F...
+
+"
+        );
+    }
+
+    #[test]
+    fn test_render_overlay() {
+        let board: Board = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1".into();
+        let attacks = board.get_attacks_for_piece(Square::A1, Piece::WhiteRook);
+        let output = board.render_overlay(attacks, '*');
+        assert_eq!(
+            output,
+            "  8  * . . . ♚ . . .
+  7  * . . . . . . .
+  6  * . . . . . . .
+  5  * . . . . . . .
+  4  * . . . . . . .
+  3  * . . . . . . .
+  2  * . . . . . . .
+  1  ♖ * * * * . . .
+ =>  a b c d e f g h
+"
+        );
+    }
+
+    #[test]
+    fn test_render_colored() {
+        let board = Board::initial_board();
+        let output = board.render(&[], true);
+        // A8 is light, H8 dark.
+        assert!(output.starts_with("  8 \x1b[47m ♜\x1b[0m\x1b[100m ♞"));
+        assert!(output.contains("\x1b[100m ♜\x1b[0m\n"));
+        assert!(!board.render(&[], false).contains('\x1b'));
+    }
 }
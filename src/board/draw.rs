@@ -0,0 +1,285 @@
+//! Draw detection.
+//! <https://www.chessprogramming.org/Draw>
+
+use std::fmt::Display;
+
+use crate::{
+    bitboard,
+    common::{Color, Piece},
+};
+
+use super::Board;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawReason {
+    FiftyMoveRule,
+    ThreefoldRepetition,
+    InsufficientMaterial,
+    // Only returned by game_result(), see draw_reason().
+    Stalemate,
+}
+
+// Result of the game in the current position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw(DrawReason),
+    Ongoing,
+}
+
+// In the format of the PGN result tag.
+impl Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                GameResult::WhiteWins => "1-0",
+                GameResult::BlackWins => "0-1",
+                GameResult::Draw(_) => "1/2-1/2",
+                GameResult::Ongoing => "*",
+            }
+        )
+    }
+}
+
+impl Display for DrawReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DrawReason::FiftyMoveRule => "fifty-move rule",
+                DrawReason::ThreefoldRepetition => "threefold repetition",
+                DrawReason::InsufficientMaterial => "insufficient material",
+                DrawReason::Stalemate => "stalemate",
+            }
+        )
+    }
+}
+
+// Hashes of the positions of the game, including the current one.
+#[derive(Debug, Clone, Default)]
+pub struct GameHistory(Vec<u64>);
+
+impl GameHistory {
+    // History with only the board position.
+    pub fn new(board: &Board) -> Self {
+        Self(vec![board.hash()])
+    }
+
+    pub fn push(&mut self, hash: u64) {
+        self.0.push(hash);
+    }
+
+    pub fn pop(&mut self) {
+        self.0.pop();
+    }
+
+    // How many times the position with this hash occurred.
+    pub fn count(&self, hash: u64) -> usize {
+        self.0.iter().filter(|&&h| h == hash).count()
+    }
+}
+
+impl Board {
+    // Fifty-move rule: No capture or pawn move in the last fifty moves of each side.
+    // <https://www.chessprogramming.org/Fifty-move_Rule>
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.half_move_clock >= 100
+    }
+
+    // Neither side can checkmate: KvK, KBvK, KNvK, and any number of bishops all on same colored squares.
+    // <https://www.chessprogramming.org/Draw_Evaluation#Insufficient_Material>
+    pub fn is_insufficient_material(&self) -> bool {
+        let material = self.material_count(Color::White) + self.material_count(Color::Black);
+        if material.pawns > 0 || material.major_pieces() > 0 {
+            return false;
+        }
+
+        let bishops_bb = self.pieces_of(Piece::WhiteBishop) | self.pieces_of(Piece::BlackBishop);
+        if material.minor_pieces() <= 1 {
+            return true;
+        }
+        material.knights == 0
+            && (bishops_bb & bitboard::LIGHT_SQUARES == 0
+                || bishops_bb & bitboard::DARK_SQUARES == 0)
+    }
+
+    // Threefold repetition: The same position occurred three times.
+    // <https://www.chessprogramming.org/Repetitions>
+    pub fn is_threefold_repetition(&self, history: &GameHistory) -> bool {
+        history.count(self.hash()) >= 3
+    }
+
+    // Why the game is drawn, None if it isn't.
+    // Stalemate isn't included, as it requires generating the moves, see is_stalemate().
+    pub fn draw_reason(&self, history: &GameHistory) -> Option<DrawReason> {
+        // A checkmate on the last move has priority over the fifty-move rule.
+        if self.is_fifty_move_draw() && !self.is_checkmate() {
+            Some(DrawReason::FiftyMoveRule)
+        } else if self.is_threefold_repetition(history) {
+            Some(DrawReason::ThreefoldRepetition)
+        } else if self.is_insufficient_material() {
+            Some(DrawReason::InsufficientMaterial)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_drawn(&self, history: &GameHistory) -> bool {
+        self.draw_reason(history).is_some()
+    }
+
+    // Checks all the ways the game can end. Checkmate is first, as it has priority over the fifty-move rule.
+    pub fn game_result(&self, history: &GameHistory) -> GameResult {
+        if self.has_legal_moves() {
+            self.draw_reason(history)
+                .map_or(GameResult::Ongoing, GameResult::Draw)
+        } else if !self.is_in_check() {
+            GameResult::Draw(DrawReason::Stalemate)
+        } else if self.get_side_to_move().is_white() {
+            GameResult::BlackWins
+        } else {
+            GameResult::WhiteWins
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_fifty_move_draw() {
+        let mut board: Board = "4k3/8/8/8/8/8/4P3/4K2R w K - 99 80".into();
+        assert!(!board.is_fifty_move_draw());
+        let state = board.make_move(board.new_move_from_pure("h1h2"));
+        assert!(board.is_fifty_move_draw());
+        board.unmake_move(board.new_move_from_pure("h2h1"), state);
+        assert!(!board.is_fifty_move_draw());
+
+        // Pawn moves reset the clock.
+        board.make_move(board.new_move_from_pure("e2e4"));
+        assert_eq!(board.get_half_move_clock(), 0);
+        assert!(!board.is_fifty_move_draw());
+    }
+
+    #[test]
+    fn test_is_threefold_repetition() {
+        let mut board = Board::initial_board();
+        let mut history = GameHistory::new(&board);
+        for _ in 0..2 {
+            for mv in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                assert!(!board.is_threefold_repetition(&history));
+                board.update_by_move(board.new_move_from_pure(mv));
+                history.push(board.hash());
+            }
+        }
+        assert_eq!(board.hash(), Board::initial_board().hash());
+        assert!(board.is_threefold_repetition(&history));
+
+        history.pop();
+        assert!(!board.is_threefold_repetition(&history));
+    }
+
+    #[test]
+    fn test_is_insufficient_material() {
+        for fen in [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",    // KvK
+            "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",  // KBvK
+            "4k3/8/8/8/8/8/8/4K1n1 w - - 0 1",  // KvKN
+            "2b1k3/8/8/8/8/8/8/4KB2 w - - 0 1", // KBvKB, both on light squares
+        ] {
+            let board: Board = fen.into();
+            assert!(board.is_insufficient_material(), "{fen}");
+        }
+
+        for fen in [
+            "4k3/8/8/8/8/8/8/4K2R w - - 0 1",   // KRvK
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",  // KPvK
+            "1b2k3/8/8/8/8/8/8/4KB2 w - - 0 1", // KBvKB, opposite colors
+            "4k3/8/8/8/8/8/8/4KBN1 w - - 0 1",  // KBNvK
+            Board::initial_board().to_fen().as_str(),
+        ] {
+            let board: Board = fen.into();
+            assert!(!board.is_insufficient_material(), "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_draw_reason() {
+        let board = Board::initial_board();
+        let history = GameHistory::new(&board);
+        assert_eq!(board.draw_reason(&history), None);
+        assert!(!board.is_drawn(&history));
+
+        let board: Board = "4k3/8/8/8/8/8/4P3/4K2R w K - 100 80".into();
+        let history = GameHistory::new(&board);
+        assert_eq!(board.draw_reason(&history), Some(DrawReason::FiftyMoveRule));
+        assert!(board.is_drawn(&history));
+
+        // Mate on the last move before the fifty-move limit.
+        let board: Board = "k6R/8/1K6/8/8/8/8/8 b - - 100 80".into();
+        let history = GameHistory::new(&board);
+        assert_eq!(board.draw_reason(&history), None);
+
+        let board: Board = "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1".into();
+        let history = GameHistory::new(&board);
+        assert_eq!(
+            board.draw_reason(&history),
+            Some(DrawReason::InsufficientMaterial)
+        );
+
+        let mut board = Board::initial_board();
+        let mut history = GameHistory::new(&board);
+        for _ in 0..2 {
+            for mv in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                board.update_by_move(board.new_move_from_pure(mv));
+                history.push(board.hash());
+            }
+        }
+        assert_eq!(
+            board.draw_reason(&history),
+            Some(DrawReason::ThreefoldRepetition)
+        );
+        assert_eq!(
+            DrawReason::ThreefoldRepetition.to_string(),
+            "threefold repetition"
+        );
+    }
+
+    #[test]
+    fn test_game_result() {
+        let board = Board::initial_board();
+        let history = GameHistory::new(&board);
+        assert_eq!(board.game_result(&history), GameResult::Ongoing);
+        assert_eq!(board.game_result(&history).to_string(), "*");
+
+        let board: Board = "k6R/8/1K6/8/8/8/8/8 b - - 100 80".into();
+        let history = GameHistory::new(&board);
+        assert_eq!(board.game_result(&history), GameResult::WhiteWins);
+        assert_eq!(board.game_result(&history).to_string(), "1-0");
+
+        // Fool's mate.
+        let board: Board = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3".into();
+        let history = GameHistory::new(&board);
+        assert_eq!(board.game_result(&history), GameResult::BlackWins);
+
+        let board: Board = "k7/8/1Q6/8/8/8/8/7K b - - 0 1".into();
+        let history = GameHistory::new(&board);
+        assert_eq!(
+            board.game_result(&history),
+            GameResult::Draw(DrawReason::Stalemate)
+        );
+        assert_eq!(board.game_result(&history).to_string(), "1/2-1/2");
+
+        let board: Board = "4k3/8/8/8/8/8/4P3/4K2R w K - 100 80".into();
+        let history = GameHistory::new(&board);
+        assert_eq!(
+            board.game_result(&history),
+            GameResult::Draw(DrawReason::FiftyMoveRule)
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Changing the pieces on the board directly, to build positions without a FEN string.
+
+use crate::{
+    bitboard,
+    common::{Piece, Square},
+};
+
+use super::{zobrist, Board};
+
+impl Board {
+    // The bitboards of all pieces must stay consistent with the individual piece bitboards.
+    // The other rules of validate() don't apply, as the position may be only partially built.
+    fn debug_check_invariants(&self) {
+        debug_assert_eq!(self.validate_bitboards(), Ok(()));
+        debug_assert_eq!(self.hash, self.compute_hash());
+    }
+
+    // Puts the piece on the square, replacing the piece that was there if any.
+    // Castling rights and the en passant square are not updated.
+    pub fn set_piece(&mut self, sq: Square, piece: Piece) {
+        self.remove_piece(sq);
+        let bb = bitboard::from_square(sq);
+        self.pieces[piece as usize] |= bb;
+        self.all[piece.get_color() as usize] |= bb;
+        self.occupied |= bb;
+        self.hash ^= zobrist::piece_key(piece, sq);
+        self.debug_check_invariants();
+    }
+
+    // Removes the piece on the square and returns it, None if it was empty.
+    pub fn remove_piece(&mut self, sq: Square) -> Option<Piece> {
+        let piece = self.get_piece_at(sq)?;
+        let bb = bitboard::from_square(sq);
+        self.pieces[piece as usize] &= !bb;
+        self.all[piece.get_color() as usize] &= !bb;
+        self.occupied &= !bb;
+        self.hash ^= zobrist::piece_key(piece, sq);
+        self.debug_check_invariants();
+        Some(piece)
+    }
+
+    // Moves the piece, capturing what is on the target square. Panics if the from square is empty.
+    // Unlike a real move, the side to move and the clocks are not changed.
+    pub fn move_piece(&mut self, from: Square, to: Square) {
+        let piece = self
+            .remove_piece(from)
+            .unwrap_or_else(|| panic!("No piece to move on {from}"));
+        self.set_piece(to, piece);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{Piece::*, Square::*},
+        fen,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_set_piece() {
+        let mut board = Board::empty();
+        board.set_piece(E1, WhiteKing);
+        board.set_piece(E8, BlackKing);
+        board.set_piece(D2, WhitePawn);
+        assert_eq!(board, "4k3/8/8/8/8/8/3P4/4K3 w - - 0 1".into());
+
+        // Replacing a piece.
+        board.set_piece(D2, BlackQueen);
+        assert_eq!(board, "4k3/8/8/8/8/8/3q4/4K3 w - - 0 1".into());
+    }
+
+    #[test]
+    fn test_remove_piece() {
+        let mut board = Board::initial_board();
+        assert_eq!(board.remove_piece(D1), Some(WhiteQueen));
+        assert_eq!(board.remove_piece(D1), None);
+        assert_eq!(board.remove_piece(E4), None);
+        assert_eq!(
+            board,
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1".into()
+        );
+    }
+
+    #[test]
+    fn test_move_piece() {
+        let mut board: Board = fen::KIWIPETE.into();
+        board.move_piece(E5, F7);
+        assert_eq!(board.get_piece_at(E5), None);
+        assert_eq!(board.get_piece_at(F7), Some(WhiteKnight));
+        assert_eq!(
+            board,
+            "r3k2r/p1ppqNb1/bn2pnp1/3P4/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1".into()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "No piece to move on e4")]
+    fn test_move_piece_empty() {
+        Board::initial_board().move_piece(E4, E5);
+    }
+
+    // The black bitboards are checked too, not only the white ones.
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "ColorMismatch(Black)")]
+    fn test_invariants_black_pieces() {
+        let mut board = Board::initial_board();
+        // Still consistent with the occupied bitboard.
+        board.pieces[BlackRook as usize] &= !bitboard::from_square(A8);
+        board.set_piece(E4, WhitePawn);
+    }
+}
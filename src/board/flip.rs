@@ -0,0 +1,74 @@
+//! Mirrored positions, to check that the evaluation and search treat both colors the same way.
+
+use crate::common::Square;
+
+use super::{Board, CastlingAbility};
+
+impl CastlingAbility {
+    // White castling rights become black ones and vice versa.
+    fn swap_colors(self) -> Self {
+        Self(((self.0 & 0b0011) << 2) | ((self.0 >> 2) & 0b0011))
+    }
+}
+
+impl Board {
+    // The same position with the colors swapped: The board is mirrored vertically, rank 1 becoming rank 8,
+    // white pieces become black, and the other side is to move.
+    // <https://www.chessprogramming.org/Flipping_Mirroring_and_Rotating#FlipVertically>
+    pub fn flip_vertical(&self) -> Self {
+        let mut board = Self {
+            // Mirroring the ranks is reversing the bytes, and colors alternate in the pieces array.
+            pieces: std::array::from_fn(|i| self.pieces[i ^ 1].swap_bytes()),
+            all: [self.all[1].swap_bytes(), self.all[0].swap_bytes()],
+            occupied: self.occupied.swap_bytes(),
+            side_to_move: self.side_to_move.opposite(),
+            en_passant_target_square: self
+                .en_passant_target_square
+                .map(|sq| Square::from(sq as u8 ^ 0x38)),
+            castling_ability: self.castling_ability.swap_colors(),
+            castling_rooks: std::array::from_fn(|i| {
+                Square::from(self.castling_rooks[i ^ 2] as u8 ^ 0x38)
+            }),
+            chess960: self.chess960,
+            half_move_clock: self.half_move_clock,
+            full_move_counter: self.full_move_counter,
+            hash: 0,
+        };
+        board.hash = board.compute_hash();
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fen;
+
+    use super::*;
+
+    #[test]
+    fn test_flip_vertical() {
+        let board: Board = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2".into();
+        assert_eq!(
+            board.flip_vertical(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 2".into()
+        );
+
+        let board: Board = "r3k3/8/8/8/8/8/5p2/4K2R w Kq - 3 10".into();
+        assert_eq!(
+            board.flip_vertical(),
+            "4k2r/5P2/8/8/8/8/8/R3K3 b Qk - 3 10".into()
+        );
+    }
+
+    #[test]
+    fn test_flip_vertical_twice() {
+        for fen in [fen::START_POSITION, fen::KIWIPETE] {
+            let board: Board = fen.into();
+            assert_eq!(board.flip_vertical().flip_vertical(), board);
+            assert_eq!(
+                board.flip_vertical().generate_legal_moves().len(),
+                board.generate_legal_moves().len()
+            );
+        }
+    }
+}
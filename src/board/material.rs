@@ -0,0 +1,150 @@
+//! Material on the board.
+
+use std::ops::Add;
+
+use crate::{
+    bitboard,
+    common::{Color, Piece},
+};
+
+use super::Board;
+
+// Weights of the pieces for the game phase. Pawns and kings don't count.
+const KNIGHT_PHASE: u32 = 4;
+const BISHOP_PHASE: u32 = 4;
+const ROOK_PHASE: u32 = 6;
+const QUEEN_PHASE: u32 = 12;
+// Phase of the initial position.
+const MAX_PHASE: u32 = 4 * KNIGHT_PHASE + 4 * BISHOP_PHASE + 4 * ROOK_PHASE + 2 * QUEEN_PHASE;
+
+// Number of pieces of each type, kings excluded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MaterialCount {
+    pub pawns: u32,
+    pub knights: u32,
+    pub bishops: u32,
+    pub rooks: u32,
+    pub queens: u32,
+}
+
+impl MaterialCount {
+    pub fn minor_pieces(self) -> u32 {
+        self.knights + self.bishops
+    }
+
+    pub fn major_pieces(self) -> u32 {
+        self.rooks + self.queens
+    }
+}
+
+// Material of both sides together.
+impl Add for MaterialCount {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            pawns: self.pawns + other.pawns,
+            knights: self.knights + other.knights,
+            bishops: self.bishops + other.bishops,
+            rooks: self.rooks + other.rooks,
+            queens: self.queens + other.queens,
+        }
+    }
+}
+
+impl Board {
+    pub fn material_count(&self, color: Color) -> MaterialCount {
+        let count = |piece: Piece| bitboard::count_ones(self.pieces_of(piece));
+        MaterialCount {
+            pawns: count(Piece::get_pawn_of(color)),
+            knights: count(Piece::get_knight_of(color)),
+            bishops: count(Piece::get_bishop_of(color)),
+            rooks: count(Piece::get_rook_of(color)),
+            queens: count(Piece::get_queen_of(color)),
+        }
+    }
+
+    // How far the game is from the end game, based on the non-pawn material left:
+    // 1.0 is the middle game (all pieces on the board), 0.0 is a pure pawn end game.
+    // <https://www.chessprogramming.org/Game_Phase>
+    #[allow(clippy::cast_precision_loss)]
+    pub fn game_phase(&self) -> f32 {
+        let material = self.material_count(Color::White) + self.material_count(Color::Black);
+        let phase = material.knights * KNIGHT_PHASE
+            + material.bishops * BISHOP_PHASE
+            + material.rooks * ROOK_PHASE
+            + material.queens * QUEEN_PHASE;
+        // With promotions, there can be more material than in the initial position.
+        phase.min(MAX_PHASE) as f32 / MAX_PHASE as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_material_count_initial_position() {
+        let board = Board::initial_board();
+        let expected = MaterialCount {
+            pawns: 8,
+            knights: 2,
+            bishops: 2,
+            rooks: 2,
+            queens: 1,
+        };
+        assert_eq!(board.material_count(Color::White), expected);
+        assert_eq!(board.material_count(Color::Black), expected);
+        assert_eq!(expected.minor_pieces(), 4);
+        assert_eq!(expected.major_pieces(), 3);
+    }
+
+    #[test]
+    fn test_material_count() {
+        let board: Board = "4k3/pp6/2n5/8/8/8/P7/4K2R w - - 0 1".into();
+        let white = board.material_count(Color::White);
+        let black = board.material_count(Color::Black);
+        assert_eq!(
+            white,
+            MaterialCount {
+                pawns: 1,
+                rooks: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            black,
+            MaterialCount {
+                pawns: 2,
+                knights: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            white + black,
+            MaterialCount {
+                pawns: 3,
+                knights: 1,
+                rooks: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_game_phase() {
+        assert_eq!(Board::initial_board().game_phase(), 1.0);
+
+        let board: Board = "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1".into();
+        assert!(board.game_phase() < 0.01);
+
+        // Queens traded.
+        let board: Board = "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1".into();
+        assert!((board.game_phase() - 0.7).abs() < 0.01);
+
+        // Promotions don't go over the middle game.
+        let board: Board = "QQQQkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQk - 0 1".into();
+        assert_eq!(board.game_phase(), 1.0);
+    }
+}
@@ -3,11 +3,20 @@
 use super::Board;
 
 use crate::{
-    bitboard::{self, movements},
-    common::{Piece, Square},
+    bitboard::{self, movements, BitBoard},
+    common::Piece,
     moves::Move,
 };
 
+// Which kind of moves to generate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MoveKind {
+    All,
+    Captures,
+    // Captures and promotions are the "noisy" moves, that change the material balance.
+    CapturesAndPromotions,
+}
+
 impl Board {
     fn can_castle_king_side(&self) -> bool {
         let side_to_move = self.get_side_to_move();
@@ -21,22 +30,86 @@ impl Board {
             && movements::can_castle_queen_side(self.occupied, side_to_move)
     }
 
+    fn generate_castling(&self, moves_list: &mut Vec<Move>) {
+        if self.chess960 {
+            moves_list.extend(self.generate_chess960_castling());
+        } else {
+            if self.can_castle_king_side() {
+                moves_list.push(Move::KING_TO_KING_SIDE_CASTLING[self.get_side_to_move() as usize]);
+            }
+            if self.can_castle_queen_side() {
+                moves_list
+                    .push(Move::KING_TO_QUEEN_SIDE_CASTLING[self.get_side_to_move() as usize]);
+            }
+        }
+    }
+
+    // In Chess960, the king and the rook end up on the same squares as in standard chess.
+    // All squares they go through must be empty, except for the king and rook themselves.
+    fn generate_chess960_castling(&self) -> impl Iterator<Item = Move> + '_ {
+        let color = self.get_side_to_move();
+        let king = Piece::get_king_of(color);
+        let king_bb = self.pieces_of(king);
+        [true, false].into_iter().filter_map(move |king_side| {
+            let allowed = if king_side {
+                self.castling_ability.can_castle_king_side(color)
+            } else {
+                self.castling_ability.can_castle_queen_side(color)
+            };
+            if !allowed || bitboard::is_empty(king_bb) {
+                return None;
+            }
+            let mv = Move::castling_chess960(
+                bitboard::get_square(king_bb),
+                self.get_castling_rook(color, king_side),
+                king,
+            );
+            let king_mv = mv.get_piece_move();
+            let rook_mv = mv.get_castling().unwrap();
+            let path = bitboard::ray_between(king_mv.get_from(), king_mv.get_to())
+                | king_mv.get_to()
+                | bitboard::ray_between(rook_mv.get_from(), rook_mv.get_to())
+                | rook_mv.get_to();
+            let blockers = self.occupied
+                & !bitboard::from_square(king_mv.get_from())
+                & !bitboard::from_square(rook_mv.get_from());
+            (path & blockers == 0).then_some(mv)
+        })
+    }
+
     // Generate all possible moves from this board.
     pub fn generate_moves_for(&self, pieces: &[Piece]) -> Vec<Move> {
-        // Pseudo-legal or legal ones?
+        self.generate_pseudo_legal_moves(pieces, MoveKind::All)
+    }
 
+    // Pseudo-legal moves of that kind, they may leave the king in check.
+    fn generate_pseudo_legal_moves(&self, pieces: &[Piece], kind: MoveKind) -> Vec<Move> {
         let mut moves_list = Vec::new();
 
+        let own_bb = self.all_pieces_of_color(self.get_side_to_move());
+        let opposite_bb = self.all_pieces_of_color(self.opposite_side());
+        let promotion_rank_bb = if self.get_side_to_move().is_white() {
+            bitboard::RANK_8
+        } else {
+            bitboard::RANK_1
+        };
+
         for &piece in pieces
             .iter()
             .filter(|p| self.get_side_to_move() == p.get_color())
         {
-            let own_bb = self.all[self.get_side_to_move() as usize];
-            let opposite_bb = self.all[self.opposite_side() as usize];
+            // Squares the piece may move to.
+            let allowed_bb: BitBoard = match kind {
+                MoveKind::All => bitboard::UNIVERSAL,
+                MoveKind::CapturesAndPromotions if piece.is_pawn() => {
+                    opposite_bb | promotion_rank_bb
+                }
+                MoveKind::Captures | MoveKind::CapturesAndPromotions => opposite_bb,
+            };
 
-            let pieces_bb = self.pieces[piece as usize];
-            for from_bb in bitboard::into_iter(pieces_bb) {
-                let from_square = bitboard::get_index(from_bb).into();
+            let pieces_bb = self.pieces_of(piece);
+            for from_square in bitboard::iter_squares(pieces_bb) {
+                let from_bb = bitboard::from_square(from_square);
 
                 let moves_bb = match piece {
                     Piece::WhiteKing | Piece::BlackKing => {
@@ -60,30 +133,26 @@ impl Board {
                     Piece::WhiteQueen | Piece::BlackQueen => {
                         movements::get_queen_moves(from_bb, self.occupied, own_bb)
                     }
-                };
+                } & allowed_bb;
 
                 // Generate moves.
-                for to_bb in bitboard::into_iter(moves_bb) {
-                    let to_square: Square = bitboard::get_index(to_bb).into();
-                    let is_capture = opposite_bb & to_bb != 0;
+                for to_square in bitboard::iter_squares(moves_bb) {
+                    let captured = if bitboard::contains_square(opposite_bb, to_square) {
+                        self.get_piece_at(to_square)
+                    } else {
+                        None
+                    };
 
                     // Promotions
                     if piece.is_pawn() && to_square.is_promotion_rank_for(self.get_side_to_move()) {
-                        moves_list.extend(
-                            Piece::PROMOTION_PIECES[self.get_side_to_move() as usize]
-                                .iter()
-                                .map(|&promotion_piece| {
-                                    Move::new(
-                                        from_square,
-                                        to_square,
-                                        Some(promotion_piece),
-                                        piece,
-                                        is_capture,
-                                    )
-                                }),
-                        );
+                        moves_list.extend(Move::promotions_from(
+                            from_square,
+                            to_square,
+                            piece,
+                            captured,
+                        ));
                     } else {
-                        moves_list.push(Move::new(from_square, to_square, None, piece, is_capture));
+                        moves_list.push(Move::new(from_square, to_square, None, piece, captured));
                     }
                 }
 
@@ -100,34 +169,209 @@ impl Board {
                         _ => 0,
                     };
 
-                    moves_list.extend(bitboard::into_iter(ep_attacks_bb).map(|to_bb| {
-                        Move::capture(from_square, bitboard::get_index(to_bb).into(), piece)
-                    }));
+                    moves_list.extend(
+                        bitboard::iter_squares(ep_attacks_bb)
+                            .map(|to_square| Move::en_passant(from_square, to_square, piece)),
+                    );
                 }
             }
         }
 
-        // Castling
-        if self.can_castle_king_side() {
-            moves_list.push(Move::KING_TO_KING_SIDE_CASTLING[self.get_side_to_move() as usize]);
-        }
-        if self.can_castle_queen_side() {
-            moves_list.push(Move::KING_TO_QUEEN_SIDE_CASTLING[self.get_side_to_move() as usize]);
+        if kind != MoveKind::All {
+            return moves_list;
         }
 
+        self.generate_castling(&mut moves_list);
+
         moves_list
     }
 
     pub fn generate_moves(&self) -> Vec<Move> {
         self.generate_moves_for(&Piece::ALL_PIECES)
     }
+
+    // Generate only the moves that don't leave the king in check.
+    pub fn generate_legal_moves(&self) -> Vec<Move> {
+        self.generate_evasion_candidates()
+            .into_iter()
+            .filter(|&mv| self.copy_with_move(mv).is_some())
+            .collect()
+    }
+
+    // Number of legal moves, for when the moves themselves aren't needed, like at the leaves of perft.
+    pub fn count_legal_moves(&self) -> usize {
+        self.generate_legal_moves().len()
+    }
+
+    // When in check, only some moves can be legal:
+    // With one checker, king moves, capturing the checker or blocking it.
+    // With two checkers, only king moves.
+    fn generate_evasion_candidates(&self) -> Vec<Move> {
+        let checkers = self.checkers();
+        match bitboard::count_ones(checkers) {
+            0 => self.generate_moves(),
+            1 => {
+                let king_bb = self.pieces_of(Piece::get_king_of(self.side_to_move));
+                let checker = bitboard::get_square(checkers);
+                let evasions_bb =
+                    checkers | bitboard::ray_between(bitboard::get_square(king_bb), checker);
+                self.generate_moves()
+                    .into_iter()
+                    .filter(|&mv| {
+                        mv.get_piece().is_king()
+                            || bitboard::contains_square(evasions_bb, mv.get_to())
+                            // The checker may be a pawn captured en passant.
+                            || self.is_en_passant_capture(mv)
+                    })
+                    .collect()
+            }
+            _ => self.generate_moves_for(&[Piece::get_king_of(self.side_to_move)]),
+        }
+    }
+
+    // Stops at the first legal move found, cheaper than generating all legal moves.
+    pub fn has_legal_moves(&self) -> bool {
+        self.generate_moves()
+            .into_iter()
+            .any(|mv| self.copy_with_move(mv).is_some())
+    }
+
+    // Generate only the legal captures, including en passant.
+    pub fn generate_captures(&self) -> Vec<Move> {
+        self.generate_pseudo_legal_moves(&Piece::ALL_PIECES, MoveKind::Captures)
+            .into_iter()
+            .filter(|&mv| self.copy_with_move(mv).is_some())
+            .collect()
+    }
+
+    // Generate the legal captures and promotions.
+    pub fn generate_captures_and_promotions(&self) -> Vec<Move> {
+        self.generate_pseudo_legal_moves(&Piece::ALL_PIECES, MoveKind::CapturesAndPromotions)
+            .into_iter()
+            .filter(|&mv| self.copy_with_move(mv).is_some())
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{common::Piece::*, common::Square::*};
+    use crate::{common::Color, common::Piece::*, common::Square::*, fen};
 
     use super::*;
+    #[test]
+    fn test_count_legal_moves() {
+        for fen in [
+            fen::START_POSITION,
+            fen::KIWIPETE,
+            fen::POSITION_3,
+            fen::POSITION_4,
+            // In check, and checkmate.
+            "4k3/8/8/8/8/8/4r3/4K3 w - - 0 1",
+            "k6R/8/1K6/8/8/8/8/8 b - - 0 1",
+        ] {
+            let board: Board = fen.into();
+            assert_eq!(
+                board.count_legal_moves(),
+                board.generate_legal_moves().len(),
+                "{fen}"
+            );
+        }
+        assert_eq!(Board::initial_board().count_legal_moves(), 20);
+        assert_eq!(Board::from_fen(fen::KIWIPETE).count_legal_moves(), 48);
+    }
+
+    #[test]
+    fn test_generate_moves_only_side_to_move() {
+        let board: Board = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".into();
+        assert_eq!(board.get_side_to_move(), Color::Black);
+        let moves = board.generate_moves();
+        assert_eq!(moves.len(), 20);
+        assert!(moves
+            .iter()
+            .all(|mv| mv.get_piece().get_color() == Color::Black));
+
+        let mut board = board;
+        board.make_move(board.new_move_from_pure("e7e5"));
+        assert_eq!(board.get_side_to_move(), Color::White);
+        assert!(board
+            .generate_moves()
+            .iter()
+            .all(|mv| mv.get_piece().get_color() == Color::White));
+    }
+
+    #[test]
+    fn test_generate_captures() {
+        let board: Board = "4k3/8/8/3r4/2P5/8/8/3QK3 w - - 0 1".into();
+        assert_eq!(
+            board.generate_captures(),
+            &[
+                Move::capture(C4, D5, WhitePawn, BlackRook),
+                Move::capture(D1, D5, WhiteQueen, BlackRook),
+            ]
+        );
+
+        // En passant is included, the pinned knight capture isn't.
+        let board: Board = "4k3/8/8/b1PpP3/8/5p2/3N4/4K3 w - d6 0 1".into();
+        assert_eq!(
+            board.generate_captures(),
+            &[
+                Move::en_passant(C5, D6, WhitePawn),
+                Move::en_passant(E5, D6, WhitePawn),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_captures_same_as_filtering() {
+        for fen in [
+            crate::fen::KIWIPETE,
+            "4k3/8/8/b1PpP3/8/5p2/3N4/4K3 w - d6 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+        ] {
+            let board: Board = fen.into();
+            let captures: Vec<Move> = board
+                .generate_legal_moves()
+                .into_iter()
+                .filter(|mv| mv.is_capture())
+                .collect();
+            assert_eq!(board.generate_captures(), captures, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_captures_have_captured_piece() {
+        for fen in [
+            crate::fen::KIWIPETE,
+            "4k3/8/8/b1PpP3/8/5p2/3N4/4K3 w - d6 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+        ] {
+            let board: Board = fen.into();
+            for mv in board.generate_captures() {
+                let captured = mv.get_captured_piece();
+                assert!(captured.is_some(), "{fen} {mv}");
+                if !board.is_en_passant_capture(mv) {
+                    assert_eq!(captured, board.get_piece_at(mv.get_to()), "{fen} {mv}");
+                }
+            }
+            assert!(board
+                .generate_legal_moves()
+                .iter()
+                .filter(|mv| !mv.is_capture())
+                .all(|mv| mv.get_captured_piece().is_none()));
+        }
+    }
+
+    #[test]
+    fn test_generate_captures_and_promotions() {
+        let board: Board = "1n2k3/P7/8/8/8/8/8/4K2R w K - 0 1".into();
+        assert!(board.generate_captures().iter().all(|mv| mv.is_capture()));
+        assert_eq!(board.generate_captures().len(), 4);
+        let moves = board.generate_captures_and_promotions();
+        // 4 quiet promotions and 4 capture promotions.
+        assert_eq!(moves.len(), 8);
+        assert!(moves.iter().all(|mv| mv.get_promotion().is_some()));
+    }
+
     #[test]
     fn test_generate_moves_white_king() {
         let board: Board = "2k5/8/8/8/8/8/2Pp4/2K5 w - - 0 1".into();
@@ -138,7 +382,7 @@ mod tests {
                 Move::quiet(C1, B1, WhiteKing),
                 Move::quiet(C1, D1, WhiteKing),
                 Move::quiet(C1, B2, WhiteKing),
-                Move::capture(C1, D2, WhiteKing),
+                Move::capture(C1, D2, WhiteKing, BlackPawn),
             ]
         );
     }
@@ -151,7 +395,7 @@ mod tests {
             moves,
             &[
                 Move::quiet(C8, B7, BlackKing),
-                Move::capture(C8, C7, BlackKing),
+                Move::capture(C8, C7, BlackKing, WhitePawn),
                 Move::quiet(C8, B8, BlackKing),
                 Move::quiet(C8, D8, BlackKing),
             ]
@@ -190,11 +434,11 @@ mod tests {
         assert_eq!(
             moves,
             &[
-                Move::capture(B2, A3, WhitePawn),
+                Move::capture(B2, A3, WhitePawn, BlackKnight),
                 Move::quiet(B2, B3, WhitePawn),
-                Move::capture(B2, C3, WhitePawn),
+                Move::capture(B2, C3, WhitePawn, BlackPawn),
                 Move::quiet(B2, B4, WhitePawn),
-                Move::capture(D2, C3, WhitePawn),
+                Move::capture(D2, C3, WhitePawn, BlackPawn),
                 Move::quiet(E2, E3, WhitePawn),
                 Move::quiet(F2, F3, WhitePawn),
                 Move::quiet(F2, F4, WhitePawn),
@@ -215,19 +459,69 @@ mod tests {
                 Move::quiet(C6, C5, BlackPawn),
                 Move::quiet(B7, B5, BlackPawn),
                 Move::quiet(B7, B6, BlackPawn),
-                Move::capture(C7, D6, BlackPawn),
-                Move::capture(E7, D6, BlackPawn),
+                Move::capture(C7, D6, BlackPawn, WhiteBishop),
+                Move::capture(E7, D6, BlackPawn, WhiteBishop),
                 Move::quiet(E7, E6, BlackPawn),
                 Move::quiet(F7, F5, BlackPawn),
                 Move::quiet(F7, F6, BlackPawn),
-                Move::capture(F7, G6, BlackPawn),
+                Move::capture(F7, G6, BlackPawn, WhitePawn),
                 Move::quiet(H7, H5, BlackPawn),
-                Move::capture(H7, G6, BlackPawn),
+                Move::capture(H7, G6, BlackPawn, WhitePawn),
                 Move::quiet(H7, H6, BlackPawn),
             ]
         );
     }
 
+    #[test]
+    fn test_generate_promotions() {
+        let board: Board = "2n5/1P6/8/8/8/8/8/k1K5 w - - 0 1".into();
+        let moves = board.generate_moves_for(&[WhitePawn]);
+        assert_eq!(
+            moves,
+            &[
+                Move::promotion(B7, B8, WhitePawn, WhiteQueen),
+                Move::promotion(B7, B8, WhitePawn, WhiteKnight),
+                Move::promotion(B7, B8, WhitePawn, WhiteRook),
+                Move::promotion(B7, B8, WhitePawn, WhiteBishop),
+                Move::new(B7, C8, Some(WhiteQueen), WhitePawn, Some(BlackKnight)),
+                Move::new(B7, C8, Some(WhiteKnight), WhitePawn, Some(BlackKnight)),
+                Move::new(B7, C8, Some(WhiteRook), WhitePawn, Some(BlackKnight)),
+                Move::new(B7, C8, Some(WhiteBishop), WhitePawn, Some(BlackKnight)),
+            ]
+        );
+
+        let board: Board = "K1k5/8/8/8/8/8/6p1/8 b - - 0 1".into();
+        let moves = board.generate_moves_for(&[BlackPawn]);
+        assert_eq!(
+            moves,
+            &[
+                Move::promotion(G2, G1, BlackPawn, BlackQueen),
+                Move::promotion(G2, G1, BlackPawn, BlackKnight),
+                Move::promotion(G2, G1, BlackPawn, BlackRook),
+                Move::promotion(G2, G1, BlackPawn, BlackBishop),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_under_promotion_avoids_stalemate() {
+        let board: Board = "8/1P6/k7/8/1K6/8/8/8 w - - 0 1".into();
+        let moves = board.generate_moves_for(&[WhitePawn]);
+
+        // Promoting to a queen is stalemate.
+        let queen_promotion = Move::promotion(B7, B8, WhitePawn, WhiteQueen);
+        assert!(moves.contains(&queen_promotion));
+        let after_queen = board.copy_with_move(queen_promotion).unwrap();
+        assert_eq!(after_queen.attacks_king(Color::Black), 0);
+        assert_eq!(after_queen.perft(1), 0);
+
+        // Promoting to a rook leaves the black king a way out.
+        let rook_promotion = Move::promotion(B7, B8, WhitePawn, WhiteRook);
+        assert!(moves.contains(&rook_promotion));
+        let after_rook = board.copy_with_move(rook_promotion).unwrap();
+        assert!(after_rook.perft(1) > 0);
+    }
+
     #[test]
     fn test_en_passant_attacks_1() {
         // Two black pawns can take the same en passant white pawn.
@@ -237,8 +531,8 @@ mod tests {
         assert_eq!(
             moves,
             &[
-                Move::capture(A4, B3, BlackPawn),
-                Move::capture(C4, B3, BlackPawn),
+                Move::en_passant(A4, B3, BlackPawn),
+                Move::en_passant(C4, B3, BlackPawn),
                 Move::quiet(F7, F5, BlackPawn),
                 Move::quiet(F7, F6, BlackPawn),
                 Move::quiet(G7, G5, BlackPawn),
@@ -256,13 +550,50 @@ mod tests {
         assert_eq!(
             moves,
             &[
-                Move::capture(C4, B3, BlackPawn),
+                Move::capture(C4, B3, BlackPawn, WhiteBishop),
                 Move::quiet(C4, C3, BlackPawn), // Push, leaves the king in check.
-                Move::capture(C4, D3, BlackPawn), // En passant, leaves the king in check.
+                Move::en_passant(C4, D3, BlackPawn), // En passant, leaves the king in check.
             ]
         );
     }
 
+    #[test]
+    fn test_en_passant_attacks_3() {
+        let board: Board = "rnbqkbnr/pppp1ppp/8/4pP2/8/8/PPPPP1PP/RNBQKBNR w KQkq e6 0 2".into();
+        let moves = board.generate_moves_for(&[WhitePawn]);
+        assert!(moves.contains(&Move::en_passant(F5, E6, WhitePawn)));
+
+        // No en passant without the target square.
+        let board: Board = "rnbqkbnr/pppp1ppp/8/4pP2/8/8/PPPPP1PP/RNBQKBNR w KQkq - 0 2".into();
+        let moves = board.generate_moves_for(&[WhitePawn]);
+        assert!(!moves.contains(&Move::en_passant(F5, E6, WhitePawn)));
+    }
+
+    #[test]
+    fn test_generate_legal_moves() {
+        let board = Board::initial_board();
+        assert_eq!(board.generate_legal_moves().len(), 20);
+
+        // Black king in check by the rook, can only escape sideways.
+        let board: Board = "4k3/8/8/8/8/8/8/4RK2 b - - 0 1".into();
+        assert_eq!(
+            board.generate_legal_moves(),
+            &[
+                Move::quiet(E8, D7, BlackKing),
+                Move::quiet(E8, F7, BlackKing),
+                Move::quiet(E8, D8, BlackKing),
+                Move::quiet(E8, F8, BlackKing),
+            ]
+        );
+
+        // Pinned knight cannot move.
+        let board: Board = "4k3/4n3/8/8/8/8/8/4RK2 b - - 0 1".into();
+        assert!(board
+            .generate_legal_moves()
+            .iter()
+            .all(|mv| mv.get_piece() == BlackKing));
+    }
+
     #[test]
     fn test_generate_castling() {
         let board: Board = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8".into();
@@ -272,9 +603,90 @@ mod tests {
             &[
                 Move::quiet(E1, F1, WhiteKing),
                 Move::quiet(E1, D2, WhiteKing),
-                Move::capture(E1, F2, WhiteKing),
-                Move::quiet(E1, G1, WhiteKing),
+                Move::capture(E1, F2, WhiteKing, BlackKnight),
+                Move::castling(E1, G1, WhiteKing),
             ]
         );
     }
+
+    #[test]
+    fn test_generate_castling_both_sides() {
+        let board: Board = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".into();
+        let moves = board.generate_moves_for(&[WhiteKing]);
+        assert!(moves.contains(&Move::castling(E1, G1, WhiteKing)));
+        assert!(moves.contains(&Move::castling(E1, C1, WhiteKing)));
+
+        let board: Board = "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1".into();
+        let moves = board.generate_moves_for(&[BlackKing]);
+        assert!(moves.contains(&Move::castling(E8, G8, BlackKing)));
+        assert!(moves.contains(&Move::castling(E8, C8, BlackKing)));
+
+        // Castling ability lost, or pieces in between.
+        let board: Board = "r3k2r/8/8/8/8/8/8/R3K2R w kq - 0 1".into();
+        let moves = board.generate_moves_for(&[WhiteKing]);
+        assert_eq!(moves.iter().filter(|m| m.is_castling()).count(), 0);
+        let board: Board = "r3k2r/8/8/8/8/8/8/RN2K1NR w KQkq - 0 1".into();
+        let moves = board.generate_moves_for(&[WhiteKing]);
+        assert_eq!(moves.iter().filter(|m| m.is_castling()).count(), 0);
+    }
+
+    #[test]
+    fn test_generate_chess960_castling() {
+        // King and rook swap places.
+        let board: Board = "4k3/8/8/8/8/8/8/5KR1 w G - 0 1".into();
+        assert!(board.is_chess960());
+        let castling = Move::castling_chess960(F1, G1, WhiteKing);
+        assert!(board.generate_legal_moves().contains(&castling));
+        let mut board = board;
+        board.make_move(castling);
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/5RK1 b - - 1 1");
+
+        // The king doesn't move, only the rook does.
+        let board: Board = "1r4k1/8/8/8/8/8/8/6K1 b b - 0 1".into();
+        let castling = Move::castling_chess960(G8, B8, BlackKing);
+        assert!(board.generate_legal_moves().contains(&castling));
+
+        // A piece on the rook destination, or the king passing over an attacked square.
+        let board: Board = "4k3/8/8/8/8/8/8/R1N1K3 w A - 0 1".into();
+        assert!(!board.generate_moves().iter().any(|mv| mv.is_castling()));
+        let board: Board = "4k3/8/8/8/8/8/8/1K3R2 w F - 0 1".into();
+        assert!(board
+            .generate_legal_moves()
+            .iter()
+            .any(|mv| mv.is_castling()));
+        let board: Board = "3rk3/8/8/8/8/8/8/1K3R2 w F - 0 1".into();
+        assert!(!board
+            .generate_legal_moves()
+            .iter()
+            .any(|mv| mv.is_castling()));
+    }
+
+    #[test]
+    fn test_generate_legal_moves_in_check() {
+        for fen in [
+            // Single check by a rook, can block, capture or move the king.
+            "4k3/8/8/8/8/8/1B6/r3K3 w - - 0 1",
+            // Check by a pawn that can be captured en passant.
+            "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+            // Double check.
+            "4k3/8/8/8/1b6/3n4/8/2Q1K3 w - - 0 1",
+            // Check by a knight.
+            "4k3/8/8/8/8/3n4/8/4K2R w K - 0 1",
+        ] {
+            let board: Board = fen.into();
+            let all_legal: Vec<Move> = board
+                .generate_moves()
+                .into_iter()
+                .filter(|&mv| board.copy_with_move(mv).is_some())
+                .collect();
+            assert_eq!(board.generate_legal_moves(), all_legal, "{fen}");
+        }
+
+        // Only king moves on double check.
+        let board: Board = "4k3/8/8/8/1b6/3n4/8/2Q1K3 w - - 0 1".into();
+        assert!(board
+            .generate_legal_moves()
+            .iter()
+            .all(|mv| mv.get_piece() == WhiteKing));
+    }
 }
@@ -0,0 +1,75 @@
+//! The moves played to reach a position, with the state needed to take them back.
+
+use crate::moves::Move;
+
+use super::{Board, BoardState};
+
+// Kept outside of Board, as boards are copied for every move during the search.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveHistory(Vec<(Move, BoardState)>);
+
+impl MoveHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Plays the move on the board and records it.
+    pub fn make_move(&mut self, board: &mut Board, mv: Move) {
+        let state = board.make_move(mv);
+        self.0.push((mv, state));
+    }
+
+    // Takes back the last move played and returns it, None if there are no moves.
+    pub fn unmake_move(&mut self, board: &mut Board) -> Option<Move> {
+        let (mv, state) = self.0.pop()?;
+        board.unmake_move(mv, state);
+        Some(mv)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    // All the moves played, in order.
+    pub fn to_pgn_moves(&self) -> Vec<Move> {
+        self.0.iter().map(|(mv, _)| *mv).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fen;
+
+    use super::*;
+
+    #[test]
+    fn test_make_unmake() {
+        let original: Board = fen::KIWIPETE.into();
+        let mut board = original;
+        let mut history = MoveHistory::new();
+        let mut moves = Vec::new();
+        for mv in ["e1g1", "e8c8", "e5f7", "d8f8"] {
+            let mv = board.new_move_from_pure(mv);
+            history.make_move(&mut board, mv);
+            moves.push(mv);
+        }
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.to_pgn_moves(), moves);
+
+        let last = history.unmake_move(&mut board);
+        assert_eq!(last, moves.pop());
+        assert_eq!(history.to_pgn_moves(), moves);
+
+        while history.unmake_move(&mut board).is_some() {}
+        assert!(history.is_empty());
+        assert_eq!(board, original);
+    }
+}
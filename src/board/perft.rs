@@ -1,21 +1,74 @@
 //! Perft <https://www.chessprogramming.org/Perft>
 
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use itertools::Itertools;
+
 use crate::{board::Board, moves::Move};
 
+// Runs perft and measures how long it takes, as a benchmark of the move generation.
+// The bottleneck is the legality check: Making each pseudo-legal move with copy_with_move to see
+// if it leaves the king in check takes several times longer than generating the moves.
+// Reproduce with `cargo bench --bench perft`, comparing the generate_moves and copy_with_move cases.
+pub fn perft_bench(board: &Board, depth: u8) -> (u64, Duration) {
+    let start = Instant::now();
+    let nodes = board.perft(depth.into()) as u64;
+    (nodes, start.elapsed())
+}
+
+// Nodes per second for a perft or search run.
+#[allow(clippy::cast_possible_truncation)]
+pub fn nodes_per_second(nodes: u64, elapsed: Duration) -> u64 {
+    let micros = elapsed.as_micros().max(1);
+    (u128::from(nodes) * 1_000_000 / micros) as u64
+}
+
+// Checks the perft_split result against the expected counts, for example from Stockfish "go perft".
+// Panics with the list of the moves that differ.
+pub fn perft_compare(board: &Board, depth: u8, expected: &[(&str, u64)]) {
+    let actual = board.perft_split(depth);
+    let expected: HashMap<String, u64> = expected
+        .iter()
+        .map(|&(mv, count)| (mv.to_string(), count))
+        .collect();
+    let count_str = |count: Option<&u64>| count.map_or("missing".to_string(), u64::to_string);
+    let differences = actual
+        .keys()
+        .chain(expected.keys())
+        .unique()
+        .sorted()
+        .filter(|mv| actual.get(*mv) != expected.get(*mv))
+        .map(|mv| {
+            format!(
+                "{mv}: {} instead of {}",
+                count_str(actual.get(mv)),
+                count_str(expected.get(mv))
+            )
+        })
+        .join("\n");
+    assert!(
+        differences.is_empty(),
+        "Perft {depth} differs for:\n{differences}"
+    );
+}
+
 impl Board {
     pub fn perft(&self, depth: usize) -> usize {
         if depth == 0 {
             return 1;
         }
 
+        // Bulk counting: No need to make the moves of the last ply.
+        if depth == 1 {
+            return self.count_legal_moves();
+        }
+
         let mut nodes = 0;
         let move_list = self.generate_moves();
 
-        // If we had a legal move generator, we could do following optimization:
-        // if depth == 1 {
-        //     return move_list.len();
-        // }
-
         for mv in move_list {
             if let Some(board_copy) = self.copy_with_move(mv) {
                 nodes += board_copy.perft(depth - 1);
@@ -36,10 +89,20 @@ impl Board {
         }
         nodes
     }
+
+    // Same as divide, but by move in the same notation as Stockfish, so that the outputs can be compared.
+    pub fn perft_split(&self, depth: u8) -> HashMap<String, u64> {
+        self.divide(depth.into())
+            .into_iter()
+            .map(|(mv, count)| (mv.pure().to_string(), count as u64))
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::fen;
+
     use super::*;
 
     #[test]
@@ -61,6 +124,143 @@ mod tests {
         assert_eq!(board.perft(1), 20);
         assert_eq!(board.perft(2), 400);
         assert_eq!(board.perft(3), 8902);
+        assert_eq!(board.perft(4), 197_281);
+    }
+
+    #[test]
+    fn test_perft_positions() {
+        // Values from <https://www.chessprogramming.org/Perft_Results>
+        let b: Board = fen::KIWIPETE.into();
+        assert_eq!(b.perft(1), 48);
+        assert_eq!(b.perft(2), 2039);
+        assert_eq!(b.perft(3), 97_862);
+
+        let b: Board = fen::POSITION_3.into();
+        assert_eq!(b.perft(1), 14);
+        assert_eq!(b.perft(2), 191);
+        assert_eq!(b.perft(3), 2812);
+        assert_eq!(b.perft(4), 43_238);
+
+        let b: Board = fen::POSITION_4.into();
+        assert_eq!(b.perft(1), 6);
+        assert_eq!(b.perft(2), 264);
+        assert_eq!(b.perft(3), 9467);
+
+        let b: Board = fen::POSITION_5.into();
+        assert_eq!(b.perft(1), 44);
+        assert_eq!(b.perft(2), 1486);
+
+        let b: Board = fen::POSITION_6.into();
+        assert_eq!(b.perft(1), 46);
+        assert_eq!(b.perft(2), 2079);
+    }
+
+    #[test]
+    fn test_perft_chess960() {
+        // Values from <https://www.chessprogramming.org/Chess960_Perft_Results>
+        let b: Board = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9".into();
+        assert_eq!(b.perft(1), 21);
+        assert_eq!(b.perft(2), 528);
+        assert_eq!(b.perft(3), 12189);
+        assert_eq!(b.perft(4), 326_672);
+
+        let b: Board = "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9".into();
+        assert_eq!(b.perft(1), 21);
+        assert_eq!(b.perft(2), 807);
+        assert_eq!(b.perft(3), 18002);
+
+        let b: Board = "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9".into();
+        assert_eq!(b.perft(1), 20);
+        assert_eq!(b.perft(2), 479);
+        assert_eq!(b.perft(3), 10471);
+
+        let b: Board = "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9".into();
+        assert_eq!(b.perft(1), 22);
+        assert_eq!(b.perft(2), 593);
+        assert_eq!(b.perft(3), 13440);
+
+        // Standard positions give the same results when encoding castling the Chess960 way.
+        let mut b: Board = fen::KIWIPETE.into();
+        b.set_chess960(true);
+        assert_eq!(b.perft(3), 97_862);
+    }
+
+    #[test]
+    fn test_perft_bench() {
+        let (nodes, _) = perft_bench(&Board::initial_board(), 3);
+        assert_eq!(nodes, 8902);
+    }
+
+    #[test]
+    fn test_nodes_per_second() {
+        assert_eq!(nodes_per_second(1000, Duration::from_millis(10)), 100_000);
+        assert_eq!(
+            nodes_per_second(5_000_000, Duration::from_secs(2)),
+            2_500_000
+        );
+        assert_eq!(nodes_per_second(10, Duration::ZERO), 10_000_000);
+    }
+
+    #[test]
+    fn test_divide_initial() {
+        let board = Board::initial_board();
+        let nodes = board.divide(1);
+        assert_eq!(nodes.len(), 20);
+        assert!(nodes.iter().all(|(_, count)| *count == 1));
+    }
+
+    #[test]
+    fn test_perft_split() {
+        let board: Board = "8/P7/8/8/8/8/8/k6K w - - 0 1".into();
+        let split = board.perft_split(1);
+        assert_eq!(split.len(), 7);
+        assert_eq!(split.get("a7a8q"), Some(&1));
+        assert_eq!(split.get("a7a8n"), Some(&1));
+        assert_eq!(split.get("h1g2"), Some(&1));
+
+        // Output of Stockfish "go perft 3".
+        perft_compare(
+            &Board::initial_board(),
+            3,
+            &[
+                ("a2a3", 380),
+                ("b2b3", 420),
+                ("c2c3", 420),
+                ("d2d3", 539),
+                ("e2e3", 599),
+                ("f2f3", 380),
+                ("g2g3", 420),
+                ("h2h3", 380),
+                ("a2a4", 420),
+                ("b2b4", 421),
+                ("c2c4", 441),
+                ("d2d4", 560),
+                ("e2e4", 600),
+                ("f2f4", 401),
+                ("g2g4", 421),
+                ("h2h4", 420),
+                ("b1a3", 400),
+                ("b1c3", 440),
+                ("g1f3", 440),
+                ("g1h3", 400),
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Perft 1 differs for:\na2a3: 1 instead of 2\na7a8: missing instead of 1"
+    )]
+    fn test_perft_compare_fails() {
+        let moves: Vec<String> = Board::initial_board()
+            .generate_legal_moves()
+            .iter()
+            .map(|mv| mv.pure().to_string())
+            .collect();
+        let mut expected: Vec<(&str, u64)> = moves.iter().map(|mv| (mv.as_str(), 1)).collect();
+        expected[0].1 = 2;
+        expected.push(("a7a8", 1));
+        perft_compare(&Board::initial_board(), 1, &expected);
     }
 
     #[test]
@@ -91,57 +291,57 @@ mod tests {
     }
 
     #[test]
-    #[cfg_attr(not(feature = "perft"), ignore)]
+    #[cfg_attr(not(feature = "perft"), ignore = "slow, enable with the perft feature")]
     fn test_peterellisjones_slow() {
         // Slower tests, not enabled by default.
         let b: Board = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8".into();
-        assert_eq!(b.perft(3), 62379);
+        assert_eq!(b.perft(3), 62_379);
 
         let b: Board =
             "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10".into();
-        assert_eq!(b.perft(3), 89890);
+        assert_eq!(b.perft(3), 89_890);
 
         let b: Board = "3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1".into();
-        assert_eq!(b.perft(6), 1134888);
+        assert_eq!(b.perft(6), 1_134_888);
 
         // Push and en-passant leaving king in check.
         let b: Board = "8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1".into();
-        assert_eq!(b.perft(6), 1015133);
+        assert_eq!(b.perft(6), 1_015_133);
 
         let b: Board = "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1".into();
-        assert_eq!(b.perft(6), 1440467);
+        assert_eq!(b.perft(6), 1_440_467);
 
         let b: Board = "5k2/8/8/8/8/8/8/4K2R w K - 0 1".into();
-        assert_eq!(b.perft(6), 661072);
+        assert_eq!(b.perft(6), 661_072);
 
         let b: Board = "3k4/8/8/8/8/8/8/R3K3 w Q - 0 1".into();
-        assert_eq!(b.perft(6), 803711);
+        assert_eq!(b.perft(6), 803_711);
 
         let b: Board = "r3k2r/1b4bq/8/8/8/8/7B/R3K2R w KQkq - 0 1".into();
-        assert_eq!(b.perft(4), 1274206);
+        assert_eq!(b.perft(4), 1_274_206);
 
         let b: Board = "r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1".into();
-        assert_eq!(b.perft(4), 1720476);
+        assert_eq!(b.perft(4), 1_720_476);
 
         let b: Board = "2K2r2/4P3/8/8/8/8/8/3k4 w - - 0 1".into();
-        assert_eq!(b.perft(6), 3821001);
+        assert_eq!(b.perft(6), 3_821_001);
 
         let b: Board = "8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1".into();
-        assert_eq!(b.perft(5), 1004658);
+        assert_eq!(b.perft(5), 1_004_658);
 
         let b: Board = "4k3/1P6/8/8/8/8/K7/8 w - - 0 1".into();
-        assert_eq!(b.perft(6), 217342);
+        assert_eq!(b.perft(6), 217_342);
 
         let b: Board = "8/P1k5/K7/8/8/8/8/8 w - - 0 1".into();
-        assert_eq!(b.perft(6), 92683);
+        assert_eq!(b.perft(6), 92_683);
 
         let b: Board = "K1k5/8/P7/8/8/8/8/8 w - - 0 1".into();
         assert_eq!(b.perft(6), 2217);
 
         let b: Board = "8/k1P5/8/1K6/8/8/8/8 w - - 0 1".into();
-        assert_eq!(b.perft(7), 567584);
+        assert_eq!(b.perft(7), 567_584);
 
         let b: Board = "8/8/2k5/5q2/5n2/8/5K2/8 b - - 0 1".into();
-        assert_eq!(b.perft(4), 23527);
+        assert_eq!(b.perft(4), 23_527);
     }
 }
@@ -2,15 +2,23 @@
 
 use crate::{
     bitboard::{self, BitBoard},
-    common::Color,
-    moves::Move,
+    common::{Color, Square},
+    moves::{Move, MoveError},
 };
 
-use super::Board;
+use super::{zobrist, Board, BoardState};
 
 impl Board {
     fn toggle_side(&mut self) {
         self.side_to_move = self.side_to_move.opposite();
+        self.hash ^= zobrist::side_to_move_key();
+    }
+
+    // A move is an en passant capture if a pawn moves to the en passant target square.
+    // Such moves are generated as regular captures, the captured pawn being behind the target square.
+    pub fn is_en_passant_capture(&self, mv: Move) -> bool {
+        mv.get_piece().is_pawn()
+            && matches!(self.en_passant_target_square, Some(sq) if sq == mv.get_to())
     }
 
     // Updates the bitboards and castling rights only.
@@ -24,53 +32,63 @@ impl Board {
         self.pieces[mv.get_piece() as usize] ^= from_to_bb;
         self.all[color as usize] ^= from_to_bb;
         self.occupied ^= from_to_bb;
+        self.hash ^= zobrist::piece_key(mv.get_piece(), mv.get_from())
+            ^ zobrist::piece_key(mv.get_piece(), mv.get_to());
 
-        if mv.is_capture() {
+        if let Some(captured) = mv.get_captured_piece() {
             // If we are trying to move into the en-passant square, we need to correct the square we will clear.
-            let to_bb_capture = if mv.get_piece().is_pawn()
-                && matches!(self.en_passant_target_square, Some(sq) if sq == mv.get_to())
-            {
-                if color == Color::White {
-                    to_bb >> 8
+            let to_bb_capture = if self.is_en_passant_capture(mv) {
+                if color.is_white() {
+                    bitboard::shift_south(to_bb)
                 } else {
-                    to_bb << 8
+                    bitboard::shift_north(to_bb)
                 }
             } else {
                 to_bb
             };
 
-            // Loop over bitboards opposite color.
-            for bb in self
-                .pieces
-                .iter_mut()
-                .skip(color.opposite() as usize)
-                .step_by(2)
-            {
-                if *bb & to_bb_capture != 0 {
-                    *bb ^= to_bb_capture;
-                    self.all[color.opposite() as usize] ^= to_bb_capture;
-                    self.occupied ^= to_bb_capture;
-                    break;
-                }
-            }
+            self.pieces[captured as usize] ^= to_bb_capture;
+            self.all[captured.get_color() as usize] ^= to_bb_capture;
+            self.occupied ^= to_bb_capture;
+            self.hash ^= zobrist::piece_key(captured, bitboard::get_square(to_bb_capture));
         }
 
-        self.castling_ability.clear(mv.get_from());
-        self.castling_ability.clear(mv.get_to()); // in case rook gets taken
+        self.hash ^= zobrist::castling_key(self.castling_ability);
+        if self.chess960 {
+            self.castling_ability
+                .clear_chess960(mv, self.castling_rooks);
+        } else {
+            self.castling_ability.clear(mv.get_from());
+            self.castling_ability.clear(mv.get_to()); // in case rook gets taken
+        }
+        self.hash ^= zobrist::castling_key(self.castling_ability);
     }
 
     // Updates the board with the specified move.
     pub fn update_by_move(&mut self, mv: Move) {
-        self.update_bitboards_by_move(mv);
+        self.update_bitboards_by_move(mv.get_piece_move());
 
         if let Some(promote_to) = mv.get_promotion() {
             // Pawn was moved. We now need to switch it to the new piece.
             let to_bb: BitBoard = bitboard::from_square(mv.get_to());
             self.pieces[mv.get_piece() as usize] &= !to_bb;
             self.pieces[promote_to as usize] |= to_bb;
+            self.hash ^= zobrist::piece_key(mv.get_piece(), mv.get_to())
+                ^ zobrist::piece_key(promote_to, mv.get_to());
         }
 
+        if mv.get_piece().is_pawn() || mv.is_capture() {
+            self.half_move_clock = 0;
+        } else {
+            self.half_move_clock = self.half_move_clock.saturating_add(1);
+        }
+        if self.side_to_move == Color::Black {
+            self.full_move_counter = self.full_move_counter.saturating_add(1);
+        }
+
+        self.hash ^= zobrist::en_passant_key(self.en_passant_target_square);
         self.en_passant_target_square = mv.get_en_passant_target_square();
+        self.hash ^= zobrist::en_passant_key(self.en_passant_target_square);
 
         if let Some(castling_rook_move) = mv.get_castling() {
             self.update_bitboards_by_move(castling_rook_move);
@@ -79,6 +97,105 @@ impl Board {
         self.toggle_side();
     }
 
+    // Returns the square of the piece captured by the move, which differs from
+    // the destination square for en passant.
+    fn get_capture_square(&self, mv: Move) -> Square {
+        if self.is_en_passant_capture(mv) {
+            Square::new(mv.get_from().get_rank(), mv.get_to().get_file())
+        } else {
+            mv.get_to()
+        }
+    }
+
+    // Applies the move and returns the state needed to undo it with unmake_move().
+    // The move is expected to be legal.
+    pub fn make_move(&mut self, mv: Move) -> BoardState {
+        let state = BoardState {
+            en_passant_target_square: self.en_passant_target_square,
+            castling_ability: self.castling_ability,
+            half_move_clock: self.half_move_clock,
+            hash: self.hash,
+        };
+        self.update_by_move(mv);
+        state
+    }
+
+    // Moves the piece back to its origin, without any other update.
+    fn move_back(&mut self, mv: Move) {
+        let from_to_bb: BitBoard =
+            bitboard::from_square(mv.get_from()) ^ bitboard::from_square(mv.get_to());
+        self.pieces[mv.get_piece() as usize] ^= from_to_bb;
+        self.all[mv.get_piece().get_color() as usize] ^= from_to_bb;
+        self.occupied ^= from_to_bb;
+    }
+
+    // Reverts a move done with make_move().
+    pub fn unmake_move(&mut self, mv: Move, state: BoardState) {
+        self.toggle_side();
+
+        if let Some(castling_rook_move) = mv.get_castling() {
+            self.move_back(castling_rook_move);
+        }
+
+        if let Some(promote_to) = mv.get_promotion() {
+            // Switch the new piece back to a pawn.
+            let to_bb: BitBoard = bitboard::from_square(mv.get_to());
+            self.pieces[promote_to as usize] &= !to_bb;
+            self.pieces[mv.get_piece() as usize] |= to_bb;
+        }
+
+        self.move_back(mv.get_piece_move());
+
+        self.en_passant_target_square = state.en_passant_target_square;
+        self.castling_ability = state.castling_ability;
+        self.half_move_clock = state.half_move_clock;
+        if self.side_to_move == Color::Black {
+            self.full_move_counter -= 1;
+        }
+        // Restoring the hash is simpler than updating it back.
+        self.hash = state.hash;
+
+        if let Some(captured_piece) = mv.get_captured_piece() {
+            let capture_bb = bitboard::from_square(self.get_capture_square(mv));
+            self.pieces[captured_piece as usize] |= capture_bb;
+            self.all[captured_piece.get_color() as usize] |= capture_bb;
+            self.occupied |= capture_bb;
+        }
+        debug_assert_eq!(self.validate(), Ok(()), "Invalid board after unmaking {mv}");
+    }
+
+    // Passes the turn to the opponent, for null move pruning.
+    // <https://www.chessprogramming.org/Null_Move>
+    pub fn make_null_move(&mut self) -> BoardState {
+        let state = BoardState {
+            en_passant_target_square: self.en_passant_target_square,
+            castling_ability: self.castling_ability,
+            half_move_clock: self.half_move_clock,
+            hash: self.hash,
+        };
+        self.hash ^= zobrist::en_passant_key(self.en_passant_target_square);
+        self.en_passant_target_square = None;
+        self.hash ^= zobrist::en_passant_key(None);
+        self.toggle_side();
+        state
+    }
+
+    // Reverts a move done with make_null_move().
+    pub fn unmake_null_move(&mut self, state: BoardState) {
+        self.side_to_move = self.side_to_move.opposite();
+        self.en_passant_target_square = state.en_passant_target_square;
+        self.hash = state.hash;
+    }
+
+    // Returns the board after the move, or an error if the move isn't legal in this position.
+    // Slower than copy_with_move(), as it checks that the move is one of the generated ones.
+    pub fn apply_move(&self, mv: Move) -> Result<Self, MoveError> {
+        if !self.generate_moves().contains(&mv) {
+            return Err(MoveError::IllegalMove);
+        }
+        self.copy_with_move(mv).ok_or(MoveError::IllegalMove)
+    }
+
     pub fn copy_with_move(&self, mv: Move) -> Option<Self> {
         let mut board_copy = *self;
         board_copy.update_by_move(mv);
@@ -89,15 +206,18 @@ impl Board {
             return None;
         }
 
-        if let Some(rook_mv) = mv.get_castling() {
+        if mv.is_castling() {
             // We are not allowed to be in check before the castling.
             if self.attacks_king(king_color) != 0 {
                 return None;
             }
 
             // We need to check that the king doesn't pass over an attacked square.
-            // That square is where the rook moves.
-            if self.attacks_to(rook_mv.get_to()) & self.all[king_color.opposite() as usize] != 0 {
+            // In standard chess, that square is where the rook moves.
+            let king_mv = mv.get_piece_move();
+            if bitboard::iter_squares(bitboard::ray_between(king_mv.get_from(), king_mv.get_to()))
+                .any(|sq| self.is_square_attacked(sq, king_color.opposite()))
+            {
                 return None;
             }
         }
@@ -108,9 +228,12 @@ impl Board {
 
 #[cfg(test)]
 mod tests {
-    use crate::common::{
-        Piece::{self, *},
-        Square::*,
+    use crate::{
+        common::{
+            Piece::{self, *},
+            Square::*,
+        },
+        fen,
     };
 
     use super::*;
@@ -129,13 +252,13 @@ mod tests {
     #[test]
     fn test_update_by_move_capture() {
         let mut board: Board = "2k5/8/8/8/8/8/2Pp4/2K5 w - - 0 1".into();
-        let mv = Move::capture(C1, D2, WhiteKing);
+        let mv = Move::capture(C1, D2, WhiteKing, BlackPawn);
         board.update_by_move(mv);
         assert_eq!(board.to_string(), "2k5/8/8/8/8/8/2PK4/8 b - - 0 1");
 
         let mut board: Board =
             "rnbqkbnr/ppp1pppp/8/3p4/8/2N5/PPPPPPPP/R1BQKBNR w KQkq - 0 1".into();
-        let mv = Move::capture(C3, D5, WhiteKnight);
+        let mv = Move::capture(C3, D5, WhiteKnight, BlackPawn);
         board.update_by_move(mv);
         assert_eq!(
             board.to_string(),
@@ -146,9 +269,9 @@ mod tests {
     #[test]
     fn test_update_by_move_capture_2() {
         let mut board: Board = "8/8/8/3k4/2pP4/1B6/6K1/8 b - - 0 1".into();
-        let mv = Move::capture(C4, B3, BlackPawn);
+        let mv = Move::capture(C4, B3, BlackPawn, WhiteBishop);
         board.update_by_move(mv);
-        assert_eq!(board.to_string(), "8/8/8/3k4/3P4/1p6/6K1/8 w - - 0 1");
+        assert_eq!(board.to_string(), "8/8/8/3k4/3P4/1p6/6K1/8 w - - 0 2");
         assert_eq!(board.pieces[Piece::WhiteBishop as usize], 0);
     }
 
@@ -166,9 +289,9 @@ mod tests {
     #[test]
     fn test_update_by_move_castling() {
         let mut board: Board = "4k3/8/8/8/8/8/PPPPPPPP/R3K1NR w Q - 0 1".into();
-        let mv = Move::quiet(E1, C1, WhiteKing); // White queen side castle
+        let mv = Move::castling(E1, C1, WhiteKing); // White queen side castle
         board.update_by_move(mv);
-        assert_eq!(board, "4k3/8/8/8/8/8/PPPPPPPP/2KR2NR b - - 0 1".into());
+        assert_eq!(board, "4k3/8/8/8/8/8/PPPPPPPP/2KR2NR b - - 1 1".into());
     }
 
     #[test]
@@ -179,22 +302,35 @@ mod tests {
         board.update_by_move(mv);
         assert_eq!(
             board,
-            "rnbqkbnr/ppp1pppp/3p4/8/8/5P2/PPPPPKPP/RNBQ1BNR b kq - 0 1".into()
+            "rnbqkbnr/ppp1pppp/3p4/8/8/5P2/PPPPPKPP/RNBQ1BNR b kq - 1 1".into()
         );
     }
 
     #[test]
     fn test_update_by_move_promotion() {
         let mut board: Board = "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1".into();
-        let mv = Move::new(B7, B8, Some(WhiteQueen), WhitePawn, false);
+        let mv = Move::promotion(B7, B8, WhitePawn, WhiteQueen);
         board.update_by_move(mv);
         assert_eq!(board, "1Q2k3/8/8/8/8/8/8/4K3 b - - 0 1".into());
     }
 
+    #[test]
+    fn test_update_by_move_under_promotion_capture() {
+        let mut board: Board = "r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1".into();
+        let mv = Move::new(B7, A8, Some(WhiteKnight), WhitePawn, Some(BlackRook));
+        board.update_by_move(mv);
+        assert_eq!(board, "N3k3/8/8/8/8/8/8/4K3 b - - 0 1".into());
+
+        let mut board: Board = "4k3/8/8/8/8/8/6p1/4K3 b - - 0 1".into();
+        let mv = Move::promotion(G2, G1, BlackPawn, BlackRook);
+        board.update_by_move(mv);
+        assert_eq!(board, "4k3/8/8/8/8/8/8/4K1r1 w - - 0 2".into());
+    }
+
     #[test]
     fn test_update_by_move_en_passant_capture() {
         let mut board: Board = "rnbqkbnr/2pppppp/p7/Pp6/8/8/1PPPPPPP/RNBQKBNR w KQkq b6 0 3".into();
-        let mv = Move::capture(A5, B6, WhitePawn);
+        let mv = Move::en_passant(A5, B6, WhitePawn);
         board.update_by_move(mv);
         assert_eq!(
             board,
@@ -202,11 +338,144 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_update_by_move_en_passant_capture_black_pawn() {
+        let mut board: Board =
+            "rnbqkbnr/pppp1ppp/8/4pP2/8/8/PPPPP1PP/RNBQKBNR w KQkq e6 0 2".into();
+        let mv = Move::en_passant(F5, E6, WhitePawn);
+        assert!(board.is_en_passant_capture(mv));
+        board.update_by_move(mv);
+        assert_eq!(
+            board,
+            "rnbqkbnr/pppp1ppp/4P3/8/8/8/PPPPP1PP/RNBQKBNR b KQkq - 0 2".into()
+        );
+        assert_eq!(board.pieces[Piece::BlackPawn as usize] & E5, 0);
+    }
+
+    #[test]
+    fn test_is_en_passant_capture() {
+        let board: Board = "rnbqkbnr/pppp1ppp/8/4pP2/8/8/PPPPP1PP/RNBQKBNR w KQkq e6 0 2".into();
+        assert!(!board.is_en_passant_capture(Move::quiet(F5, F6, WhitePawn)));
+        assert!(!board.is_en_passant_capture(Move::quiet(D1, E6, WhiteQueen)));
+
+        let board: Board = "rnbqkbnr/pppp1ppp/8/4pP2/8/8/PPPPP1PP/RNBQKBNR w KQkq - 0 2".into();
+        assert!(!board.is_en_passant_capture(Move::en_passant(F5, E6, WhitePawn)));
+    }
+
+    #[test]
+    fn test_make_move() {
+        let mut board: Board =
+            "rnbqkbnr/ppp1pppp/8/3p4/8/2N5/PPPPPPPP/R1BQKBNR w KQkq - 0 1".into();
+        let mv = board.new_move_from_pure("c3d5");
+        assert_eq!(mv.get_captured_piece(), Some(BlackPawn));
+        board.make_move(mv);
+        assert_eq!(
+            board,
+            "rnbqkbnr/ppp1pppp/8/3N4/8/8/PPPPPPPP/R1BQKBNR b KQkq - 0 1".into()
+        );
+
+        let mut board: Board =
+            "rnbqkbnr/pppp1ppp/8/4pP2/8/8/PPPPP1PP/RNBQKBNR w KQkq e6 0 2".into();
+        let mv = board.new_move_from_pure("f5e6");
+        assert_eq!(mv, Move::en_passant(F5, E6, WhitePawn));
+        assert_eq!(mv.get_captured_piece(), Some(BlackPawn));
+        let state = board.make_move(mv);
+        assert_eq!(state.en_passant_target_square, Some(E6));
+    }
+
+    #[test]
+    fn test_unmake_move() {
+        // Capture promotion.
+        let original: Board =
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1".into();
+        let mut board = original;
+        let mv = board.new_move_from_pure("b2a1n");
+        assert_eq!(mv.get_captured_piece(), Some(WhiteRook));
+        let state = board.make_move(mv);
+        board.unmake_move(mv, state);
+        assert_eq!(board, original);
+
+        // Castling.
+        let original: Board = "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1".into();
+        let mut board = original;
+        let mv = Move::castling(E8, C8, BlackKing);
+        let state = board.make_move(mv);
+        assert_eq!(board, "2kr3r/8/8/8/8/8/8/R3K2R w KQ - 1 2".into());
+        board.unmake_move(mv, state);
+        assert_eq!(board, original);
+    }
+
+    #[test]
+    fn test_make_unmake_all_moves() {
+        for fen in [
+            fen::START_POSITION,
+            fen::KIWIPETE,
+            fen::POSITION_3,
+            fen::POSITION_4,
+            fen::POSITION_5,
+            fen::POSITION_6,
+            "rnbqkbnr/pppp1ppp/8/4pP2/8/8/PPPPP1PP/RNBQKBNR w KQkq e6 0 2",
+        ] {
+            let original: Board = fen.into();
+            for mv in original.generate_legal_moves() {
+                let mut board = original;
+                let state = board.make_move(mv);
+                assert_eq!(Some(board), original.copy_with_move(mv));
+                assert_eq!(board.hash(), board.compute_hash());
+                board.unmake_move(mv, state);
+                assert_eq!(board, original, "Unmake failed for {mv} on {fen}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_make_unmake_null_move() {
+        let original: Board =
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3".into();
+        let mut board = original;
+        let state = board.make_null_move();
+        assert_eq!(
+            board,
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3".into()
+        );
+        assert_eq!(board.hash(), board.compute_hash());
+        board.unmake_null_move(state);
+        assert_eq!(board, original);
+    }
+
+    #[test]
+    fn test_apply_move() {
+        let board = Board::initial_board();
+        let mv = board.new_move_from_pure("e2e4");
+        let new_board = board.apply_move(mv).unwrap();
+        assert_eq!(
+            new_board,
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".into()
+        );
+
+        // Not a valid move for that piece, or not the side to move.
+        assert_eq!(
+            board.apply_move(Move::quiet(E2, E5, WhitePawn)),
+            Err(MoveError::IllegalMove)
+        );
+        assert_eq!(
+            board.apply_move(Move::quiet(E7, E5, BlackPawn)),
+            Err(MoveError::IllegalMove)
+        );
+
+        // Pinned knight.
+        let board: Board = "4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1".into();
+        assert_eq!(
+            board.apply_move(Move::quiet(E2, C3, WhiteKnight)),
+            Err(MoveError::IllegalMove)
+        );
+    }
+
     #[test]
     fn test_copy_with_move_in_check_castling() {
         let board: Board =
             "r3k2r/p1pp1pb1/bn2Qnp1/2qPN3/1p2P3/2N5/PPPBBPPP/R3K2R b KQkq - 3 2".into();
-        let castling_mv = Move::quiet(E8, G8, BlackKing);
+        let castling_mv = Move::castling(E8, G8, BlackKing);
         // Not allowed to castle if in check.
         assert_eq!(board.copy_with_move(castling_mv), None);
     }
@@ -214,7 +483,7 @@ mod tests {
     #[test]
     fn test_copy_with_move_castling_over_attacked_square() {
         let board: Board = "r3k2r/1b4bq/8/8/8/8/7B/3RK2R b Kkq - 1 1".into();
-        let castling_mv = Move::quiet(E8, C8, BlackKing);
+        let castling_mv = Move::castling(E8, C8, BlackKing);
         // Not allowed to castle over attacked square
         assert_eq!(board.copy_with_move(castling_mv), None);
     }
@@ -223,7 +492,7 @@ mod tests {
     fn test_copy_with_move_castling_rook_attacked() {
         let board: Board = "rnb2k1r/pp1Pbppp/2p5/q7/2B5/8/PPPQNnPP/RNB1K2R w KQ - 3 9".into();
         board.print();
-        let castling_mv = Move::quiet(E1, G1, WhiteKing);
+        let castling_mv = Move::castling(E1, G1, WhiteKing);
         // Rook is attacked, but castling is still allowed then.
         assert!(board.copy_with_move(castling_mv).is_some());
     }
@@ -242,11 +511,11 @@ mod tests {
         // Push or en passant taking is not allowed, as it leaves the king in check.
         let mv = Move::quiet(C4, C3, BlackPawn);
         assert_eq!(board.copy_with_move(mv), None);
-        let mv = Move::capture(C4, D3, BlackPawn);
+        let mv = Move::en_passant(C4, D3, BlackPawn);
         assert_eq!(board.copy_with_move(mv), None);
 
         // But taking the attacker is.
-        let mv = Move::capture(C4, B3, BlackPawn);
+        let mv = Move::capture(C4, B3, BlackPawn, WhiteBishop);
         assert!(board.copy_with_move(mv).is_some());
     }
 }
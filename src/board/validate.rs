@@ -0,0 +1,167 @@
+//! Consistency checks of the board, to catch bugs in the board updates.
+
+use std::fmt;
+
+use crate::{
+    bitboard::{self, BitBoard},
+    common::{Color, Piece},
+};
+
+use super::Board;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardError {
+    // The same square is set in several piece bitboards.
+    OverlappingPieces,
+    // The bitboard of all pieces of that color doesn't match the piece bitboards.
+    ColorMismatch(Color),
+    OccupiedMismatch,
+    // There must be exactly one king of each color.
+    InvalidKingCount(Color),
+    PawnOnBackRank,
+    HashMismatch,
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardError::OverlappingPieces => write!(f, "Several pieces on the same square"),
+            BoardError::ColorMismatch(color) => {
+                write!(f, "Pieces of {color:?} don't match the piece bitboards")
+            }
+            BoardError::OccupiedMismatch => {
+                write!(f, "Occupied squares don't match the pieces")
+            }
+            BoardError::InvalidKingCount(color) => write!(f, "Not exactly one {color:?} king"),
+            BoardError::PawnOnBackRank => write!(f, "Pawn on the first or last rank"),
+            BoardError::HashMismatch => write!(f, "Hash doesn't match the position"),
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+impl Board {
+    // The bitboards are consistent with each other, which must hold even for positions being built.
+    pub(super) fn validate_bitboards(&self) -> Result<(), BoardError> {
+        let pieces_count: u32 = self.pieces.iter().map(|&bb| bitboard::count_ones(bb)).sum();
+        let union = self.pieces.iter().fold(0, |union, &bb| union | bb);
+        if pieces_count != bitboard::count_ones(union) {
+            return Err(BoardError::OverlappingPieces);
+        }
+
+        for color in [Color::White, Color::Black] {
+            let color_bb: BitBoard = Piece::pieces_of_color(color)
+                .iter()
+                .fold(0, |all, &piece| all | self.pieces_of(piece));
+            if color_bb != self.all_pieces_of_color(color) {
+                return Err(BoardError::ColorMismatch(color));
+            }
+        }
+        if self.occupied != union {
+            return Err(BoardError::OccupiedMismatch);
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), BoardError> {
+        self.validate_bitboards()?;
+
+        for color in [Color::White, Color::Black] {
+            if bitboard::count_ones(self.pieces_of(Piece::get_king_of(color))) != 1 {
+                return Err(BoardError::InvalidKingCount(color));
+            }
+        }
+
+        let pawns = self.pieces_of(Piece::WhitePawn) | self.pieces_of(Piece::BlackPawn);
+        if bitboard::contains(pawns, bitboard::RANK_1 | bitboard::RANK_8) {
+            return Err(BoardError::PawnOnBackRank);
+        }
+
+        if self.hash != self.compute_hash() {
+            return Err(BoardError::HashMismatch);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fen;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_ok() {
+        for fen in [
+            fen::START_POSITION,
+            fen::KIWIPETE,
+            fen::POSITION_3,
+            fen::POSITION_4,
+        ] {
+            assert_eq!(Board::from_fen(fen).validate(), Ok(()), "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_validate_kings() {
+        assert_eq!(
+            Board::from_fen(fen::EMPTY_BOARD).validate(),
+            Err(BoardError::InvalidKingCount(Color::White))
+        );
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K1K1 w - - 0 1").validate(),
+            Err(BoardError::InvalidKingCount(Color::White))
+        );
+        assert_eq!(
+            Board::from_fen("8/8/8/8/8/8/8/4K3 w - - 0 1").validate(),
+            Err(BoardError::InvalidKingCount(Color::Black))
+        );
+    }
+
+    #[test]
+    fn test_validate_pawns() {
+        assert_eq!(
+            Board::from_fen("4k2P/8/8/8/8/8/8/4K3 w - - 0 1").validate(),
+            Err(BoardError::PawnOnBackRank)
+        );
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/p3K3 w - - 0 1").validate(),
+            Err(BoardError::PawnOnBackRank)
+        );
+    }
+
+    #[test]
+    fn test_validate_corrupted() {
+        let valid = Board::initial_board();
+
+        let mut board = valid;
+        board.pieces[Piece::WhiteQueen as usize] |= board.pieces_of(Piece::WhiteKing);
+        assert_eq!(board.validate(), Err(BoardError::OverlappingPieces));
+
+        let mut board = valid;
+        board.all[Color::Black as usize] = 0;
+        assert_eq!(
+            board.validate(),
+            Err(BoardError::ColorMismatch(Color::Black))
+        );
+
+        let mut board = valid;
+        board.occupied = 0;
+        assert_eq!(board.validate(), Err(BoardError::OccupiedMismatch));
+
+        let mut board = valid;
+        board.hash ^= 1;
+        assert_eq!(board.validate(), Err(BoardError::HashMismatch));
+    }
+
+    #[test]
+    fn test_validate_after_moves() {
+        let mut board: Board = fen::KIWIPETE.into();
+        for mv in board.generate_legal_moves() {
+            let state = board.make_move(mv);
+            assert_eq!(board.validate(), Ok(()), "{mv}");
+            board.unmake_move(mv, state);
+        }
+    }
+}
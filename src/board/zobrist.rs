@@ -0,0 +1,250 @@
+//! Zobrist hashing.
+//! <https://www.chessprogramming.org/Zobrist_Hashing>
+
+use crate::{
+    bitboard,
+    common::{Color, Piece, Square},
+};
+
+use super::{Board, CastlingAbility};
+
+// Pseudo random number generator, so that we can create the keys at compile time.
+// <https://prng.di.unimi.it/splitmix64.c>
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+struct ZobristKeys {
+    pieces: [[u64; 64]; 12],
+    side_to_move: u64,
+    // One entry for each castling ability combination, so that it can be indexed directly.
+    castling: [u64; 16],
+    en_passant_file: [u64; 8],
+}
+
+const fn generate_keys() -> ZobristKeys {
+    let mut state = 0x4B41_494B; // Any seed is fine, "KAIK".
+    let mut value;
+
+    let mut pieces = [[0; 64]; 12];
+    let mut p = 0;
+    while p < 12 {
+        let mut sq = 0;
+        while sq < 64 {
+            (state, value) = splitmix64(state);
+            pieces[p][sq] = value;
+            sq += 1;
+        }
+        p += 1;
+    }
+
+    (state, value) = splitmix64(state);
+    let side_to_move = value;
+
+    let mut castling_rights = [0; 4];
+    let mut i = 0;
+    while i < 4 {
+        (state, value) = splitmix64(state);
+        castling_rights[i] = value;
+        i += 1;
+    }
+    let mut castling = [0; 16];
+    let mut ability = 0;
+    while ability < 16 {
+        let mut i = 0;
+        while i < 4 {
+            if ability & (1 << i) != 0 {
+                castling[ability] ^= castling_rights[i];
+            }
+            i += 1;
+        }
+        ability += 1;
+    }
+
+    let mut en_passant_file = [0; 8];
+    let mut file = 0;
+    while file < 8 {
+        (state, value) = splitmix64(state);
+        en_passant_file[file] = value;
+        file += 1;
+    }
+
+    ZobristKeys {
+        pieces,
+        side_to_move,
+        castling,
+        en_passant_file,
+    }
+}
+
+const KEYS: ZobristKeys = generate_keys();
+
+pub fn piece_key(piece: Piece, square: Square) -> u64 {
+    KEYS.pieces[piece as usize][square as usize]
+}
+
+pub fn side_to_move_key() -> u64 {
+    KEYS.side_to_move
+}
+
+pub fn castling_key(castling_ability: CastlingAbility) -> u64 {
+    KEYS.castling[castling_ability.0 as usize]
+}
+
+pub fn en_passant_key(en_passant_target_square: Option<Square>) -> u64 {
+    en_passant_target_square.map_or(0, |sq| KEYS.en_passant_file[sq.get_file() as usize])
+}
+
+impl Board {
+    // Calculates the hash from scratch.
+    // During the game, the hash is updated incrementally instead.
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+        for piece in Piece::ALL_PIECES {
+            for square in bitboard::iter_squares(self.pieces_of(piece)) {
+                hash ^= piece_key(piece, square);
+            }
+        }
+        if self.side_to_move == Color::Black {
+            hash ^= side_to_move_key();
+        }
+        hash ^= castling_key(self.castling_ability);
+        hash ^= en_passant_key(self.en_passant_target_square);
+        hash
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+// Much faster than hashing all the fields, and consistent with Eq, as equal boards have the same Zobrist hash.
+impl std::hash::Hash for Board {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        hash::{BuildHasher, RandomState},
+    };
+
+    use crate::fen;
+
+    use super::*;
+
+    #[test]
+    fn test_keys_are_unique() {
+        let mut keys = KEYS.pieces.iter().flatten().copied().collect::<Vec<_>>();
+        keys.push(KEYS.side_to_move);
+        keys.extend_from_slice(&KEYS.castling[1..]);
+        keys.extend_from_slice(&KEYS.en_passant_file);
+        let count = keys.len();
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(keys.len(), count);
+    }
+
+    #[test]
+    fn test_hash_different_positions() {
+        let board = Board::initial_board();
+        assert_eq!(board.hash(), board.compute_hash());
+        assert_ne!(board.hash(), 0);
+
+        let other_side: Board = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1".into();
+        assert_ne!(board.hash(), other_side.hash());
+
+        let no_castling: Board = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1".into();
+        assert_ne!(board.hash(), no_castling.hash());
+
+        assert_ne!(
+            Board::from_fen(fen::KIWIPETE).hash(),
+            Board::from_fen(fen::POSITION_4).hash()
+        );
+    }
+
+    #[test]
+    fn test_hash_same_position_different_move_orders() {
+        let mut board1 = Board::initial_board();
+        for mv in ["g1f3", "g8f6", "b1c3", "b8c6"] {
+            board1.make_move(board1.new_move_from_pure(mv));
+        }
+        let mut board2 = Board::initial_board();
+        for mv in ["b1c3", "b8c6", "g1f3", "g8f6"] {
+            board2.make_move(board2.new_move_from_pure(mv));
+        }
+        assert_eq!(board1.hash(), board2.hash());
+        assert_eq!(board1.hash(), board1.compute_hash());
+
+        // Double pushes set the en passant square, single pushes don't.
+        let mut board1 = Board::initial_board();
+        for mv in ["e2e4", "e7e5"] {
+            board1.make_move(board1.new_move_from_pure(mv));
+        }
+        let mut board2 = Board::initial_board();
+        for mv in ["e2e3", "e7e6", "e3e4", "e6e5"] {
+            board2.make_move(board2.new_move_from_pure(mv));
+        }
+        assert_ne!(board1.hash(), board2.hash());
+
+        // Once the en passant square is gone, the positions are the same.
+        for mv in ["g1f3", "g8f6"] {
+            board1.make_move(board1.new_move_from_pure(mv));
+            board2.make_move(board2.new_move_from_pure(mv));
+        }
+        assert_eq!(board1.hash(), board2.hash());
+        assert_eq!(board1.hash(), board1.compute_hash());
+    }
+
+    #[test]
+    fn test_std_hash_same_position_different_move_orders() {
+        let mut board1 = Board::initial_board();
+        for mv in ["g1f3", "g8f6", "b1c3", "b8c6"] {
+            board1.make_move(board1.new_move_from_pure(mv));
+        }
+        let mut board2 = Board::initial_board();
+        for mv in ["b1c3", "b8c6", "g1f3", "g8f6"] {
+            board2.make_move(board2.new_move_from_pure(mv));
+        }
+        assert_eq!(board1, board2);
+        assert_eq!(board1.hash(), board2.hash());
+
+        let state = RandomState::new();
+        assert_eq!(state.hash_one(board1), state.hash_one(board2));
+        assert_ne!(
+            state.hash_one(board1),
+            state.hash_one(Board::initial_board())
+        );
+    }
+
+    #[test]
+    fn test_hash_set() {
+        let mut board1 = Board::initial_board();
+        for mv in ["e2e3", "e7e6", "d2d3"] {
+            board1.make_move(board1.new_move_from_pure(mv));
+        }
+        let mut board2 = Board::initial_board();
+        for mv in ["d2d3", "e7e6", "e2e3"] {
+            board2.make_move(board2.new_move_from_pure(mv));
+        }
+        let mut set = HashSet::new();
+        assert!(set.insert(board1));
+        assert!(!set.insert(board2));
+        assert!(set.insert(Board::initial_board()));
+        assert_eq!(set.len(), 2);
+
+        // Same Zobrist hash, but the boards aren't equal.
+        let other_clock: Board = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 5 1".into();
+        assert_eq!(other_clock.hash(), Board::initial_board().hash());
+        assert!(set.insert(other_clock));
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&board2));
+    }
+}
@@ -0,0 +1,163 @@
+//! Opening book: Moves to play in known positions, without searching.
+//! <https://www.chessprogramming.org/Opening_Book>
+//!
+//! The book is a text file with one position and move per line, like:
+//! `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 e2e4`
+//! A move listed several times for the same position is played more often.
+//! Empty lines and lines starting with '#' are ignored.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{BufRead, BufReader, Read},
+};
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{board::Board, fen::FenError, moves::Move};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookError {
+    Io(String),
+    // Line numbers are 1 based.
+    InvalidLine(usize),
+    InvalidFen(usize, FenError),
+    InvalidMove(usize, String),
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookError::Io(err) => write!(f, "Failed to read the book: {err}"),
+            BookError::InvalidLine(line) => write!(f, "Line {line}: Missing move"),
+            BookError::InvalidFen(line, err) => write!(f, "Line {line}: Invalid FEN: {err}"),
+            BookError::InvalidMove(line, mv) => {
+                write!(f, "Line {line}: Invalid or illegal move: {mv}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BookError {}
+
+#[derive(Debug, Clone, Default)]
+pub struct OpeningBook {
+    // Moves indexed by the Zobrist hash of the position.
+    moves: HashMap<u64, Vec<Move>>,
+}
+
+fn parse_line(line: &str, line_number: usize) -> Result<(Board, Move), BookError> {
+    let (fen, mv) = line
+        .trim()
+        .rsplit_once(char::is_whitespace)
+        .ok_or(BookError::InvalidLine(line_number))?;
+    let board = Board::try_from_fen(fen.trim_end())
+        .map_err(|err| BookError::InvalidFen(line_number, err))?;
+    let invalid_move = || BookError::InvalidMove(line_number, mv.to_string());
+    let mv = Move::from_uci(mv, &board).map_err(|_| invalid_move())?;
+    if !board.generate_legal_moves().contains(&mv) {
+        return Err(invalid_move());
+    }
+    Ok((board, mv))
+}
+
+impl OpeningBook {
+    pub fn load(reader: impl Read) -> Result<Self, BookError> {
+        let mut book = Self::default();
+        for (i, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.map_err(|err| BookError::Io(err.to_string()))?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (board, mv) = parse_line(&line, i + 1)?;
+            book.moves.entry(board.hash()).or_default().push(mv);
+        }
+        Ok(book)
+    }
+
+    // Number of positions in the book.
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    // A random book move for this position, None if the position isn't in the book.
+    pub fn lookup(&self, board: &Board) -> Option<Move> {
+        self.lookup_with_rng(board, &mut rand::thread_rng())
+    }
+
+    fn lookup_with_rng<R: Rng>(&self, board: &Board, rng: &mut R) -> Option<Move> {
+        // As moves can be listed several times, picking one uniformly gives a weighted selection.
+        self.moves.get(&board.hash())?.choose(rng).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::fen;
+
+    use super::*;
+
+    const BOOK: &str = "# A tiny book
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 e2e4
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 e2e4
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 d2d4
+
+rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1 c7c5
+";
+
+    #[test]
+    fn test_load() {
+        let book = OpeningBook::load(BOOK.as_bytes()).unwrap();
+        assert_eq!(book.len(), 2);
+
+        let mut board = Board::initial_board();
+        let mv = book.lookup(&board).unwrap();
+        assert!([
+            board.new_move_from_pure("e2e4"),
+            board.new_move_from_pure("d2d4")
+        ]
+        .contains(&mv));
+
+        board.update_by_move(board.new_move_from_pure("e2e4"));
+        assert_eq!(book.lookup(&board), Some(board.new_move_from_pure("c7c5")));
+
+        board.update_by_move(board.new_move_from_pure("c7c5"));
+        assert_eq!(book.lookup(&board), None);
+    }
+
+    #[test]
+    fn test_lookup_weighted() {
+        let book = OpeningBook::load(BOOK.as_bytes()).unwrap();
+        let board = Board::initial_board();
+        let e4 = board.new_move_from_pure("e2e4");
+        let mut rng = StdRng::seed_from_u64(0);
+        let e4_count = (0..1000)
+            .filter(|_| book.lookup_with_rng(&board, &mut rng) == Some(e4))
+            .count();
+        // e4 is listed twice, it should be played about two thirds of the time.
+        assert!((600..740).contains(&e4_count));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        assert_eq!(
+            OpeningBook::load("e2e4".as_bytes()).unwrap_err(),
+            BookError::InvalidLine(1)
+        );
+        assert_eq!(
+            OpeningBook::load("\nrnbqkbnr/pppppppp/8/8 w KQkq - 0 1 e2e4".as_bytes()).unwrap_err(),
+            BookError::InvalidFen(2, FenError::InvalidPiecePlacement)
+        );
+        // Illegal move.
+        assert_eq!(
+            OpeningBook::load(format!("{} e2e5", fen::START_POSITION).as_bytes()).unwrap_err(),
+            BookError::InvalidMove(1, "e2e5".to_string())
+        );
+    }
+}
@@ -9,6 +9,7 @@ mod squares;
 pub use colors::Color;
 pub use pieces::Piece;
 pub use pieces::PieceListBoard;
+pub use pieces::PieceType;
 pub use squares::Square;
 
 pub const ENGINE_NAME: &str = "Kaik";
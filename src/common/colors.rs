@@ -1,4 +1,6 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops::Not};
+
+use super::Piece;
 
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -22,6 +24,22 @@ impl Color {
             Self::Black => Self::White,
         }
     }
+
+    pub const fn is_white(self) -> bool {
+        matches!(self, Self::White)
+    }
+
+    pub const fn from_piece(piece: Piece) -> Self {
+        piece.get_color()
+    }
+}
+
+impl Not for Color {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        self.opposite()
+    }
 }
 
 impl Display for Color {
@@ -36,3 +54,23 @@ impl Display for Color {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opposite() {
+        assert_eq!(Color::White.opposite(), Color::Black);
+        assert_eq!(!Color::Black, Color::White);
+        assert_eq!(!!Color::White, Color::White);
+    }
+
+    #[test]
+    fn test_is_white_and_from_piece() {
+        assert!(Color::White.is_white());
+        assert!(!Color::Black.is_white());
+        assert_eq!(Color::from_piece(Piece::WhiteKnight), Color::White);
+        assert_eq!(Color::from_piece(Piece::BlackKing), Color::Black);
+    }
+}
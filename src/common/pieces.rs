@@ -20,8 +20,53 @@ pub enum Piece {
     BlackKing,
 }
 
+// A piece without its color, in the same order as the pieces.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PieceType {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+impl PieceType {
+    pub const ALL: [PieceType; 6] = [
+        PieceType::Pawn,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+        PieceType::King,
+    ];
+
+    // Centipawn values. The king has none, as it can't be captured.
+    // <https://www.chessprogramming.org/Simplified_Evaluation_Function>
+    pub const fn material_value(self) -> i32 {
+        match self {
+            PieceType::Pawn => 100,
+            PieceType::Knight => 320,
+            PieceType::Bishop => 330,
+            PieceType::Rook => 500,
+            PieceType::Queen => 900,
+            PieceType::King => 0,
+        }
+    }
+}
+
 pub type PieceListBoard = Vec<Option<Piece>>;
 
+// The board stores the pieces in an array indexed by the enum value.
+const _: () = {
+    let mut i = 0;
+    while i < Piece::ALL_PIECES.len() {
+        assert!(Piece::ALL_PIECES[i] as usize == i);
+        i += 1;
+    }
+};
+
 impl TryFrom<char> for Piece {
     type Error = &'static str;
 
@@ -65,7 +110,7 @@ impl From<Piece> for char {
 
 impl fmt::Display for Piece {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // write!(f, "{}", self.as_unicode())
+        // write!(f, "{}", self.unicode_symbol())
         write!(f, "{}", char::from(*self))
     }
 }
@@ -86,6 +131,25 @@ impl Piece {
         Piece::BlackKing,
     ];
 
+    // Pieces of one side only, from pawn to king.
+    pub const WHITE_PIECES: [Piece; 6] = [
+        Piece::WhitePawn,
+        Piece::WhiteKnight,
+        Piece::WhiteBishop,
+        Piece::WhiteRook,
+        Piece::WhiteQueen,
+        Piece::WhiteKing,
+    ];
+
+    pub const BLACK_PIECES: [Piece; 6] = [
+        Piece::BlackPawn,
+        Piece::BlackKnight,
+        Piece::BlackBishop,
+        Piece::BlackRook,
+        Piece::BlackQueen,
+        Piece::BlackKing,
+    ];
+
     pub const PROMOTION_PIECES: [[Piece; 4]; 2] = [
         [
             Piece::WhiteQueen,
@@ -129,8 +193,24 @@ impl Piece {
         Color::new(self as usize % 2)
     }
 
+    pub const fn pieces_of_color(color: Color) -> [Piece; 6] {
+        if color.is_white() {
+            Self::WHITE_PIECES
+        } else {
+            Self::BLACK_PIECES
+        }
+    }
+
+    pub const fn piece_type(self) -> PieceType {
+        PieceType::ALL[self as usize / 2]
+    }
+
+    pub const fn from_parts(color: Color, piece_type: PieceType) -> Self {
+        Self::ALL_PIECES[piece_type as usize * 2 + color as usize]
+    }
+
     pub const fn get_pawn_of(color: Color) -> Self {
-        if matches!(color, Color::White) {
+        if color.is_white() {
             Piece::WhitePawn
         } else {
             Piece::BlackPawn
@@ -138,7 +218,7 @@ impl Piece {
     }
 
     pub const fn get_knight_of(color: Color) -> Self {
-        if matches!(color, Color::White) {
+        if color.is_white() {
             Piece::WhiteKnight
         } else {
             Piece::BlackKnight
@@ -146,7 +226,7 @@ impl Piece {
     }
 
     pub const fn get_bishop_of(color: Color) -> Self {
-        if matches!(color, Color::White) {
+        if color.is_white() {
             Piece::WhiteBishop
         } else {
             Piece::BlackBishop
@@ -154,7 +234,7 @@ impl Piece {
     }
 
     pub const fn get_rook_of(color: Color) -> Self {
-        if matches!(color, Color::White) {
+        if color.is_white() {
             Piece::WhiteRook
         } else {
             Piece::BlackRook
@@ -162,7 +242,7 @@ impl Piece {
     }
 
     pub const fn get_queen_of(color: Color) -> Self {
-        if matches!(color, Color::White) {
+        if color.is_white() {
             Piece::WhiteQueen
         } else {
             Piece::BlackQueen
@@ -170,14 +250,19 @@ impl Piece {
     }
 
     pub const fn get_king_of(color: Color) -> Self {
-        if matches!(color, Color::White) {
+        if color.is_white() {
             Piece::WhiteKing
         } else {
             Piece::BlackKing
         }
     }
 
-    pub fn as_unicode(self) -> char {
+    // The letter used in FEN, upper case for white.
+    pub fn ascii_symbol(self) -> char {
+        self.into()
+    }
+
+    pub fn unicode_symbol(self) -> char {
         match self {
             Piece::WhitePawn => '♙',
             Piece::BlackPawn => '♟',
@@ -194,6 +279,12 @@ impl Piece {
         }
     }
 
+    pub fn from_unicode(c: char) -> Option<Piece> {
+        Self::ALL_PIECES
+            .into_iter()
+            .find(|piece| piece.unicode_symbol() == c)
+    }
+
     // Converts a string with pieces into vector of Piece. Starts with pieces on A8, A7, etc.
     // Empty squares are represented with dots.
     // The string may have line breaks, spaces etc, they are just ignored.
@@ -218,6 +309,19 @@ impl Piece {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_symbols() {
+        assert_eq!(Piece::WhiteKnight.ascii_symbol(), 'N');
+        assert_eq!(Piece::BlackQueen.ascii_symbol(), 'q');
+        assert_eq!(Piece::WhiteKing.unicode_symbol(), '♔');
+        assert_eq!(Piece::BlackPawn.unicode_symbol(), '♟');
+        for piece in Piece::ALL_PIECES {
+            assert_eq!(Piece::from_unicode(piece.unicode_symbol()), Some(piece));
+            assert_eq!(Piece::try_from(piece.ascii_symbol()), Ok(piece));
+        }
+        assert_eq!(Piece::from_unicode('K'), None);
+    }
+
     #[test]
     fn test_order() {
         assert_eq!(Piece::WhitePawn as usize, 0);
@@ -233,4 +337,36 @@ mod tests {
         assert_eq!(Piece::WhiteKing as usize, 10);
         assert_eq!(Piece::BlackKing as usize, 11);
     }
+
+    #[test]
+    fn test_pieces_of_color() {
+        assert!(Piece::WHITE_PIECES
+            .iter()
+            .all(|p| p.get_color() == Color::White));
+        assert!(Piece::BLACK_PIECES
+            .iter()
+            .all(|p| p.get_color() == Color::Black));
+        for (white, black) in Piece::WHITE_PIECES.iter().zip(Piece::BLACK_PIECES) {
+            assert_eq!(white.piece_type(), black.piece_type());
+        }
+        assert_eq!(Piece::pieces_of_color(Color::Black), Piece::BLACK_PIECES);
+    }
+
+    #[test]
+    fn test_piece_type() {
+        assert_eq!(Piece::WhitePawn.piece_type(), PieceType::Pawn);
+        assert_eq!(Piece::BlackBishop.piece_type(), PieceType::Bishop);
+        assert_eq!(Piece::BlackKing.piece_type(), PieceType::King);
+        for piece in Piece::ALL_PIECES {
+            assert_eq!(
+                Piece::from_parts(piece.get_color(), piece.piece_type()),
+                piece
+            );
+        }
+        assert_eq!(
+            Piece::from_parts(Color::Black, PieceType::Queen),
+            Piece::BlackQueen
+        );
+        assert_eq!(PieceType::Rook.material_value(), 500);
+    }
 }
@@ -126,18 +126,54 @@ impl TryFrom<&str> for Square {
 }
 
 impl Square {
+    // Checked conversion from an index, for when it may be out of the board.
+    // A TryFrom<u8> isn't possible, as it would conflict with From<u8>.
+    pub fn from_index(index: u8) -> Option<Self> {
+        (index < 64).then(|| index.into())
+    }
+
     pub fn new(rank: u8, file: u8) -> Self {
         ((rank << 3) + file).into()
     }
 
     pub fn get_rank(self) -> u8 {
-        (self as u8 & 56) >> 3
+        (self as u8 & 0x38) >> 3
     }
 
     pub fn get_file(self) -> u8 {
         self as u8 & 7
     }
 
+    pub fn same_file(self, other: Square) -> bool {
+        self.get_file() == other.get_file()
+    }
+
+    // If the files are next to each other, whatever the ranks.
+    pub fn adjacent_file(self, other: Square) -> bool {
+        self.get_file().abs_diff(other.get_file()) == 1
+    }
+
+    // If the squares are on a common diagonal or anti-diagonal, like a bishop can move.
+    pub fn same_diagonal(self, other: Square) -> bool {
+        self.get_rank().abs_diff(other.get_rank()) == self.get_file().abs_diff(other.get_file())
+    }
+
+    // Number of king steps between the two squares.
+    // <https://www.chessprogramming.org/Distance>
+    pub fn chebyshev_distance(self, other: Square) -> u8 {
+        let rank_distance = self.get_rank().abs_diff(other.get_rank());
+        let file_distance = self.get_file().abs_diff(other.get_file());
+        rank_distance.max(file_distance)
+    }
+
+    // Sum of the rank and file distances, number of rook steps on an empty board.
+    // <https://www.chessprogramming.org/Manhattan-Distance>
+    pub fn manhattan_distance(self, other: Square) -> u8 {
+        let rank_distance = self.get_rank().abs_diff(other.get_rank());
+        let file_distance = self.get_file().abs_diff(other.get_file());
+        rank_distance + file_distance
+    }
+
     pub fn is_promotion_rank_for(self, color: Color) -> bool {
         const PROMOTION_RANK: [u8; 2] = [7, 0];
         self.get_rank() == PROMOTION_RANK[color as usize]
@@ -163,6 +199,51 @@ mod tests {
         assert_eq!(Square::new(2, 2), Square::C3);
     }
 
+    #[test]
+    fn test_from_index() {
+        assert_eq!(Square::from_index(0), Some(Square::A1));
+        assert_eq!(Square::from_index(28), Some(Square::E4));
+        assert_eq!(Square::from_index(63), Some(Square::H8));
+        assert_eq!(Square::from_index(64), None);
+        assert_eq!(Square::from_index(u8::MAX), None);
+    }
+
+    #[test]
+    fn test_same_file() {
+        assert!(Square::E2.same_file(Square::E7));
+        assert!(Square::E2.same_file(Square::E2));
+        assert!(!Square::E2.same_file(Square::D2));
+    }
+
+    #[test]
+    fn test_adjacent_file() {
+        assert!(Square::E2.adjacent_file(Square::D7));
+        assert!(Square::E2.adjacent_file(Square::F2));
+        assert!(Square::A1.adjacent_file(Square::B8));
+        assert!(!Square::E2.adjacent_file(Square::E3));
+        assert!(!Square::A1.adjacent_file(Square::H1));
+    }
+
+    #[test]
+    fn test_same_diagonal() {
+        assert!(Square::A1.same_diagonal(Square::H8));
+        assert!(Square::C1.same_diagonal(Square::H6));
+        assert!(Square::A8.same_diagonal(Square::H1));
+        assert!(Square::E4.same_diagonal(Square::B7));
+        assert!(!Square::E4.same_diagonal(Square::E5));
+        assert!(!Square::A1.same_diagonal(Square::B3));
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(Square::A1.chebyshev_distance(Square::H8), 7);
+        assert_eq!(Square::A1.manhattan_distance(Square::H8), 14);
+        assert_eq!(Square::E4.chebyshev_distance(Square::E4), 0);
+        assert_eq!(Square::E4.chebyshev_distance(Square::F6), 2);
+        assert_eq!(Square::E4.manhattan_distance(Square::F6), 3);
+        assert_eq!(Square::H1.manhattan_distance(Square::A2), 8);
+    }
+
     #[test]
     fn test_get_rank() {
         assert_eq!(Square::A1.get_rank(), 0);
@@ -0,0 +1,273 @@
+//! Extended Position Description: The first four fields of FEN, followed by operations.
+//! <https://www.chessprogramming.org/Extended_Position_Description>
+//!
+//! Test suites use them to give the best move of a position, like:
+//! `2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";`
+
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+};
+
+use crate::{
+    board::{Board, GameHistory},
+    fen::FenError,
+    moves::Move,
+    pgn,
+    search::{self, SearchConfig},
+};
+
+// Operands indexed by opcode, without the quotes of string operands.
+pub type EpdOps = HashMap<String, String>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EpdError {
+    Io(String),
+    MissingFields,
+    InvalidFen(FenError),
+    InvalidOperation(String),
+    InvalidMove(String),
+    // Line numbers are 1 based.
+    InvalidLine(usize, Box<EpdError>),
+}
+
+impl fmt::Display for EpdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EpdError::Io(err) => write!(f, "Failed to read the EPD file: {err}"),
+            EpdError::MissingFields => write!(f, "Missing position fields"),
+            EpdError::InvalidFen(err) => write!(f, "Invalid position: {err}"),
+            EpdError::InvalidOperation(op) => write!(f, "Invalid operation: {op}"),
+            EpdError::InvalidMove(mv) => write!(f, "Invalid or illegal move: {mv}"),
+            EpdError::InvalidLine(line, err) => write!(f, "Line {line}: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for EpdError {}
+
+// Splits the operations on the semicolons that aren't in a string operand.
+fn split_operations(s: &str) -> Vec<&str> {
+    let mut operations = Vec::new();
+    let mut in_string = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            ';' if !in_string => {
+                operations.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    operations.push(&s[start..]);
+    operations
+        .into_iter()
+        .map(str::trim)
+        .filter(|op| !op.is_empty())
+        .collect()
+}
+
+fn parse_operation(op: &str) -> Result<(String, String), EpdError> {
+    let (opcode, operand) = op.split_once(char::is_whitespace).unwrap_or((op, ""));
+    if !opcode.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !opcode
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(EpdError::InvalidOperation(op.to_string()));
+    }
+    let operand = operand.trim();
+    let operand = operand
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(operand);
+    Ok((opcode.to_string(), operand.to_string()))
+}
+
+// Parses an EPD line. The clocks are taken from the hmvc and fmvn operations if present.
+pub fn parse(epd: &str) -> Result<(Board, EpdOps), EpdError> {
+    let mut fields = Vec::with_capacity(4);
+    let mut rest = epd.trim();
+    for _ in 0..4 {
+        let (field, remaining) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        if field.is_empty() {
+            return Err(EpdError::MissingFields);
+        }
+        fields.push(field);
+        rest = remaining.trim_start();
+    }
+
+    let ops = split_operations(rest)
+        .into_iter()
+        .map(parse_operation)
+        .collect::<Result<EpdOps, _>>()?;
+
+    let half_move_clock = ops.get("hmvc").map_or("0", String::as_str);
+    let full_move_counter = ops.get("fmvn").map_or("1", String::as_str);
+    let fen = format!("{} {half_move_clock} {full_move_counter}", fields.join(" "));
+    let board = Board::try_from_fen(&fen).map_err(EpdError::InvalidFen)?;
+    Ok((board, ops))
+}
+
+// The moves in SAN of an operation like bm or am, empty if the operation is missing.
+pub fn get_moves(board: &Board, ops: &EpdOps, opcode: &str) -> Result<Vec<Move>, EpdError> {
+    ops.get(opcode).map_or(Ok(Vec::new()), |operand| {
+        operand
+            .split_whitespace()
+            .map(|san| {
+                pgn::find_move(board, san).map_err(|_| EpdError::InvalidMove(san.to_string()))
+            })
+            .collect()
+    })
+}
+
+// Result of the search on one position of a test suite.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestResult {
+    // Value of the id operation, empty if there is none.
+    pub id: String,
+    pub best_move: Option<Move>,
+    // If the move found is one of the bm moves, and none of the am moves.
+    pub passed: bool,
+}
+
+fn run_test(board: &Board, ops: &EpdOps, depth: u8) -> Result<TestResult, EpdError> {
+    let best_moves = get_moves(board, ops, "bm")?;
+    let avoid_moves = get_moves(board, ops, "am")?;
+    let mut board = *board;
+    let mut history = GameHistory::new(&board);
+    let result = search::iterative_deepening(
+        &mut board,
+        &mut history,
+        &SearchConfig::default(),
+        depth,
+        None,
+        &mut io::sink(),
+    );
+    let best_move = result.best_move();
+    let passed = best_move.is_some_and(|mv| {
+        (best_moves.is_empty() || best_moves.contains(&mv)) && !avoid_moves.contains(&mv)
+    });
+    Ok(TestResult {
+        id: ops.get("id").cloned().unwrap_or_default(),
+        best_move,
+        passed,
+    })
+}
+
+// Searches each position of the suite to that depth.
+// Empty lines and lines starting with '#' are ignored.
+pub fn run_suite(reader: impl Read, depth: u8) -> Result<Vec<TestResult>, EpdError> {
+    let mut results = Vec::new();
+    for (i, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line.map_err(|err| EpdError::Io(err.to_string()))?;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let result = parse(&line).and_then(|(board, ops)| run_test(&board, &ops, depth));
+        results.push(result.map_err(|err| EpdError::InvalidLine(i + 1, Box::new(err)))?);
+    }
+    Ok(results)
+}
+
+pub fn run_epd_suite(path: &Path, depth: u8) -> Result<Vec<TestResult>, EpdError> {
+    let file = File::open(path).map_err(|err| EpdError::Io(err.to_string()))?;
+    run_suite(file, depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fen;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let (board, ops) = parse(
+            r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";"#,
+        )
+        .unwrap();
+        assert_eq!(
+            board,
+            Board::from_fen("2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1")
+        );
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops["bm"], "Qg6");
+        assert_eq!(ops["id"], "WAC.001");
+        assert_eq!(
+            get_moves(&board, &ops, "bm"),
+            Ok(vec![board.new_move_from_pure("g3g6")])
+        );
+        assert_eq!(get_moves(&board, &ops, "am"), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_parse_operations() {
+        let epd = format!(
+            "{} am Qxf6 O-O; c0 \"Comment; with a semicolon\"; hmvc 3; fmvn 12; noop",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -"
+        );
+        let (board, ops) = parse(&epd).unwrap();
+        assert_eq!(board.get_half_move_clock(), 3);
+        assert_eq!(board.to_fen(), fen::KIWIPETE.replace("0 1", "3 12"));
+        assert_eq!(ops["c0"], "Comment; with a semicolon");
+        assert_eq!(ops["noop"], "");
+        assert_eq!(
+            get_moves(&board, &ops, "am"),
+            Ok(vec![
+                board.new_move_from_pure("f3f6"),
+                board.new_move_from_pure("e1g1")
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse(""), Err(EpdError::MissingFields));
+        assert_eq!(parse("8/8/8/8/8/8/8/8 w -"), Err(EpdError::MissingFields));
+        assert_eq!(
+            parse("4k3/8/8/8/8/8/8/4K3 x - - id \"x\";"),
+            Err(EpdError::InvalidFen(FenError::InvalidSideToMove))
+        );
+        assert_eq!(
+            parse("4k3/8/8/8/8/8/8/4K3 w - - 1bm Ke2;"),
+            Err(EpdError::InvalidOperation("1bm Ke2".to_string()))
+        );
+        let (board, ops) = parse("4k3/8/8/8/8/8/8/4K3 w - - bm Kf3;").unwrap();
+        assert_eq!(
+            get_moves(&board, &ops, "bm"),
+            Err(EpdError::InvalidMove("Kf3".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_run_suite() {
+        let suite = r#"# Easy test positions
+k7/8/1K6/8/8/8/8/7R w - - bm Rh8#; id "mate";
+
+4k3/8/8/3q4/8/8/8/3RK3 w - - bm Rxd5; id "queen";
+4k3/8/8/3q4/8/8/8/3RK3 w - - am Rxd5; id "avoid";
+"#;
+        let results = run_suite(suite.as_bytes(), 2).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].id, "mate");
+        assert!(results[0].passed);
+        assert!(results[1].passed);
+        assert_eq!(results[2].id, "avoid");
+        assert!(!results[2].passed);
+        assert_eq!(results[1].best_move, results[2].best_move);
+
+        assert_eq!(
+            run_suite("\n4k3/8/8/8/8/8/8/4K3 w - - bm Kf3;".as_bytes(), 1),
+            Err(EpdError::InvalidLine(
+                2,
+                Box::new(EpdError::InvalidMove("Kf3".to_string()))
+            ))
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Static evaluation of a position.
+//! <https://www.chessprogramming.org/Evaluation>
+
+mod king_safety;
+mod mobility;
+mod pawns;
+mod pst;
+mod see;
+
+use crate::{
+    bitboard,
+    board::Board,
+    common::{Color, Piece, Square},
+};
+
+pub use king_safety::eval_king_safety;
+pub use mobility::eval_mobility;
+pub use pawns::eval_pawn_structure;
+pub use see::see;
+
+// Simple end game detection: No more queens.
+// Used to enable the mop-up evaluation.
+fn is_end_game(board: &Board) -> bool {
+    (board.material_count(Color::White) + board.material_count(Color::Black)).queens == 0
+}
+
+// Material advantage needed to start pushing the opponent king, about a rook.
+const MOP_UP_MIN_ADVANTAGE: i32 = 500;
+
+// Returns a positive value if white is better, negative if black is.
+fn for_white(color: Color, value: i32) -> i32 {
+    if color.is_white() {
+        value
+    } else {
+        -value
+    }
+}
+
+fn get_king_square(board: &Board, color: Color) -> Option<Square> {
+    let king_bb = board.pieces_of(Piece::get_king_of(color));
+    (king_bb != 0).then(|| bitboard::get_square(king_bb))
+}
+
+// Mop-up evaluation: When winning in the end game, the losing king should be pushed
+// to the edge and our king should get closer to it, so that it can be mated.
+// <https://www.chessprogramming.org/Mop-up_Evaluation>
+fn mop_up(board: &Board, winning: Color) -> i32 {
+    let (Some(winning_king), Some(losing_king)) = (
+        get_king_square(board, winning),
+        get_king_square(board, winning.opposite()),
+    ) else {
+        return 0;
+    };
+    let center_distance = [Square::D4, Square::E4, Square::D5, Square::E5]
+        .iter()
+        .map(|&center| losing_king.manhattan_distance(center))
+        .min()
+        .unwrap();
+    let kings_distance = winning_king.chebyshev_distance(losing_king);
+    10 * i32::from(center_distance) + 4 * (7 - i32::from(kings_distance))
+}
+
+// Tapered evaluation: Interpolates between the middle game and end game scores based on the game phase.
+// <https://www.chessprogramming.org/Tapered_Eval>
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn taper(middle_game: i32, end_game: i32, phase: f32) -> i32 {
+    (middle_game as f32 * phase + end_game as f32 * (1.0 - phase)).round() as i32
+}
+
+// Returns the score of the position in centipawns, from the point of view of the side to move.
+#[allow(clippy::cast_possible_wrap)]
+pub fn evaluate(board: &Board) -> i32 {
+    let (material, middle_game, end_game) =
+        Piece::ALL_PIECES
+            .iter()
+            .fold((0, 0, 0), |(m, mg, eg), &piece| {
+                let pieces_bb = board.pieces_of(piece);
+                let count = bitboard::count_ones(pieces_bb) as i32;
+                let material = piece.piece_type().material_value() * count;
+                let (middle, end) = bitboard::iter_squares(pieces_bb)
+                    .map(|square| pst::get_values(piece, square))
+                    .fold((0, 0), |(a, b), (v_mg, v_eg)| (a + v_mg, b + v_eg));
+                let color = piece.get_color();
+                (
+                    m + for_white(color, material),
+                    mg + for_white(color, middle),
+                    eg + for_white(color, end),
+                )
+            });
+
+    let phase = board.game_phase();
+    // King safety only matters while there are enough pieces left to attack.
+    let king_safety = for_white(Color::White, eval_king_safety(board, Color::White))
+        + for_white(Color::Black, eval_king_safety(board, Color::Black));
+    let mut score = material
+        + taper(middle_game, end_game, phase)
+        + taper(king_safety, 0, phase)
+        + eval_pawn_structure(board)
+        + eval_mobility(board);
+    if is_end_game(board) && material.abs() >= MOP_UP_MIN_ADVANTAGE {
+        let winning = if material > 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        score += for_white(winning, mop_up(board, winning));
+    }
+
+    if board.get_side_to_move().is_white() {
+        score
+    } else {
+        -score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_initial_position() {
+        assert_eq!(evaluate(&Board::initial_board()), 0);
+    }
+
+    #[test]
+    fn test_evaluate_material() {
+        // White has an extra queen (on d1, -5).
+        let board: Board = "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".into();
+        assert_eq!(evaluate(&board), 895);
+        let board: Board = "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1".into();
+        assert_eq!(evaluate(&board), -895);
+
+        // Black is a knight and a pawn up, white has an isolated pawn, and the knight is mobile.
+        let board: Board = "4k3/pp6/2n5/8/8/8/P7/4K3 w - - 0 1".into();
+        assert!((-510..-450).contains(&evaluate(&board)));
+    }
+
+    #[test]
+    fn test_evaluate_symmetry() {
+        // The same position with the colors swapped must have the same score for the side to move.
+        for fen in [
+            crate::fen::START_POSITION,
+            crate::fen::KIWIPETE,
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "k7/8/2K5/8/8/8/8/7R b - - 0 1",
+            "4k3/pp6/2n5/8/8/8/P7/4K3 w - - 0 1",
+        ] {
+            let board: Board = fen.into();
+            assert_eq!(evaluate(&board), evaluate(&board.flip_vertical()), "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_evaluate_symmetry_after_moves() {
+        // Checks many positions, to catch asymmetries like a table entry for one color only.
+        for fen in [crate::fen::KIWIPETE, crate::fen::POSITION_4] {
+            let board: Board = fen.into();
+            for mv in board.generate_legal_moves() {
+                let child = board.copy_with_move(mv).unwrap();
+                for reply in child.generate_legal_moves() {
+                    let position = child.copy_with_move(reply).unwrap();
+                    assert_eq!(
+                        evaluate(&position),
+                        evaluate(&position.flip_vertical()),
+                        "{}",
+                        position.to_fen()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_taper() {
+        assert_eq!(taper(30, -30, 1.0), 30);
+        assert_eq!(taper(30, -30, 0.0), -30);
+        assert_eq!(taper(30, -30, 0.5), 0);
+        assert_eq!(taper(20, 40, 0.25), 35);
+    }
+
+    #[test]
+    fn test_mop_up() {
+        // King pushed to the corner is better for the side with the rook.
+        let corner: Board = "k7/8/2K5/8/8/8/8/7R w - - 0 1".into();
+        let center: Board = "8/8/2K5/8/4k3/8/8/7R w - - 0 1".into();
+        assert!(evaluate(&corner) > evaluate(&center));
+        assert_eq!(mop_up(&corner, Color::White), 10 * 6 + 4 * 5);
+
+        // Kings closer together is better for the winning side.
+        let close: Board = "k7/8/1K6/8/8/8/8/7R b - - 0 1".into();
+        let far: Board = "k7/8/8/8/8/8/8/6KR b - - 0 1".into();
+        assert!(evaluate(&close) < evaluate(&far));
+    }
+
+    #[test]
+    fn test_evaluate_position() {
+        // After 1. e4, white pawn went from -20 to +20,
+        // and the bishop, queen and king knight can move to 5, 4 and 1 more squares.
+        let board: Board = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".into();
+        assert_eq!(evaluate(&board), -40 - (5 * 4 + 4 + 4));
+
+        // Centralized knight is better than on the rim.
+        let center: Board = "4k3/8/8/8/3N4/8/8/4K3 w - - 0 1".into();
+        let rim: Board = "4k3/8/8/8/N7/8/8/4K3 w - - 0 1".into();
+        assert!(evaluate(&center) > evaluate(&rim));
+    }
+}
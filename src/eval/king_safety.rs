@@ -0,0 +1,128 @@
+//! King safety: Penalties for opponent pieces attacking the squares around the king,
+//! and for missing pawns in front of it.
+//! <https://www.chessprogramming.org/King_Safety>
+
+use crate::{
+    bitboard::{self, movements, BitBoard},
+    board::Board,
+    common::{Color, Piece, PieceType},
+};
+
+// Penalty per opponent piece attacking the king zone.
+const fn attack_weight(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn => 10,
+        PieceType::Knight | PieceType::Bishop => 25,
+        PieceType::Rook => 40,
+        PieceType::Queen => 50,
+        PieceType::King => 0,
+    }
+}
+
+// Penalty per file around the king without any of our pawns.
+const OPEN_FILE_PENALTY: i32 = 15;
+// Penalty per opponent rook or queen on one of these open files.
+const ROOK_ON_OPEN_FILE_PENALTY: i32 = 50;
+
+// The king square and the squares around it.
+fn king_zone(king_bb: BitBoard) -> BitBoard {
+    king_bb | movements::get_king_attacks(king_bb)
+}
+
+// Files of the king and next to it without any pawn of that color.
+fn open_files_near_king(board: &Board, king_file: u8, color: Color) -> BitBoard {
+    let pawns = board.pieces_of(Piece::get_pawn_of(color));
+    (king_file.saturating_sub(1)..=(king_file + 1).min(7))
+        .map(bitboard::file_mask)
+        .filter(|&file| file & pawns == 0)
+        .fold(0, |open_files, file| open_files | file)
+}
+
+// Penalty for the king of that color, negative or zero.
+pub fn eval_king_safety(board: &Board, color: Color) -> i32 {
+    let king_bb = board.pieces_of(Piece::get_king_of(color));
+    if bitboard::is_empty(king_bb) {
+        return 0;
+    }
+    let opponent = color.opposite();
+    let zone = king_zone(king_bb);
+    let mut penalty = 0;
+
+    // Quick check before looking at each piece.
+    if board.attacked_squares(opponent) & zone != 0 {
+        for piece in Piece::pieces_of_color(opponent) {
+            let attackers = bitboard::iter_squares(board.pieces_of(piece))
+                .filter(|&sq| board.get_attacks_for_piece(sq, piece) & zone != 0)
+                .count();
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            let attackers = attackers as i32;
+            penalty += attack_weight(piece.piece_type()) * attackers;
+        }
+    }
+
+    let king_file = bitboard::get_square(king_bb).get_file();
+    let open_files = open_files_near_king(board, king_file, color);
+    let heavy_pieces = board.pieces_of(Piece::get_rook_of(opponent))
+        | board.pieces_of(Piece::get_queen_of(opponent));
+    #[allow(clippy::cast_possible_wrap)]
+    {
+        // 8 squares per file.
+        penalty += OPEN_FILE_PENALTY * (bitboard::count_ones(open_files) / 8) as i32;
+        penalty +=
+            ROOK_ON_OPEN_FILE_PENALTY * bitboard::count_ones(open_files & heavy_pieces) as i32;
+    }
+    -penalty
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initial_position() {
+        let board = Board::initial_board();
+        assert_eq!(eval_king_safety(&board, Color::White), 0);
+        assert_eq!(eval_king_safety(&board, Color::Black), 0);
+    }
+
+    #[test]
+    fn test_attacks_on_king_zone() {
+        // Castled king with its pawns, attacked by a knight on g4 and a bishop on b7.
+        let board: Board = "4k3/1b6/8/8/6n1/8/5PPP/6K1 w - - 0 1".into();
+        assert_eq!(eval_king_safety(&board, Color::White), -25 - 25);
+
+        // Pawn on g3 attacks f2 and h2.
+        let board: Board = "4k3/8/8/8/8/6p1/5PPP/6K1 w - - 0 1".into();
+        assert_eq!(eval_king_safety(&board, Color::White), -10);
+    }
+
+    #[test]
+    fn test_open_files() {
+        // No g-pawn.
+        let board: Board = "4k3/8/8/8/8/8/5P1P/6K1 w - - 0 1".into();
+        assert_eq!(eval_king_safety(&board, Color::White), -OPEN_FILE_PENALTY);
+
+        // King in the corner, only 2 files to look at.
+        let board: Board = "4k3/8/8/8/8/8/8/K7 w - - 0 1".into();
+        assert_eq!(
+            eval_king_safety(&board, Color::White),
+            -2 * OPEN_FILE_PENALTY
+        );
+
+        // Rook on the open file, which also attacks the king zone.
+        let board: Board = "4k1r1/8/8/8/8/8/5P1P/6K1 w - - 0 1".into();
+        assert_eq!(
+            eval_king_safety(&board, Color::White),
+            -40 - OPEN_FILE_PENALTY - ROOK_ON_OPEN_FILE_PENALTY
+        );
+    }
+
+    #[test]
+    fn test_black_king() {
+        let board: Board = "6k1/5p1p/8/8/8/8/8/4K1R1 w - - 0 1".into();
+        assert_eq!(
+            eval_king_safety(&board, Color::Black),
+            -40 - OPEN_FILE_PENALTY - ROOK_ON_OPEN_FILE_PENALTY
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Mobility: The number of squares the pieces can move to.
+//! <https://www.chessprogramming.org/Mobility>
+
+use crate::{
+    bitboard,
+    board::Board,
+    common::{Color, Piece, PieceType},
+};
+
+use super::for_white;
+
+// Centipawns per available square. Minor pieces benefit the most from mobility,
+// while rooks and queens have many squares anyway and the king is better kept safe.
+const fn weight(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Knight | PieceType::Bishop => 4,
+        PieceType::Rook => 2,
+        PieceType::Queen => 1,
+        PieceType::Pawn | PieceType::King => 0,
+    }
+}
+
+fn mobility_of(board: &Board, color: Color) -> i32 {
+    let own = board.all_pieces_of_color(color);
+    // Pinned pieces can barely move, so they don't get any mobility bonus.
+    let pinned = board.pinned_pieces(color);
+    Piece::pieces_of_color(color)
+        .iter()
+        .map(|&piece| {
+            let piece_type = piece.piece_type();
+            if weight(piece_type) == 0 {
+                return 0;
+            }
+            // Squares that piece can move to, ignoring checks.
+            let squares: u32 = bitboard::iter_squares(board.pieces_of(piece) & !pinned)
+                .map(|sq| bitboard::count_ones(board.get_attacks_for_piece(sq, piece) & !own))
+                .sum();
+            #[allow(clippy::cast_possible_wrap)]
+            let squares = squares as i32;
+            weight(piece_type) * squares
+        })
+        .sum()
+}
+
+// Returns a positive value if white pieces are more mobile, negative if black ones are.
+pub fn eval_mobility(board: &Board) -> i32 {
+    for_white(Color::White, mobility_of(board, Color::White))
+        + for_white(Color::Black, mobility_of(board, Color::Black))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initial_position() {
+        assert_eq!(eval_mobility(&Board::initial_board()), 0);
+        // Only the knights can move, 2 squares each.
+        assert_eq!(mobility_of(&Board::initial_board(), Color::White), 4 * 4);
+    }
+
+    #[test]
+    fn test_bishop_mobility() {
+        // Bishop in the center has 13 squares, in the corner blocked by its own pawn it has none.
+        let center: Board = "4k3/8/8/8/3B4/8/8/4K3 w - - 0 1".into();
+        let blocked: Board = "4k3/8/8/8/8/8/1P6/B3K3 w - - 0 1".into();
+        assert_eq!(mobility_of(&center, Color::White), 4 * 13);
+        assert_eq!(mobility_of(&blocked, Color::White), 0);
+        assert!(eval_mobility(&center) > eval_mobility(&blocked));
+    }
+
+    #[test]
+    fn test_black_mobility() {
+        let board: Board = "4k3/8/8/3n4/8/8/8/4K3 w - - 0 1".into();
+        assert_eq!(eval_mobility(&board), -4 * 8);
+    }
+
+    #[test]
+    fn test_pinned_pieces() {
+        // The knight on e2 is pinned by the rook, it doesn't count.
+        let pinned: Board = "4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1".into();
+        let free: Board = "3r2k1/8/8/8/8/8/4N3/4K3 w - - 0 1".into();
+        assert_eq!(mobility_of(&pinned, Color::White), 0);
+        assert_eq!(mobility_of(&free, Color::White), 4 * 6);
+    }
+}
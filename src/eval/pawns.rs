@@ -0,0 +1,162 @@
+//! Pawn structure evaluation.
+//! <https://www.chessprogramming.org/Pawn_Structure>
+
+use crate::{
+    bitboard::{self, BitBoard},
+    board::Board,
+    common::{Color, Piece, Square},
+};
+
+use super::for_white;
+
+// Bonus for a passed pawn, indexed by the rank relative to its color.
+const PASSED_PAWN_BONUS: [i32; 8] = [0, 10, 15, 25, 40, 60, 90, 0];
+const DOUBLED_PAWN_PENALTY: i32 = 15;
+const ISOLATED_PAWN_PENALTY: i32 = 15;
+
+// Files next to that file.
+fn adjacent_files_mask(file: u8) -> BitBoard {
+    let mask = bitboard::file_mask(file);
+    ((mask << 1) & !bitboard::FILE_A) | ((mask >> 1) & !bitboard::FILE_H)
+}
+
+// All the ranks in front of the square, from the point of view of that color.
+fn ranks_ahead_mask(square: Square, color: Color) -> BitBoard {
+    let rank = square.get_rank();
+    if color.is_white() {
+        !(bitboard::UNIVERSAL >> ((7 - rank) * 8))
+    } else {
+        !(bitboard::UNIVERSAL << (rank * 8))
+    }
+}
+
+// No opposing pawns in front of it, on the same file or the adjacent ones.
+// <https://www.chessprogramming.org/Passed_Pawn>
+fn is_passed(square: Square, color: Color, opponent_pawns: BitBoard) -> bool {
+    let file = square.get_file();
+    let front_span =
+        (bitboard::file_mask(file) | adjacent_files_mask(file)) & ranks_ahead_mask(square, color);
+    front_span & opponent_pawns == 0
+}
+
+// Score of the pawn structure of that color, positive if good.
+fn eval_pawns_of(board: &Board, color: Color) -> i32 {
+    let pawns = board.pieces_of(Piece::get_pawn_of(color));
+    let opponent_pawns = board.pieces_of(Piece::get_pawn_of(color.opposite()));
+    let mut score = 0;
+
+    for square in bitboard::iter_squares(pawns) {
+        let rank = square.get_rank();
+        let relative_rank = if color.is_white() { rank } else { 7 - rank };
+        if is_passed(square, color, opponent_pawns) {
+            score += PASSED_PAWN_BONUS[relative_rank as usize];
+        }
+        // <https://www.chessprogramming.org/Isolated_Pawn>
+        if pawns & adjacent_files_mask(square.get_file()) == 0 {
+            score -= ISOLATED_PAWN_PENALTY;
+        }
+    }
+
+    // <https://www.chessprogramming.org/Doubled_Pawn>
+    for file in 0..8 {
+        let count = bitboard::count_ones(pawns & bitboard::file_mask(file));
+        if count > 1 {
+            #[allow(clippy::cast_possible_wrap)]
+            let extra = (count - 1) as i32;
+            score -= DOUBLED_PAWN_PENALTY * extra;
+        }
+    }
+    score
+}
+
+// Returns a positive value if white has the better pawn structure, negative if black has.
+pub fn eval_pawn_structure(board: &Board) -> i32 {
+    for_white(Color::White, eval_pawns_of(board, Color::White))
+        + for_white(Color::Black, eval_pawns_of(board, Color::Black))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranks_ahead_mask() {
+        assert_eq!(
+            ranks_ahead_mask(Square::E2, Color::White),
+            bitboard::UNIVERSAL & !bitboard::RANK_1 & !bitboard::RANK_2
+        );
+        assert_eq!(ranks_ahead_mask(Square::E8, Color::White), 0);
+        assert_eq!(
+            ranks_ahead_mask(Square::E7, Color::Black),
+            bitboard::UNIVERSAL & !bitboard::RANK_7 & !bitboard::RANK_8
+        );
+        assert_eq!(ranks_ahead_mask(Square::E1, Color::Black), 0);
+    }
+
+    #[test]
+    fn test_adjacent_files_mask() {
+        assert_eq!(adjacent_files_mask(0), bitboard::FILE_B);
+        assert_eq!(adjacent_files_mask(4), bitboard::FILE_D | bitboard::FILE_F);
+        assert_eq!(adjacent_files_mask(7), bitboard::FILE_G);
+    }
+
+    #[test]
+    fn test_initial_position() {
+        assert_eq!(eval_pawn_structure(&Board::initial_board()), 0);
+    }
+
+    #[test]
+    fn test_passed_pawns() {
+        // The d5 pawn is passed, the black f7 pawn is blocked by the white pawns on e and g files.
+        let board: Board = "4k3/5p2/8/3P4/8/8/4P1P1/4K3 w - - 0 1".into();
+        assert!(is_passed(
+            Square::D5,
+            Color::White,
+            board.pieces_of(Piece::BlackPawn)
+        ));
+        assert!(!is_passed(
+            Square::E2,
+            Color::White,
+            board.pieces_of(Piece::BlackPawn)
+        ));
+        assert!(!is_passed(
+            Square::F7,
+            Color::Black,
+            board.pieces_of(Piece::WhitePawn)
+        ));
+
+        // Bonus increases with the rank.
+        let rank_4: Board = "4k3/8/8/8/P7/8/8/4K3 w - - 0 1".into();
+        let rank_6: Board = "4k3/8/P7/8/8/8/8/4K3 w - - 0 1".into();
+        assert_eq!(
+            eval_pawn_structure(&rank_4),
+            PASSED_PAWN_BONUS[3] - ISOLATED_PAWN_PENALTY
+        );
+        assert!(eval_pawn_structure(&rank_6) > eval_pawn_structure(&rank_4));
+
+        // Same for black.
+        let board: Board = "4k3/8/8/8/8/p7/8/4K3 w - - 0 1".into();
+        assert_eq!(
+            eval_pawn_structure(&board),
+            -(PASSED_PAWN_BONUS[5] - ISOLATED_PAWN_PENALTY)
+        );
+    }
+
+    #[test]
+    fn test_doubled_pawns() {
+        // Pawns on both sides so that none of them are passed or isolated.
+        let single: Board = "4k3/1ppp4/8/8/8/8/1PPP4/4K3 w - - 0 1".into();
+        assert_eq!(eval_pawn_structure(&single), 0);
+        let doubled: Board = "4k3/1ppp4/8/8/8/2P5/1PPP4/4K3 w - - 0 1".into();
+        assert_eq!(eval_pawn_structure(&doubled), -DOUBLED_PAWN_PENALTY);
+        let tripled: Board = "4k3/1ppp4/8/8/2P5/2P5/1PPP4/4K3 w - - 0 1".into();
+        assert_eq!(eval_pawn_structure(&tripled), -2 * DOUBLED_PAWN_PENALTY);
+    }
+
+    #[test]
+    fn test_isolated_pawns() {
+        // The a-pawns are isolated for both sides, but black also has an isolated h-pawn.
+        let board: Board = "4k3/p2pp2p/8/8/8/8/P2PP1PP/4K3 w - - 0 1".into();
+        assert_eq!(eval_pawn_structure(&board), ISOLATED_PAWN_PENALTY);
+    }
+}
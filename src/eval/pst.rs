@@ -0,0 +1,156 @@
+//! Piece-Square Tables.
+//! Values from <https://www.chessprogramming.org/Simplified_Evaluation_Function>
+
+use crate::common::{Piece, PieceType, Square};
+
+// The tables are written as seen from White, with A8 in the top left corner.
+// So for White we need to flip the rank to get the index, for Black the square index works directly.
+
+#[rustfmt::skip]
+const PAWN: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const KING_MIDDLE_GAME: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+#[rustfmt::skip]
+const KING_END_GAME: [i32; 64] = [
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+fn table_index(piece: Piece, square: Square) -> usize {
+    if piece.get_color().is_white() {
+        square as usize ^ 0x38
+    } else {
+        square as usize
+    }
+}
+
+// Middle game and end game values of the piece on that square, positive for both colors.
+pub fn get_values(piece: Piece, square: Square) -> (i32, i32) {
+    let index = table_index(piece, square);
+    let value = match piece.piece_type() {
+        PieceType::Pawn => PAWN[index],
+        PieceType::Knight => KNIGHT[index],
+        PieceType::Bishop => BISHOP[index],
+        PieceType::Rook => ROOK[index],
+        PieceType::Queen => QUEEN[index],
+        PieceType::King => return (KING_MIDDLE_GAME[index], KING_END_GAME[index]),
+    };
+    (value, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{Piece::*, Square::*};
+
+    use super::*;
+
+    #[test]
+    fn test_get_values() {
+        assert_eq!(get_values(WhitePawn, E2), (-20, -20));
+        assert_eq!(get_values(WhitePawn, E4), (20, 20));
+        assert_eq!(get_values(BlackPawn, E7), (-20, -20));
+        assert_eq!(get_values(BlackPawn, E5), (20, 20));
+
+        assert_eq!(get_values(WhiteKnight, A1), (-50, -50));
+        assert_eq!(get_values(BlackKnight, D5), (20, 20));
+
+        assert_eq!(get_values(WhiteKing, G1), (30, -30));
+        assert_eq!(get_values(BlackKing, G8), (30, -30));
+    }
+
+    #[test]
+    fn test_symmetry() {
+        for piece in [
+            WhitePawn,
+            WhiteKnight,
+            WhiteBishop,
+            WhiteRook,
+            WhiteQueen,
+            WhiteKing,
+        ] {
+            let black_piece = Piece::ALL_PIECES[piece as usize + 1];
+            for sq in 0..64u8 {
+                let white_square: Square = sq.into();
+                let black_square: Square = (sq ^ 0x38).into();
+                assert_eq!(
+                    get_values(piece, white_square),
+                    get_values(black_piece, black_square)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Static Exchange Evaluation: The material gained by a sequence of captures on a square.
+//! <https://www.chessprogramming.org/Static_Exchange_Evaluation>
+//! <https://www.chessprogramming.org/SEE_-_The_Swap_Algorithm>
+
+use crate::{
+    bitboard,
+    board::Board,
+    common::{Piece, PieceType},
+    moves::Move,
+};
+
+// Kings have no material value, but for SEE capturing them must be worth more than anything.
+const KING_VALUE: i32 = 20_000;
+
+fn value(piece: Piece) -> i32 {
+    if piece.is_king() {
+        KING_VALUE
+    } else {
+        piece.piece_type().material_value()
+    }
+}
+
+// Expected material gain of the move, assuming both sides capture on the target square
+// with their least valuable piece, and stop when continuing would lose material.
+pub fn see(board: &Board, mv: Move) -> i32 {
+    let target = mv.get_to();
+    let mut occupied = board.get_occupied() ^ bitboard::from_square(mv.get_from());
+    let victim_value = if board.is_en_passant_capture(mv) {
+        // The captured pawn isn't on the target square.
+        let captured_sq = if mv.get_piece().get_color().is_white() {
+            target as u8 - 8
+        } else {
+            target as u8 + 8
+        };
+        occupied &= !(1 << captured_sq);
+        PieceType::Pawn.material_value()
+    } else {
+        mv.get_captured_piece().map_or(0, value)
+    };
+
+    // gains[d] is the material won by the side making the d-th capture, if the sequence stopped there.
+    let mut gains = vec![victim_value];
+    let mut on_target_value = value(mv.get_piece());
+    let mut color = mv.get_piece().get_color().opposite();
+    while let Some((square, piece)) =
+        board.smallest_attacker_with_occupancy(target, color, occupied)
+    {
+        gains.push(on_target_value - gains.last().unwrap());
+        on_target_value = value(piece);
+        occupied ^= bitboard::from_square(square);
+        color = color.opposite();
+    }
+
+    // Each side can decide not to continue the exchange.
+    while gains.len() > 1 {
+        let last = gains.pop().unwrap();
+        let previous = gains.last_mut().unwrap();
+        *previous = -(-*previous).max(last);
+    }
+    gains[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn see_for(fen: &str, mv: &str) -> i32 {
+        let board: Board = fen.into();
+        see(&board, board.new_move_from_pure(mv))
+    }
+
+    #[test]
+    fn test_see_undefended() {
+        assert_eq!(see_for("4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1", "d1d5"), 100);
+        assert_eq!(see_for("4k3/8/8/3q4/4P3/8/8/4K3 w - - 0 1", "e4d5"), 900);
+    }
+
+    #[test]
+    fn test_see_defended() {
+        // Queen takes a pawn defended by a pawn.
+        assert_eq!(see_for("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", "d1d5"), -800);
+        // Pawn takes a defended queen, still winning.
+        assert_eq!(see_for("4k3/8/4p3/3q4/4P3/8/8/4K3 w - - 0 1", "e4d5"), 800);
+    }
+
+    #[test]
+    fn test_see_x_ray() {
+        // Rook takes the pawn, defended by a rook, but the second white rook is behind the first one.
+        assert_eq!(see_for("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), 100);
+        // Without the second rook, it's losing.
+        assert_eq!(see_for("3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1", "d2d5"), -400);
+    }
+
+    #[test]
+    fn test_see_king_cannot_capture_defended() {
+        // The king can take back only if the square isn't defended.
+        assert_eq!(see_for("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1", "e1d2"), 100);
+        assert_eq!(
+            see_for("4k3/8/8/8/8/4p3/3p4/4K3 w - - 0 1", "e1d2"),
+            -KING_VALUE + 100
+        );
+    }
+
+    #[test]
+    fn test_see_en_passant() {
+        assert_eq!(see_for("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+    }
+}
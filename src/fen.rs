@@ -1,7 +1,8 @@
 //! Parsing and creation of FEN strings.
-//! Only valid FEN strings are supported. Invalid will cause the code to assert.
 //! Doc: <https://www.chessprogramming.org/Forsyth-Edwards_Notation>
 
+use std::fmt;
+
 use itertools::Itertools;
 
 use crate::common::Color;
@@ -25,6 +26,38 @@ pub const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w K
 pub const POSITION_6: &str =
     "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
 
+// For Chess960 castling rights, the color and file of the rook.
+pub type RookFile = (Color, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenError {
+    // A FEN string must have 6 fields.
+    WrongFieldCount,
+    InvalidPiecePlacement,
+    InvalidSideToMove,
+    InvalidCastlingRights,
+    InvalidEnPassantSquare,
+    InvalidHalfMoveClock,
+    InvalidFullMoveCounter,
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            FenError::WrongFieldCount => "Wrong number of fields",
+            FenError::InvalidPiecePlacement => "Invalid piece placement",
+            FenError::InvalidSideToMove => "Invalid side to move",
+            FenError::InvalidCastlingRights => "Invalid castling rights",
+            FenError::InvalidEnPassantSquare => "Invalid en passant square",
+            FenError::InvalidHalfMoveClock => "Invalid half move clock",
+            FenError::InvalidFullMoveCounter => "Invalid full move counter",
+        };
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for FenError {}
+
 fn create_rank(rank: &[Option<Piece>]) -> String {
     assert_eq!(rank.len(), 8);
     let mut result = String::with_capacity(8);
@@ -58,25 +91,43 @@ fn get_side_to_move(side_to_move: Color) -> &'static str {
     }
 }
 
-fn get_castling_ability(castling_ability: &[Piece]) -> String {
-    if castling_ability.is_empty() {
+fn get_castling_ability(castling_ability: &[Piece], castling_rook_files: &[RookFile]) -> String {
+    if castling_ability.is_empty() && castling_rook_files.is_empty() {
         return "-".to_string();
     }
 
-    assert!(castling_ability.len() <= 4);
-    assert!([
+    assert!(castling_ability.len() + castling_rook_files.len() <= 4);
+    assert!(castling_ability.iter().all(|piece| [
         Piece::WhiteKing,
         Piece::WhiteQueen,
         Piece::BlackKing,
         Piece::BlackQueen
     ]
-    .iter()
-    .all(|piece| castling_ability.contains(piece)));
-
-    castling_ability
-        .iter()
-        .map(|piece| Into::<char>::into(*piece))
-        .join("")
+    .contains(piece)));
+    assert!(castling_rook_files.iter().all(|&(_, file)| file < 8));
+
+    // White rights first, like "KQkq" or "HAha".
+    [Color::White, Color::Black]
+        .into_iter()
+        .flat_map(|color| {
+            let pieces = castling_ability
+                .iter()
+                .filter(move |piece| piece.get_color() == color)
+                .map(|piece| Into::<char>::into(*piece));
+            let files = castling_rook_files
+                .iter()
+                .filter(move |(c, _)| *c == color)
+                .map(move |&(_, file)| {
+                    let c = (b'a' + file) as char;
+                    if color.is_white() {
+                        c.to_ascii_uppercase()
+                    } else {
+                        c
+                    }
+                });
+            pieces.chain(files)
+        })
+        .collect()
 }
 
 fn get_en_passant_target_square(square: Option<Square>) -> String {
@@ -100,7 +151,8 @@ fn get_full_move_counter(full_move_counter: usize) -> String {
 pub fn create(
     piece_placement: &[Option<Piece>],
     side_to_move: Color,
-    castling_ability: &[Piece], // max 4, only king or queen
+    castling_ability: &[Piece],       // max 4, only king or queen
+    castling_rook_files: &[RookFile], // Shredder-FEN, for Chess960 only
     en_passant_target_square: Option<Square>,
     half_move_clock: usize,
     full_move_counter: usize,
@@ -109,98 +161,161 @@ pub fn create(
         "{} {} {} {} {} {}",
         get_piece_placement(piece_placement),
         get_side_to_move(side_to_move),
-        get_castling_ability(castling_ability),
+        get_castling_ability(castling_ability, castling_rook_files),
         get_en_passant_target_square(en_passant_target_square),
         get_half_move_clock(half_move_clock),
         get_full_move_counter(full_move_counter),
     )
 }
 
-fn parse_piece_placement(s: &str) -> PieceListBoard {
-    let pieces = s
-        .split('/')
-        .flat_map(|rank| {
-            rank.chars().flat_map(|c| {
-                if let Some(d) = c.to_digit(10) {
-                    assert!((1..=8).contains(&d));
-                    vec![None; d as usize]
-                } else {
-                    vec![c.try_into().ok()]
+// Creates a FEN string without castling rights, en passant square, or history: The clocks are "0 1".
+pub fn create_minimal(piece_placement: &[Option<Piece>], side_to_move: Color) -> String {
+    create_with_defaults(piece_placement, side_to_move, &[], None)
+}
+
+// Creates a FEN string for a position without history, when the clocks are unknown or don't matter.
+pub fn create_with_defaults(
+    piece_placement: &[Option<Piece>],
+    side_to_move: Color,
+    castling_ability: &[Piece],
+    en_passant_target_square: Option<Square>,
+) -> String {
+    create(
+        piece_placement,
+        side_to_move,
+        castling_ability,
+        &[],
+        en_passant_target_square,
+        0,
+        1,
+    )
+}
+
+fn parse_piece_placement(s: &str) -> Result<PieceListBoard, FenError> {
+    let ranks = s.split('/').collect_vec();
+    if ranks.len() != 8 {
+        return Err(FenError::InvalidPiecePlacement);
+    }
+    let mut pieces = Vec::with_capacity(64);
+    for rank in ranks {
+        let rank_start = pieces.len();
+        for c in rank.chars() {
+            if let Some(d) = c.to_digit(10) {
+                if !(1..=8).contains(&d) {
+                    return Err(FenError::InvalidPiecePlacement);
                 }
-            })
-        })
-        .collect_vec();
-    assert_eq!(pieces.len(), 64);
-    pieces
+                pieces.extend(std::iter::repeat_n(None, d as usize));
+            } else {
+                let piece = c.try_into().map_err(|_| FenError::InvalidPiecePlacement)?;
+                pieces.push(Some(piece));
+            }
+        }
+        if pieces.len() - rank_start != 8 {
+            return Err(FenError::InvalidPiecePlacement);
+        }
+    }
+    Ok(pieces)
 }
 
-fn parse_side_to_move(s: &str) -> Color {
+fn parse_side_to_move(s: &str) -> Result<Color, FenError> {
     match s {
-        "w" => Color::White,
-        "b" => Color::Black,
-        _ => panic!("Invalid side to move"),
+        "w" => Ok(Color::White),
+        "b" => Ok(Color::Black),
+        _ => Err(FenError::InvalidSideToMove),
     }
 }
 
-fn parse_castling_ability(s: &str) -> Vec<Piece> {
+// Returns the rights written as KQkq, and the ones written as rook files (Shredder-FEN), like HAha.
+fn parse_castling_ability(s: &str) -> Result<(Vec<Piece>, Vec<RookFile>), FenError> {
     if s == "-" {
-        Vec::new()
-    } else {
-        s.chars().map(|c| c.try_into().unwrap()).collect()
+        return Ok((Vec::new(), Vec::new()));
+    }
+    if s.is_empty() || !s.chars().all_unique() {
+        return Err(FenError::InvalidCastlingRights);
     }
+    let mut castling = Vec::new();
+    let mut rook_files = Vec::new();
+    for c in s.chars() {
+        match c {
+            'K' | 'Q' | 'k' | 'q' => castling.push(c.try_into().unwrap()),
+            'A'..='H' => rook_files.push((Color::White, c as u8 - b'A')),
+            'a'..='h' => rook_files.push((Color::Black, c as u8 - b'a')),
+            _ => return Err(FenError::InvalidCastlingRights),
+        }
+    }
+    if castling.len() + rook_files.len() > 4 {
+        return Err(FenError::InvalidCastlingRights);
+    }
+    Ok((castling, rook_files))
 }
 
-fn parse_en_passant_target_square(s: &str) -> Option<Square> {
+fn parse_en_passant_target_square(s: &str) -> Result<Option<Square>, FenError> {
     if s == "-" {
-        None
+        return Ok(None);
+    }
+    let square: Square = s.try_into().map_err(|_| FenError::InvalidEnPassantSquare)?;
+    // The target square is behind the pawn that just moved two squares.
+    if [2, 5].contains(&square.get_rank()) {
+        Ok(Some(square))
     } else {
-        s.try_into().ok()
+        Err(FenError::InvalidEnPassantSquare)
     }
 }
 
-fn parse_half_move_clock(s: &str) -> usize {
-    s.parse().unwrap()
+// Values that don't fit are anyway over the fifty-move limit.
+fn parse_half_move_clock(s: &str) -> Result<u8, FenError> {
+    let clock: usize = s.parse().map_err(|_| FenError::InvalidHalfMoveClock)?;
+    Ok(u8::try_from(clock).unwrap_or(u8::MAX))
 }
 
-fn parse_full_move_counter(s: &str) -> usize {
-    s.parse().unwrap()
+fn parse_full_move_counter(s: &str) -> Result<u16, FenError> {
+    match s.parse::<usize>() {
+        Ok(counter) if counter > 0 => Ok(u16::try_from(counter).unwrap_or(u16::MAX)),
+        _ => Err(FenError::InvalidFullMoveCounter),
+    }
 }
 
-// Parses a FEN string.
-pub fn parse(
-    fen: &str,
-) -> (
-    PieceListBoard,
-    Color,
-    Vec<Piece>,
-    Option<Square>,
-    usize,
-    usize,
-) {
+// The six fields of a FEN string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FenFields {
+    pub piece_placement: PieceListBoard,
+    pub side_to_move: Color,
+    // Kings and queens, for king and queen side castling.
+    pub castling_ability: Vec<Piece>,
+    // For Chess960, castling rights can instead be given by the file of the rook.
+    pub castling_rook_files: Vec<RookFile>,
+    pub en_passant: Option<Square>,
+    pub half_move_clock: u8,
+    pub full_move_counter: u16,
+}
+
+// Parses a FEN string, returning an error if it is invalid.
+pub fn try_parse(fen: &str) -> Result<FenFields, FenError> {
     let parts = fen.split_ascii_whitespace().collect_vec();
-    assert_eq!(parts.len(), 6);
-    (
-        parse_piece_placement(parts[0]),
-        parse_side_to_move(parts[1]),
-        parse_castling_ability(parts[2]),
-        parse_en_passant_target_square(parts[3]),
-        parse_half_move_clock(parts[4]),
-        parse_full_move_counter(parts[5]),
-    )
+    if parts.len() != 6 {
+        return Err(FenError::WrongFieldCount);
+    }
+    let (castling_ability, castling_rook_files) = parse_castling_ability(parts[2])?;
+    Ok(FenFields {
+        piece_placement: parse_piece_placement(parts[0])?,
+        side_to_move: parse_side_to_move(parts[1])?,
+        castling_ability,
+        castling_rook_files,
+        en_passant: parse_en_passant_target_square(parts[3])?,
+        half_move_clock: parse_half_move_clock(parts[4])?,
+        full_move_counter: parse_full_move_counter(parts[5])?,
+    })
+}
+
+// Parses a FEN string.
+// Panics if the FEN string is invalid.
+pub fn parse(fen: &str) -> FenFields {
+    try_parse(fen).unwrap_or_else(|err| panic!("Invalid FEN \"{fen}\": {err}"))
 }
 
 // Parses only a list of pieces, populating the rest with sensible defaults.
 // For writing tests mainly.
-pub fn parse_pieces(
-    pieces: &str,
-) -> (
-    PieceListBoard,
-    Color,
-    Vec<Piece>,
-    Option<Square>,
-    usize,
-    usize,
-) {
+pub fn parse_pieces(pieces: &str) -> FenFields {
     parse(&format!("{pieces}  w KQkq - 0 1"))
 }
 
@@ -254,6 +369,7 @@ mod tests {
             &piece_placement,
             Color::White,
             &castling_ability,
+            &[],
             None,
             0,
             1,
@@ -271,6 +387,7 @@ mod tests {
             &piece_placement,
             Color::White,
             &castling_ability,
+            &[],
             Some(Square::C6),
             0,
             2,
@@ -281,64 +398,146 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_create_partial_castling() {
+        let piece_placement = Piece::build_list_board(
+            "r...k... ........ ........ ........ ........ ........ ........ ....K..R",
+        );
+        let fen = create(
+            &piece_placement,
+            Color::Black,
+            &[WhiteKing, BlackQueen],
+            &[],
+            None,
+            0,
+            1,
+        );
+        assert_eq!(fen, "r3k3/8/8/8/8/8/8/4K2R b Kq - 0 1");
+    }
+
+    #[test]
+    fn test_create_minimal() {
+        let piece_placement = Piece::build_list_board(
+            "....k... ........ ........ ........ ........ ........ ....P... ....K...",
+        );
+        assert_eq!(
+            create_minimal(&piece_placement, Color::White),
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"
+        );
+        assert_eq!(
+            create_with_defaults(&piece_placement, Color::Black, &[], None),
+            "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1"
+        );
+
+        let piece_placement = Piece::build_list_board(
+            "r...k... ........ ........ ........ ....P... ........ ........ ....K..R",
+        );
+        assert_eq!(
+            create_with_defaults(
+                &piece_placement,
+                Color::Black,
+                &[WhiteKing, BlackQueen],
+                Some(Square::E3)
+            ),
+            "r3k3/8/8/8/4P3/8/8/4K2R b Kq e3 0 1"
+        );
+    }
+
     #[test]
     fn test_parse_starting_position() {
-        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
-        let (pieces, side, castling, en_passant, half_move, full_move) = parse(fen);
+        let fields = parse(START_POSITION);
 
-        assert_eq!(pieces.len(), 64);
+        assert_eq!(fields.piece_placement.len(), 64);
         assert_eq!(
-            pieces,
+            fields.piece_placement,
             Piece::build_list_board(
                 "rnbqkbnr pppppppp ........ ........ ........ ........ PPPPPPPP RNBQKBNR",
             )
         );
-        assert_eq!(side, Color::White);
-        assert_eq!(castling.len(), 4);
-        assert!(castling.contains(&Piece::WhiteKing));
-        assert!(castling.contains(&Piece::WhiteQueen));
-        assert!(castling.contains(&Piece::BlackKing));
-        assert!(castling.contains(&Piece::BlackQueen));
-        assert_eq!(en_passant, None);
-        assert_eq!(half_move, 0);
-        assert_eq!(full_move, 1);
+        assert_eq!(fields.side_to_move, Color::White);
+        assert_eq!(fields.castling_ability.len(), 4);
+        assert!(fields.castling_ability.contains(&Piece::WhiteKing));
+        assert!(fields.castling_ability.contains(&Piece::WhiteQueen));
+        assert!(fields.castling_ability.contains(&Piece::BlackKing));
+        assert!(fields.castling_ability.contains(&Piece::BlackQueen));
+        assert_eq!(fields.en_passant, None);
+        assert_eq!(fields.half_move_clock, 0);
+        assert_eq!(fields.full_move_counter, 1);
     }
 
     #[test]
     fn test_parse_middle_game_position() {
         let fen = "r1bqkbnr/pppppppp/2n5/8/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq e3 0 3";
-        let (pieces, side, castling, en_passant, half_move, full_move) = parse(fen);
+        let fields = parse(fen);
 
-        assert_eq!(pieces.len(), 64);
+        assert_eq!(fields.piece_placement.len(), 64);
         assert_eq!(
-            pieces,
+            fields.piece_placement,
             Piece::build_list_board(
                 "r.bqkbnr pppppppp ..n..... ........ ....P... .....N.. PPPP.PPP RNBQKB.R",
             )
         );
-        assert_eq!(side, Color::Black);
-        assert_eq!(castling.len(), 4);
-        assert!(castling.contains(&Piece::WhiteKing));
-        assert!(castling.contains(&Piece::WhiteQueen));
-        assert!(castling.contains(&Piece::BlackKing));
-        assert!(castling.contains(&Piece::BlackQueen));
-        assert_eq!(en_passant, Some(Square::try_from("e3").unwrap()));
-        assert_eq!(half_move, 0);
-        assert_eq!(full_move, 3);
+        assert_eq!(fields.side_to_move, Color::Black);
+        assert_eq!(fields.castling_ability.len(), 4);
+        assert!(fields.castling_ability.contains(&Piece::WhiteKing));
+        assert!(fields.castling_ability.contains(&Piece::WhiteQueen));
+        assert!(fields.castling_ability.contains(&Piece::BlackKing));
+        assert!(fields.castling_ability.contains(&Piece::BlackQueen));
+        assert_eq!(fields.en_passant, Some(Square::try_from("e3").unwrap()));
+        assert_eq!(fields.half_move_clock, 0);
+        assert_eq!(fields.full_move_counter, 3);
+    }
+
+    #[test]
+    fn test_parse_shredder_castling() {
+        let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+        let fields = parse(fen);
+        assert!(fields.castling_ability.is_empty());
+        assert_eq!(
+            fields.castling_rook_files,
+            [
+                (Color::White, 7),
+                (Color::White, 5),
+                (Color::Black, 7),
+                (Color::Black, 5)
+            ]
+        );
+        let fen_again = create(
+            &fields.piece_placement,
+            fields.side_to_move,
+            &fields.castling_ability,
+            &fields.castling_rook_files,
+            fields.en_passant,
+            fields.half_move_clock.into(),
+            fields.full_move_counter.into(),
+        );
+        assert_eq!(fen_again, fen);
+
+        // Both notations can be mixed.
+        let fields = parse("4k3/8/8/8/8/8/8/1R2K2R w KBk - 0 1");
+        assert_eq!(fields.castling_ability, [WhiteKing, BlackKing]);
+        assert_eq!(fields.castling_rook_files, [(Color::White, 1)]);
     }
 
     #[test]
     fn test_parse_end_game_position() {
         let fen = EMPTY_BOARD;
-        let (pieces, side, castling, en_passant, half_move, full_move) = parse(fen);
-
-        assert_eq!(pieces.len(), 64);
-        assert!(pieces.iter().all(|p| p.is_none()));
-        assert_eq!(side, Color::White);
-        assert_eq!(castling.len(), 0);
-        assert_eq!(en_passant, None);
-        assert_eq!(half_move, 0);
-        assert_eq!(full_move, 1);
+        let fields = parse(fen);
+
+        assert_eq!(fields.piece_placement.len(), 64);
+        assert!(fields.piece_placement.iter().all(Option::is_none));
+        assert_eq!(fields.side_to_move, Color::White);
+        assert_eq!(fields.castling_ability.len(), 0);
+        assert_eq!(fields.en_passant, None);
+        assert_eq!(fields.half_move_clock, 0);
+        assert_eq!(fields.full_move_counter, 1);
+    }
+
+    #[test]
+    fn test_parse_large_counters() {
+        let fields = parse("8/8/8/8/8/8/8/8 w - - 300 70000");
+        assert_eq!(fields.half_move_clock, u8::MAX);
+        assert_eq!(fields.full_move_counter, u16::MAX);
     }
 
     #[test]
@@ -347,4 +546,77 @@ mod tests {
         let result = std::panic::catch_unwind(|| parse(fen));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_try_parse_errors() {
+        for (fen, error) in [
+            ("", FenError::WrongFieldCount),
+            ("8/8/8/8/8/8/8/8 w - - 0", FenError::WrongFieldCount),
+            ("8/8/8/8/8/8/8 w - - 0 1", FenError::InvalidPiecePlacement),
+            ("8/8/8/8/8/8/8/7 w - - 0 1", FenError::InvalidPiecePlacement),
+            (
+                "8/8/8/8/8/8/8/44k w - - 0 1",
+                FenError::InvalidPiecePlacement,
+            ),
+            (
+                "8/8/8/8/8/8/8/7x w - - 0 1",
+                FenError::InvalidPiecePlacement,
+            ),
+            (
+                "8/8/8/8/8/8/8/09 w - - 0 1",
+                FenError::InvalidPiecePlacement,
+            ),
+            ("8/8/8/8/8/8/8/8 W - - 0 1", FenError::InvalidSideToMove),
+            (
+                "8/8/8/8/8/8/8/8 w KK - 0 1",
+                FenError::InvalidCastlingRights,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 w Kx - 0 1",
+                FenError::InvalidCastlingRights,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 w KQHAk - 0 1",
+                FenError::InvalidCastlingRights,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 w - e4 0 1",
+                FenError::InvalidEnPassantSquare,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 w - e9 0 1",
+                FenError::InvalidEnPassantSquare,
+            ),
+            ("8/8/8/8/8/8/8/8 w - - -1 1", FenError::InvalidHalfMoveClock),
+            (
+                "8/8/8/8/8/8/8/8 w - - 0 0",
+                FenError::InvalidFullMoveCounter,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 w - - 0 x",
+                FenError::InvalidFullMoveCounter,
+            ),
+        ] {
+            assert_eq!(try_parse(fen), Err(error), "{fen}");
+        }
+        assert!(try_parse(KIWIPETE).is_ok());
+        assert!(try_parse("8/8/8/8/8/8/8/8 b Qk e3 12 40").is_ok());
+    }
+
+    #[test]
+    fn test_try_parse_random_input() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        const CHARS: &[u8] = b"pnbrqkPNBRQK0123456789/ -wabcdefgh";
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..10_000 {
+            // Valid FEN with some characters changed, the parser must not panic.
+            let mut bytes = POSITION_5.as_bytes().to_vec();
+            for _ in 0..rng.gen_range(1..4) {
+                let i = rng.gen_range(0..bytes.len());
+                bytes[i] = CHARS[rng.gen_range(0..CHARS.len())];
+            }
+            let _ = try_parse(&String::from_utf8_lossy(&bytes));
+        }
+    }
 }
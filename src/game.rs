@@ -1,11 +1,20 @@
 use std::io::Write;
 
-use rand::seq::IteratorRandom;
-
-use crate::{board::Board, moves::Move};
+use crate::{
+    board::{Board, DrawReason, GameHistory, MoveHistory},
+    moves::Move,
+    pgn::{self, PgnError, Tags},
+    search::{self, SearchConfig, SearchResult, TimeControl},
+};
 
 pub struct Game {
+    // Position the game started from, and the moves played since.
+    initial_board: Board,
+    moves: MoveHistory,
     board: Board,
+    // Positions of the game, for repetition detection.
+    history: GameHistory,
+    tags: Tags,
     debug: bool,
 }
 
@@ -13,53 +22,197 @@ impl Game {
     // A game is always initialized to a position, either the starting one or from a FEN string.
     pub fn new() -> Self {
         Self {
+            initial_board: Board::initial_board(),
+            moves: MoveHistory::new(),
             board: Board::initial_board(),
+            history: GameHistory::new(&Board::initial_board()),
+            tags: Tags::default(),
             debug: false,
         }
     }
 
+    // Loads a game from a PGN string, positioned after the last move.
+    pub fn from_pgn(pgn: &str) -> Result<Self, PgnError> {
+        let (tags, initial_board, pgn_moves) = pgn::parse(pgn)?;
+        let mut board = initial_board;
+        let mut history = GameHistory::new(&board);
+        let mut moves = MoveHistory::new();
+        for mv in pgn_moves {
+            moves.make_move(&mut board, mv);
+            history.push(board.hash());
+        }
+        Ok(Self {
+            initial_board,
+            moves,
+            board,
+            history,
+            tags,
+            debug: false,
+        })
+    }
+
     pub fn new_game(&mut self) {
-        self.board = Board::initial_board();
+        self.set_to_board(Board::initial_board());
+        self.tags = Tags::default();
     }
 
     pub fn set_to_startpos(&mut self) {
-        self.board = Board::initial_board();
+        self.set_to_board(Board::initial_board());
     }
 
     pub fn set_to_fen(&mut self, fen: &str) {
-        self.board = Board::from_fen(fen);
+        self.set_to_board(Board::from_fen(fen));
+    }
+
+    fn set_to_board(&mut self, board: Board) {
+        self.initial_board = board;
+        self.moves.clear();
+        self.board = board;
+        self.history = GameHistory::new(&board);
     }
 
     pub fn get_board(&self) -> Board {
         self.board
     }
 
+    // Why the game is drawn in the current position, None if it isn't.
+    pub fn draw_reason(&self) -> Option<DrawReason> {
+        self.board.draw_reason(&self.history)
+    }
+
     pub fn display_board<W: Write>(&self, writer: &mut W) {
         let _ = self.board.write(writer);
     }
 
     pub fn apply_moves(&mut self, moves: &[&str]) {
         for mv in moves {
-            self.board.update_by_move(self.board.new_move_from_pure(mv));
+            let mv = self.board.new_move_from_pure(mv);
+            self.moves.make_move(&mut self.board, mv);
+            self.history.push(self.board.hash());
         }
     }
 
-    // Starts a search and returns the best move found.
-    pub fn start_search(&self) -> Option<Move> {
-        // Get pseudo-legal moves
-        self.board
-            .generate_moves()
-            .iter()
-            .filter(|&&mv| {
-                // Filter out moves that leave the king in check.
-                self.board.copy_with_move(mv).is_some()
-            })
-            // Pick a random one
-            .choose(&mut rand::thread_rng())
-            .copied()
+    // Takes back the last move, returning it. None if no moves were played.
+    pub fn undo_move(&mut self) -> Option<Move> {
+        let mv = self.moves.unmake_move(&mut self.board)?;
+        self.history.pop();
+        Some(mv)
+    }
+
+    pub fn set_tags(&mut self, tags: Tags) {
+        self.tags = tags;
+    }
+
+    pub fn to_pgn(&self) -> String {
+        pgn::create(&self.tags, &self.initial_board, &self.moves.to_pgn_moves())
+    }
+
+    // Starts a search on all the CPUs and returns its result.
+    // The progress of the search is written to the info writer.
+    pub fn start_parallel_search<W: Write>(
+        &self,
+        time_control: &TimeControl,
+        info_writer: &mut W,
+    ) -> SearchResult {
+        search::search_parallel(
+            &self.board,
+            &self.history,
+            &SearchConfig::default(),
+            time_control.max_depth(),
+            time_control.allocate_time(self.board.get_side_to_move()),
+            info_writer,
+        )
     }
 
     pub fn set_debug(&mut self, val: bool) {
         self.debug = val;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{Piece::*, Square::*};
+
+    use super::*;
+
+    #[test]
+    fn test_to_pgn_scholars_mate() {
+        let mut game = Game::new();
+        game.apply_moves(&["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"]);
+        game.set_tags(Tags {
+            event: "Casual game".to_string(),
+            site: "Zurich SUI".to_string(),
+            date: "2024.03.01".to_string(),
+            round: "1".to_string(),
+            white: "Kaik".to_string(),
+            black: "Player".to_string(),
+            result: "1-0".to_string(),
+        });
+        assert_eq!(
+            game.to_pgn(),
+            r#"[Event "Casual game"]
+[Site "Zurich SUI"]
+[Date "2024.03.01"]
+[Round "1"]
+[White "Kaik"]
+[Black "Player"]
+[Result "1-0"]
+
+1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0
+"#
+        );
+    }
+
+    #[test]
+    fn test_from_pgn() {
+        let game = Game::from_pgn("1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0").unwrap();
+        assert_eq!(
+            game.get_board(),
+            "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4".into()
+        );
+        assert!(game.to_pgn().ends_with("4. Qxf7# 1-0\n"));
+
+        assert!(Game::from_pgn("1. e4 e4").is_err());
+    }
+
+    #[test]
+    fn test_to_pgn_reset() {
+        let mut game = Game::new();
+        game.apply_moves(&["e2e4"]);
+        game.set_to_startpos();
+        assert!(game.to_pgn().ends_with("\n\n*\n"));
+    }
+
+    #[test]
+    fn test_undo_move() {
+        let mut game = Game::new();
+        game.apply_moves(&["e2e4", "e7e5"]);
+        assert_eq!(game.undo_move(), Some(Move::quiet(E7, E5, BlackPawn)));
+        assert_eq!(
+            game.get_board(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".into()
+        );
+        assert!(game.to_pgn().ends_with(
+            "
+
+1. e4 *
+"
+        ));
+        game.undo_move();
+        assert_eq!(game.undo_move(), None);
+        assert_eq!(game.get_board(), Board::initial_board());
+    }
+
+    #[test]
+    fn test_history_threefold_repetition() {
+        let mut game = Game::new();
+        let moves = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        game.apply_moves(&moves);
+        assert!(!game.board.is_threefold_repetition(&game.history));
+        game.apply_moves(&moves);
+        assert!(game.board.is_threefold_repetition(&game.history));
+
+        game.set_to_startpos();
+        assert!(!game.board.is_threefold_repetition(&game.history));
+    }
+}
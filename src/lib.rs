@@ -0,0 +1,27 @@
+//! Kaik Chess Engine, as a library so that the benchmarks can use it.
+#![allow(dead_code)]
+// The engine is an application, the library isn't meant to be a public API.
+#![allow(
+    clippy::must_use_candidate,
+    clippy::return_self_not_must_use,
+    clippy::missing_errors_doc,
+    clippy::missing_panics_doc,
+    clippy::new_without_default
+)]
+
+#[macro_use]
+extern crate log;
+
+pub mod bitboard;
+pub mod board;
+pub mod book;
+pub mod common;
+pub mod epd;
+pub mod eval;
+pub mod fen;
+pub mod game;
+pub mod moves;
+pub mod pgn;
+pub mod search;
+pub mod tt;
+pub mod uci;
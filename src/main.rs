@@ -9,21 +9,16 @@ use simplelog::{
     ColorChoice, CombinedLogger, Config, LevelFilter, TermLogger, TerminalMode, WriteLogger,
 };
 use std::fs::File;
-use std::path::PathBuf;
-use std::{io, time::Instant};
-
-use board::Board;
-use common::Square;
-use moves::Move;
-use uci::Uci;
-
-mod bitboard;
-mod board;
-mod common;
-mod fen;
-mod game;
-mod moves;
-mod uci;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use kaik::{
+    board::{nodes_per_second, perft_bench, Board, GameHistory, GameResult},
+    book::{BookError, OpeningBook},
+    common::Square,
+    moves::Move,
+    uci::Uci,
+};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -32,6 +27,10 @@ struct Arguments {
     #[arg(short, long, value_name = "FILE")]
     log: Option<PathBuf>,
 
+    /// Sets an opening book file
+    #[arg(short, long, value_name = "FILE")]
+    book: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -56,13 +55,15 @@ enum Commands {
         position: String,
         moves: Option<String>,
     },
+    /// Plays a game between two players, entering the moves in long algebraic notation.
+    Play,
 }
 
-fn create_board(position: &String, moves: &Option<String>) -> Board {
+fn create_board(position: &str, moves: Option<&String>) -> Board {
     let mut b: Board = if position == "startpos" {
         Board::initial_board()
     } else {
-        position.as_str().into()
+        position.into()
     };
     if let Some(m) = moves {
         apply_moves(&mut b, m);
@@ -104,7 +105,7 @@ fn main() {
             position,
             moves,
         }) => {
-            divide(&create_board(position, moves), *depth);
+            divide(&create_board(position, moves.as_ref()), *depth);
             return;
         }
         Some(Commands::Perft {
@@ -112,7 +113,7 @@ fn main() {
             position,
             moves,
         }) => {
-            let nodes_cnt = create_board(position, moves).perft(*depth);
+            let nodes_cnt = create_board(position, moves.as_ref()).perft(*depth);
             println!("{nodes_cnt}");
             return;
         }
@@ -121,15 +122,19 @@ fn main() {
             position,
             moves,
         }) => {
-            perft(&create_board(position, moves), *depth);
+            perft(&create_board(position, moves.as_ref()), *depth);
             return;
         }
-        _ => {}
+        Some(Commands::Play) => {
+            run_repl();
+            return;
+        }
+        None => {}
     }
 
     info!("Kaik Chess Engine");
 
-    start_uci_loop();
+    start_uci_loop(load_book(args.book.as_deref()));
 
     // hacks();
 }
@@ -152,25 +157,98 @@ fn hacks() {
     }
 }
 
-fn start_uci_loop() {
+// Without a book, or if it fails to load, we just search from the first move.
+fn load_book(path: Option<&Path>) -> Option<OpeningBook> {
+    let path = path?;
+    let book = File::open(path)
+        .map_err(|err| BookError::Io(err.to_string()))
+        .and_then(OpeningBook::load);
+    match book {
+        Ok(book) => {
+            info!(
+                "Loaded {} book positions from {}",
+                book.len(),
+                path.display()
+            );
+            Some(book)
+        }
+        Err(err) => {
+            error!("Failed to load the book {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+fn start_uci_loop(book: Option<OpeningBook>) {
     let stdio = io::stdin();
     let input = stdio.lock();
 
     let output = io::stdout();
 
     let mut uci = Uci::new(input, output);
+    if let Some(book) = book {
+        uci.set_book(book);
+    }
 
     uci.uci_loop();
 }
 
+pub fn run_repl() {
+    let stdin = io::stdin();
+    repl(stdin.lock(), io::stdout());
+}
+
+// Prints the board and reads moves, until the game is over or the input ends.
+// Writing to stdout isn't expected to fail, so write errors panic.
+fn repl<R: BufRead, W: Write>(reader: R, mut writer: W) {
+    let mut board = Board::initial_board();
+    let mut history = GameHistory::new(&board);
+    let mut lines = reader.lines();
+    loop {
+        board.write(&mut writer).unwrap();
+        if let Some(result) = game_result(&board, &history) {
+            writeln!(writer, "{result}").unwrap();
+            return;
+        }
+        // Prompts again until the move is valid.
+        loop {
+            write!(writer, "{} to move> ", board.get_side_to_move()).unwrap();
+            writer.flush().unwrap();
+            let Some(Ok(line)) = lines.next() else {
+                return;
+            };
+            let input = line.trim();
+            if input.is_empty() {
+                continue;
+            }
+            match Move::from_long_algebraic(input, &board).and_then(|mv| board.apply_move(mv)) {
+                Ok(next) => {
+                    board = next;
+                    history.push(board.hash());
+                    break;
+                }
+                Err(err) => writeln!(writer, "{err}: {input}").unwrap(),
+            }
+        }
+    }
+}
+
+// How the game ended, None if it isn't over.
+fn game_result(board: &Board, history: &GameHistory) -> Option<String> {
+    match board.game_result(history) {
+        GameResult::WhiteWins => Some("Checkmate, White wins.".to_string()),
+        GameResult::BlackWins => Some("Checkmate, Black wins.".to_string()),
+        GameResult::Draw(reason) => Some(format!("Draw by {reason}.")),
+        GameResult::Ongoing => None,
+    }
+}
+
 fn perft(board: &Board, depth: usize) {
-    let now = Instant::now();
-    let nodes_count = board.perft(depth);
-    let elapsed = now.elapsed();
+    let (nodes_count, elapsed) = perft_bench(board, u8::try_from(depth).unwrap_or(u8::MAX));
 
     println!("Perft results for depth {depth}: {nodes_count} nodes.");
 
-    let nodes_secs = nodes_count as u128 / elapsed.as_micros();
+    let nodes_secs = nodes_per_second(nodes_count, elapsed) / 1_000_000;
     println!("Time: {elapsed:.2?} secs. \t{nodes_secs} millions nodes / secs.");
 }
 
@@ -184,7 +262,7 @@ fn divide(board: &Board, depth: usize) {
         println!("{}: {count}", mv.pure());
     }
     println!();
-    println!("Nodes searched: {total_nodes}",);
+    println!("Nodes searched: {total_nodes}");
 }
 
 fn print_moves_with_board(board: &Board, moves: &[Move]) {
@@ -211,3 +289,32 @@ fn apply_moves(board: &mut Board, moves: &str) {
         board.update_by_move(board.new_move(from, to));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &str) -> String {
+        let mut output = Vec::new();
+        repl(input.as_bytes(), &mut output);
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_repl_fools_mate() {
+        let output = run("f2f3\ne7e5\ng2g4\nd8h4\n");
+        assert!(output.ends_with("Checkmate, Black wins.\n"));
+        assert_eq!(output.matches("White to move> ").count(), 2);
+        assert_eq!(output.matches("Black to move> ").count(), 2);
+    }
+
+    #[test]
+    fn test_repl_invalid_input() {
+        let output = run("e2e5\nhi\n\ne2e4\n");
+        assert!(output.contains("Illegal move: e2e5\n"));
+        assert!(output.contains("Invalid move length: hi\n"));
+        assert_eq!(output.matches("White to move> ").count(), 4);
+        // Input ended while waiting for black's move.
+        assert!(output.ends_with("Black to move> "));
+    }
+}
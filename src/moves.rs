@@ -3,7 +3,37 @@
 
 use std::fmt::Display;
 
-use crate::{common::Piece, common::Square};
+use crate::{board::Board, common::Piece, common::Square};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    InvalidLength,
+    InvalidSquare,
+    NoPieceOnFromSquare,
+    InvalidPromotion,
+    // A promotion piece was given for a move that isn't a promotion.
+    UnexpectedPromotion,
+    OwnPieceOnDestination,
+    // The piece can't move like that, or the move leaves the king in check.
+    IllegalMove,
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = match self {
+            MoveError::InvalidLength => "Invalid move length",
+            MoveError::InvalidSquare => "Invalid square",
+            MoveError::NoPieceOnFromSquare => "No piece on from square",
+            MoveError::InvalidPromotion => "Invalid promotion flag",
+            MoveError::UnexpectedPromotion => "Promotion flag on non-promotion move",
+            MoveError::OwnPieceOnDestination => "Destination occupied by own piece",
+            MoveError::IllegalMove => "Illegal move",
+        };
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for MoveError {}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Move {
@@ -14,7 +44,9 @@ pub struct Move {
     promotion: Option<Piece>,
     // Following information helps to avoid board lookups when applying moves.
     piece: Piece, // Piece performing the move
-    is_capture: bool,
+    captured: Option<Piece>,
+    // For Chess960 castling only, the square of the rook.
+    castling_rook: Option<Square>,
     // We can add more flags: Castling, double push pawn, en passant.
 }
 
@@ -24,27 +56,140 @@ impl Move {
         to: Square,
         promotion: Option<Piece>,
         piece: Piece,
-        is_capture: bool,
+        captured: Option<Piece>,
     ) -> Self {
         assert!(match promotion {
             None => true,
             Some(p) => !p.is_pawn() && !p.is_king(),
         });
+        assert!(match captured {
+            None => true,
+            Some(c) => c as u8 % 2 != piece as u8 % 2,
+        });
         Self {
             from,
             to,
             promotion,
             piece,
-            is_capture,
+            captured,
+            castling_rook: None,
         }
     }
 
+    // Moves must be created with the captured piece if there is one, including en passant:
+    // is_capture() is true for captures and false for all other moves.
     pub const fn quiet(from: Square, to: Square, piece: Piece) -> Self {
-        Self::new(from, to, None, piece, false)
+        Self::new(from, to, None, piece, None)
+    }
+
+    pub const fn capture(from: Square, to: Square, piece: Piece, captured: Piece) -> Self {
+        Self::new(from, to, None, piece, Some(captured))
+    }
+
+    // Promotion without capture. Capturing promotions need to be created with new().
+    pub const fn promotion(from: Square, to: Square, piece: Piece, promo: Piece) -> Self {
+        assert!(piece.is_pawn());
+        Self::new(from, to, Some(promo), piece, None)
+    }
+
+    // The four promotions of a pawn move, queen first. Captured is the piece on the destination, if any.
+    pub fn promotions_from(
+        from: Square,
+        to: Square,
+        piece: Piece,
+        captured: Option<Piece>,
+    ) -> [Self; 4] {
+        assert!(piece.is_pawn());
+        Piece::PROMOTION_PIECES[piece.get_color() as usize]
+            .map(|promotion| Self::new(from, to, Some(promotion), piece, captured))
+    }
+
+    // The destination is the en passant target square, not the square of the captured pawn.
+    pub const fn en_passant(from: Square, to: Square, piece: Piece) -> Self {
+        assert!(piece.is_pawn());
+        Self::capture(
+            from,
+            to,
+            piece,
+            Piece::get_pawn_of(piece.get_color().opposite()),
+        )
+    }
+
+    // Castling is encoded as the king move only, see get_castling() for the rook move.
+    pub const fn castling(from: Square, to: Square, piece: Piece) -> Self {
+        assert!(piece.is_king());
+        Self::quiet(from, to, piece)
+    }
+
+    // Chess960 castling is encoded as the king moving to the square of its rook, like UCI does.
+    // See get_piece_move() for the actual king move.
+    pub const fn castling_chess960(from: Square, rook: Square, piece: Piece) -> Self {
+        assert!(piece.is_king());
+        let mut mv = Self::quiet(from, rook, piece);
+        mv.castling_rook = Some(rook);
+        mv
+    }
+
+    // Parses a move in the UCI format (pure coordinate notation), like "e2e4" or "e7e8q".
+    pub fn from_uci(s: &str, board: &Board) -> Result<Self, MoveError> {
+        if !s.is_ascii() || !(4..=5).contains(&s.len()) {
+            return Err(MoveError::InvalidLength);
+        }
+        let from: Square = s[0..2].try_into().map_err(|_| MoveError::InvalidSquare)?;
+        let to: Square = s[2..4].try_into().map_err(|_| MoveError::InvalidSquare)?;
+
+        let piece = board
+            .get_piece_at(from)
+            .ok_or(MoveError::NoPieceOnFromSquare)?;
+        let color = piece.get_color();
+        let promotion = if piece.is_pawn() && to.is_promotion_rank_for(color) {
+            let promotion_piece = match s.get(4..5) {
+                Some("q") => Piece::get_queen_of(color),
+                Some("r") => Piece::get_rook_of(color),
+                Some("b") => Piece::get_bishop_of(color),
+                Some("n") => Piece::get_knight_of(color),
+                _ => return Err(MoveError::InvalidPromotion),
+            };
+            Some(promotion_piece)
+        } else if s.len() == 5 {
+            return Err(MoveError::UnexpectedPromotion);
+        } else {
+            None
+        };
+
+        if board
+            .get_piece_at(to)
+            .is_some_and(|p| p.get_color() == color)
+        {
+            // In Chess960, castling is written as the king taking its own rook.
+            if piece.is_king()
+                && board.is_chess960()
+                && board.get_piece_at(to) == Some(Piece::get_rook_of(color))
+            {
+                return Ok(Self::castling_chess960(from, to, piece));
+            }
+            return Err(MoveError::OwnPieceOnDestination);
+        }
+
+        let mv = Self::quiet(from, to, piece);
+        let captured = if board.is_en_passant_capture(mv) {
+            Some(Piece::get_pawn_of(color.opposite()))
+        } else {
+            board.get_piece_at(to)
+        };
+        Ok(Self::new(from, to, promotion, piece, captured))
     }
 
-    pub const fn capture(from: Square, to: Square, piece: Piece) -> Self {
-        Self::new(from, to, None, piece, true)
+    // Parses a move in long algebraic notation, like "e2e4" or "e7e8=Q".
+    // The promotion can also be given as in UCI, like "e7e8q".
+    pub fn from_long_algebraic(s: &str, board: &Board) -> Result<Self, MoveError> {
+        match s.split_once('=') {
+            Some((squares, promotion)) if squares.len() == 4 && promotion.len() == 1 => {
+                Self::from_uci(&format!("{squares}{}", promotion.to_lowercase()), board)
+            }
+            Some(_) => Err(MoveError::InvalidPromotion),
+            None => Self::from_uci(s, board),
+        }
     }
 
     pub fn get_from(self) -> Square {
@@ -59,12 +204,23 @@ impl Move {
         self.promotion
     }
 
+    // Promotion to a knight, rook or bishop, which are rarely the best.
+    pub fn is_underpromotion(self) -> bool {
+        self.promotion
+            .is_some_and(|promotion| !promotion.is_queen())
+    }
+
     pub fn get_piece(self) -> Piece {
         self.piece
     }
 
     pub fn is_capture(self) -> bool {
-        self.is_capture
+        self.captured.is_some()
+    }
+
+    // For en passant, this is the pawn next to the destination square.
+    pub fn get_captured_piece(self) -> Option<Piece> {
+        self.captured
     }
 
     pub fn is_pawn_double_push(self) -> bool {
@@ -74,7 +230,7 @@ impl Move {
     pub fn get_en_passant_target_square(self) -> Option<Square> {
         if self.is_pawn_double_push() {
             assert_eq!(self.from.get_file(), self.to.get_file());
-            let rank = (self.from.get_rank() + self.to.get_rank()) / 2;
+            let rank = u8::midpoint(self.from.get_rank(), self.to.get_rank());
             Some(Square::new(rank, self.from.get_file()))
         } else {
             None
@@ -82,16 +238,20 @@ impl Move {
     }
 
     pub const KING_TO_KING_SIDE_CASTLING: [Move; 2] = [
-        Move::quiet(Square::E1, Square::G1, Piece::WhiteKing),
-        Move::quiet(Square::E8, Square::G8, Piece::BlackKing),
+        Move::castling(Square::E1, Square::G1, Piece::WhiteKing),
+        Move::castling(Square::E8, Square::G8, Piece::BlackKing),
     ];
 
     pub const KING_TO_QUEEN_SIDE_CASTLING: [Move; 2] = [
-        Move::quiet(Square::E1, Square::C1, Piece::WhiteKing),
-        Move::quiet(Square::E8, Square::C8, Piece::BlackKing),
+        Move::castling(Square::E1, Square::C1, Piece::WhiteKing),
+        Move::castling(Square::E8, Square::C8, Piece::BlackKing),
     ];
 
-    // If this is a castling move, the move itself indicates the king move.
+    pub fn is_castling(self) -> bool {
+        self.get_castling().is_some()
+    }
+
+    // If this is a castling move, the move itself indicates the king move (see get_piece_move()).
     // This function returns the extra rook move that needs to be done.
     pub fn get_castling(self) -> Option<Move> {
         const WHITE_KING_SIDE: Option<Move> =
@@ -102,6 +262,17 @@ impl Move {
             Some(Move::quiet(Square::H8, Square::F8, Piece::BlackRook));
         const BLACK_QUEEN_SIDE: Option<Move> =
             Some(Move::quiet(Square::A8, Square::D8, Piece::BlackRook));
+        if let Some(rook) = self.castling_rook {
+            let rook_to_file = if rook.get_file() > self.from.get_file() {
+                5
+            } else {
+                3
+            };
+            let rook_to = Square::new(self.from.get_rank(), rook_to_file);
+            let rook_piece = Piece::get_rook_of(self.piece.get_color());
+            return Some(Move::quiet(rook, rook_to, rook_piece));
+        }
+
         if self.piece.is_king() {
             if self.from == Square::E1 {
                 // White
@@ -122,6 +293,25 @@ impl Move {
         None
     }
 
+    // The move of the piece itself, without the rook move for castling.
+    // It's the move itself, except for Chess960 castling where the king doesn't go to the rook square.
+    pub fn get_piece_move(self) -> Move {
+        if let Some(rook) = self.castling_rook {
+            let king_to_file = if rook.get_file() > self.from.get_file() {
+                6
+            } else {
+                2
+            };
+            Move::quiet(
+                self.from,
+                Square::new(self.from.get_rank(), king_to_file),
+                self.piece,
+            )
+        } else {
+            self
+        }
+    }
+
     pub fn print_list(moves: &[Move]) {
         for mv in moves {
             println!("{mv}");
@@ -142,12 +332,25 @@ impl Move {
         write!(f, "{}{}{}", self.get_from(), self.get_to(), promotion)
     }
 
+    // Long algebraic notation, like "e2e4" or "e7e8=Q".
+    pub fn to_long_algebraic(self) -> String {
+        match self.promotion {
+            Some(promotion) => format!(
+                "{}{}={}",
+                self.from,
+                self.to,
+                char::from(promotion).to_ascii_uppercase()
+            ),
+            None => format!("{}{}", self.from, self.to),
+        }
+    }
+
     fn fmt_as_lan(self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // Long Algebraic Notation
         // <https://www.chessprogramming.org/Algebraic_Chess_Notation#Long_Algebraic_Notation_.28LAN.29>
         let from = self.get_from().to_string().to_uppercase();
         let to = self.get_to().to_string().to_uppercase();
-        let separator = if self.is_capture { 'x' } else { '-' };
+        let separator = if self.is_capture() { 'x' } else { '-' };
         if self.piece.is_pawn() {
             let promotion = match self.get_promotion() {
                 Some(Piece::WhiteQueen | Piece::BlackQueen) => "Q",
@@ -163,15 +366,80 @@ impl Move {
         }
     }
 
+    // Standard Algebraic Notation, like "Nf3", "exd5", "O-O" or "e8=Q+".
+    // <https://www.chessprogramming.org/Algebraic_Chess_Notation#Standard_Algebraic_Notation_.28SAN.29>
+    // The board is the position before the move is played.
+    pub fn to_san(self, board: &Board) -> String {
+        let mut san = String::new();
+        if self.is_castling() {
+            san.push_str(if self.get_piece_move().to.get_file() == 6 {
+                "O-O"
+            } else {
+                "O-O-O"
+            });
+        } else {
+            let is_capture = self.is_capture();
+            if self.piece.is_pawn() {
+                if is_capture {
+                    san.push((self.from.get_file() + b'a') as char);
+                }
+            } else {
+                san.push(char::from(self.piece).to_ascii_uppercase());
+
+                // Disambiguation, if other pieces of the same kind can go to the same square.
+                let others: Vec<Move> = board
+                    .generate_legal_moves()
+                    .into_iter()
+                    .filter(|mv| mv.piece == self.piece && mv.to == self.to && mv.from != self.from)
+                    .collect();
+                if !others.is_empty() {
+                    let file = self.from.get_file();
+                    let rank = self.from.get_rank();
+                    if others.iter().all(|mv| mv.from.get_file() != file) {
+                        san.push((file + b'a') as char);
+                    } else if others.iter().all(|mv| mv.from.get_rank() != rank) {
+                        san.push((rank + b'1') as char);
+                    } else {
+                        san.push_str(&self.from.to_string());
+                    }
+                }
+            }
+            if is_capture {
+                san.push('x');
+            }
+            san.push_str(&self.to.to_string());
+            if let Some(promotion) = self.promotion {
+                san.push('=');
+                san.push(char::from(promotion).to_ascii_uppercase());
+            }
+        }
+
+        if let Some(new_board) = board.copy_with_move(self) {
+            if new_board.is_in_check() {
+                if new_board.generate_legal_moves().is_empty() {
+                    san.push('#');
+                } else {
+                    san.push('+');
+                }
+            }
+        }
+        san
+    }
+
     pub fn pure(&self) -> impl std::fmt::Display + '_ {
         struct Pure<'a>(&'a Move);
-        impl<'a> std::fmt::Display for Pure<'a> {
+        impl std::fmt::Display for Pure<'_> {
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
                 self.0.fmt_as_pure(f)
             }
         }
         Pure(self)
     }
+
+    // Displays the move in SAN, the board being the position before the move.
+    pub fn display_san<'a>(&'a self, board: &'a Board) -> MoveDisplay<'a> {
+        MoveDisplay(self, board)
+    }
 }
 
 impl Display for Move {
@@ -179,3 +447,261 @@ impl Display for Move {
         self.fmt_as_lan(f)
     }
 }
+
+// A move with the board it is played on, which SAN needs.
+pub struct MoveDisplay<'a>(&'a Move, &'a Board);
+
+impl Display for MoveDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Padding, so that moves can be aligned with "{:8}".
+        f.pad(&self.0.to_san(self.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{Piece::*, Square::*};
+
+    use super::*;
+
+    #[test]
+    fn test_from_uci() {
+        let board = Board::initial_board();
+        assert_eq!(
+            Move::from_uci("e2e4", &board),
+            Ok(Move::quiet(E2, E4, WhitePawn))
+        );
+        assert_eq!(
+            Move::from_uci("g1f3", &board),
+            Ok(Move::quiet(G1, F3, WhiteKnight))
+        );
+
+        let board: Board = "4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1".into();
+        assert_eq!(
+            Move::from_uci("e4d5", &board),
+            Ok(Move::capture(E4, D5, WhitePawn, BlackPawn))
+        );
+
+        // En passant.
+        let board: Board = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1".into();
+        assert_eq!(
+            Move::from_uci("e5d6", &board),
+            Ok(Move::en_passant(E5, D6, WhitePawn))
+        );
+    }
+
+    #[test]
+    fn test_long_algebraic() {
+        let board: Board = "3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1".into();
+        for (lan, mv) in [
+            ("e1d1", Move::quiet(E1, D1, WhiteKing)),
+            ("e7e8=Q", Move::promotion(E7, E8, WhitePawn, WhiteQueen)),
+            (
+                "e7d8=N",
+                Move::new(E7, D8, Some(WhiteKnight), WhitePawn, Some(BlackRook)),
+            ),
+        ] {
+            assert_eq!(mv.to_long_algebraic(), lan);
+            assert_eq!(Move::from_long_algebraic(lan, &board), Ok(mv));
+        }
+        assert_eq!(
+            Move::from_long_algebraic("e7e8q", &board),
+            Ok(Move::promotion(E7, E8, WhitePawn, WhiteQueen))
+        );
+        assert_eq!(
+            Move::from_long_algebraic("e7e8=", &board),
+            Err(MoveError::InvalidPromotion)
+        );
+        assert_eq!(
+            Move::from_long_algebraic("e7e8=K", &board),
+            Err(MoveError::InvalidPromotion)
+        );
+        assert_eq!(
+            Move::from_long_algebraic("e1e2=Q", &board),
+            Err(MoveError::UnexpectedPromotion)
+        );
+        assert_eq!(
+            Move::from_long_algebraic("e3e4", &board),
+            Err(MoveError::NoPieceOnFromSquare)
+        );
+    }
+
+    #[test]
+    fn test_constructors_capture_flag() {
+        assert!(!Move::quiet(E2, E4, WhitePawn).is_capture());
+        let capture = Move::capture(E4, D5, WhitePawn, BlackKnight);
+        assert!(capture.is_capture());
+        assert_eq!(capture.get_captured_piece(), Some(BlackKnight));
+        let en_passant = Move::en_passant(E5, D6, WhitePawn);
+        assert!(en_passant.is_capture());
+        assert_eq!(en_passant.get_captured_piece(), Some(BlackPawn));
+        let promotion = Move::promotion(B2, B1, BlackPawn, BlackRook);
+        assert!(!promotion.is_capture());
+        assert_eq!(promotion.get_captured_piece(), None);
+        assert_eq!(promotion.get_promotion(), Some(BlackRook));
+    }
+
+    #[test]
+    fn test_promotions_from() {
+        assert_eq!(
+            Move::promotions_from(B7, B8, WhitePawn, None),
+            [
+                Move::promotion(B7, B8, WhitePawn, WhiteQueen),
+                Move::promotion(B7, B8, WhitePawn, WhiteKnight),
+                Move::promotion(B7, B8, WhitePawn, WhiteRook),
+                Move::promotion(B7, B8, WhitePawn, WhiteBishop),
+            ]
+        );
+        let captures = Move::promotions_from(G2, H1, BlackPawn, Some(WhiteRook));
+        assert!(captures
+            .iter()
+            .all(|mv| mv.get_captured_piece() == Some(WhiteRook)));
+        assert_eq!(captures[0].get_promotion(), Some(BlackQueen));
+
+        assert!(!captures[0].is_underpromotion());
+        assert!(captures[1..].iter().all(|mv| mv.is_underpromotion()));
+        assert!(!Move::quiet(E2, E4, WhitePawn).is_underpromotion());
+    }
+
+    #[test]
+    fn test_from_uci_promotion() {
+        let board: Board = "3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1".into();
+        assert_eq!(
+            Move::from_uci("e7e8q", &board),
+            Ok(Move::promotion(E7, E8, WhitePawn, WhiteQueen))
+        );
+        assert_eq!(
+            Move::from_uci("e7d8n", &board),
+            Ok(Move::new(
+                E7,
+                D8,
+                Some(WhiteKnight),
+                WhitePawn,
+                Some(BlackRook)
+            ))
+        );
+        assert!(Move::from_uci("e7e8", &board).is_err());
+        assert!(Move::from_uci("e7e8k", &board).is_err());
+    }
+
+    #[test]
+    fn test_from_uci_invalid() {
+        let board = Board::initial_board();
+        assert!(Move::from_uci("", &board).is_err());
+        assert!(Move::from_uci("e2", &board).is_err());
+        assert!(Move::from_uci("e2e4e5", &board).is_err());
+        assert!(Move::from_uci("z2e4", &board).is_err());
+        assert!(Move::from_uci("e3e4", &board).is_err()); // Empty square
+        assert!(Move::from_uci("e2e4q", &board).is_err());
+    }
+
+    fn san(fen: &str, mv: &str) -> String {
+        let board: Board = fen.into();
+        board.new_move_from_pure(mv).to_san(&board)
+    }
+
+    #[test]
+    fn test_to_san() {
+        let start = crate::fen::START_POSITION;
+        assert_eq!(san(start, "g1f3"), "Nf3");
+        assert_eq!(san(start, "e2e4"), "e4");
+
+        let fen = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2";
+        assert_eq!(san(fen, "e4d5"), "exd5");
+        // En passant.
+        let fen = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1";
+        assert_eq!(san(fen, "e5d6"), "exd6");
+
+        // Castling.
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert_eq!(san(fen, "e1g1"), "O-O");
+        assert_eq!(san(fen, "e1c1"), "O-O-O");
+        assert_eq!(san("r3k2r/8/8/8/8/8/8/R2K3R b kq - 0 1", "e8c8"), "O-O-O+");
+
+        // Promotions.
+        let fen = "3r4/4P3/8/8/8/8/8/k3K3 w - - 0 1";
+        assert_eq!(san(fen, "e7e8q"), "e8=Q");
+        assert_eq!(san(fen, "e7d8n"), "exd8=N");
+        assert_eq!(san("7k/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7e8q"), "e8=Q+");
+    }
+
+    #[test]
+    fn test_display_san() {
+        let board = Board::initial_board();
+        let mv = board.new_move_from_pure("g1f3");
+        assert_eq!(format!("{}", mv.display_san(&board)), "Nf3");
+        assert_eq!(format!("{:<5}|", mv.display_san(&board)), "Nf3  |");
+        // The plain Display is still long algebraic.
+        assert_eq!(mv.to_string(), "NG1-F3");
+    }
+
+    #[test]
+    fn test_to_san_disambiguation() {
+        // Knights on b1 and f3 can both go to d2.
+        let fen = "4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1";
+        assert_eq!(san(fen, "b1d2"), "Nbd2");
+        assert_eq!(san(fen, "f3d2"), "Nfd2");
+
+        // Rooks on the same file.
+        let fen = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(san(fen, "a1a3"), "R1a3");
+        assert_eq!(san(fen, "a5a3"), "R5a3");
+
+        // Three queens, need both file and rank.
+        let fen = "1k6/8/8/8/4Q2Q/8/8/K6Q w - - 0 1";
+        assert_eq!(san(fen, "h4e1"), "Qh4e1");
+        assert_eq!(san(fen, "e4e1"), "Qee1");
+        assert_eq!(san(fen, "h4f2"), "Qf2");
+    }
+
+    #[test]
+    fn test_to_san_mate() {
+        // Scholar's mate.
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4";
+        assert_eq!(san(fen, "h5f7"), "Qxf7#");
+    }
+
+    #[test]
+    fn test_is_castling() {
+        assert!(Move::castling(E1, G1, WhiteKing).is_castling());
+        assert!(Move::castling(E1, C1, WhiteKing).is_castling());
+        assert!(Move::castling(E8, G8, BlackKing).is_castling());
+        assert!(Move::castling(E8, C8, BlackKing).is_castling());
+
+        assert!(!Move::quiet(E1, F1, WhiteKing).is_castling());
+        assert!(!Move::quiet(E8, G8, BlackQueen).is_castling());
+    }
+
+    #[test]
+    fn test_chess960_castling() {
+        let board: Board = "4k3/8/8/8/8/8/8/1R3KR1 w BG - 0 1".into();
+        let mv = Move::from_uci("f1g1", &board).unwrap();
+        assert_eq!(mv, Move::castling_chess960(F1, G1, WhiteKing));
+        assert!(mv.is_castling());
+        assert_eq!(mv.get_piece_move(), Move::quiet(F1, G1, WhiteKing));
+        assert_eq!(mv.get_castling(), Some(Move::quiet(G1, F1, WhiteRook)));
+        assert_eq!(mv.pure().to_string(), "f1g1");
+        assert_eq!(mv.to_san(&board), "O-O");
+
+        let mv = Move::from_uci("f1b1", &board).unwrap();
+        assert_eq!(mv.get_piece_move(), Move::quiet(F1, C1, WhiteKing));
+        assert_eq!(mv.get_castling(), Some(Move::quiet(B1, D1, WhiteRook)));
+        assert_eq!(mv.to_san(&board), "O-O-O");
+
+        // Not castling in standard chess.
+        let board: Board = "4k3/8/8/8/8/8/8/4K2R w - - 0 1".into();
+        assert!(Move::from_uci("e1h1", &board).is_err());
+    }
+
+    #[test]
+    fn test_get_castling() {
+        assert_eq!(
+            Move::castling(E1, G1, WhiteKing).get_castling(),
+            Some(Move::quiet(H1, F1, WhiteRook))
+        );
+        assert_eq!(
+            Move::castling(E8, C8, BlackKing).get_castling(),
+            Some(Move::quiet(A8, D8, BlackRook))
+        );
+    }
+}
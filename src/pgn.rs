@@ -0,0 +1,406 @@
+//! Parsing and creation of PGN strings.
+//! Doc: <https://www.chessprogramming.org/Portable_Game_Notation>
+//! Spec: <https://www.saremco.com/Files/pgn_standard.txt>
+
+use std::fmt;
+
+use itertools::Itertools;
+
+use crate::{board::Board, common::Color, fen::FenError, moves::Move};
+
+// Lines of the movetext should not be longer than this.
+const MAX_LINE_LENGTH: usize = 80;
+
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    InvalidTag(String),
+    InvalidFen(FenError),
+    UnterminatedComment,
+    InvalidMove(String),
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnError::InvalidTag(tag) => write!(f, "Invalid tag: {tag}"),
+            PgnError::InvalidFen(err) => write!(f, "Invalid FEN tag: {err}"),
+            PgnError::UnterminatedComment => write!(f, "Unterminated comment or variation"),
+            PgnError::InvalidMove(mv) => write!(f, "Invalid or illegal move: {mv}"),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+// The Seven Tag Roster, with the PGN values for unknown tags as defaults.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tags {
+    pub event: String,
+    pub site: String,
+    pub date: String,
+    pub round: String,
+    pub white: String,
+    pub black: String,
+    // "1-0", "0-1", "1/2-1/2", or "*" for a game in progress.
+    pub result: String,
+}
+
+impl Default for Tags {
+    fn default() -> Self {
+        Self {
+            event: "?".to_string(),
+            site: "?".to_string(),
+            date: "????.??.??".to_string(),
+            round: "?".to_string(),
+            white: "?".to_string(),
+            black: "?".to_string(),
+            result: "*".to_string(),
+        }
+    }
+}
+
+fn create_tags(tags: &Tags, initial_board: &Board) -> String {
+    let mut pairs = vec![
+        ("Event", tags.event.clone()),
+        ("Site", tags.site.clone()),
+        ("Date", tags.date.clone()),
+        ("Round", tags.round.clone()),
+        ("White", tags.white.clone()),
+        ("Black", tags.black.clone()),
+        ("Result", tags.result.clone()),
+    ];
+    // Games not starting from the initial position need to include it.
+    if *initial_board != Board::initial_board() {
+        pairs.push(("SetUp", "1".to_string()));
+        pairs.push(("FEN", initial_board.to_fen()));
+    }
+    pairs
+        .iter()
+        .map(|(name, value)| format!("[{name} \"{value}\"]"))
+        .join("\n")
+        + "\n"
+}
+
+// Moves in SAN, with the move numbers.
+fn create_movetext_tokens(initial_board: &Board, moves: &[Move]) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut board = *initial_board;
+    let mut move_number = 1;
+    for (i, mv) in moves.iter().enumerate() {
+        if board.get_side_to_move().is_white() {
+            tokens.push(format!("{move_number}."));
+        } else if i == 0 {
+            tokens.push(format!("{move_number}..."));
+        }
+        tokens.push(mv.to_san(&board));
+        if board.get_side_to_move() == Color::Black {
+            move_number += 1;
+        }
+        board.update_by_move(*mv);
+    }
+    tokens
+}
+
+fn wrap_tokens(tokens: &[String]) -> String {
+    let mut result = String::new();
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length > 0 {
+            if line_length + 1 + token.len() > MAX_LINE_LENGTH {
+                result.push('\n');
+                line_length = 0;
+            } else {
+                result.push(' ');
+                line_length += 1;
+            }
+        }
+        result.push_str(token);
+        line_length += token.len();
+    }
+    result
+}
+
+// Creates a PGN string from the initial position and the moves played.
+pub fn create(tags: &Tags, initial_board: &Board, moves: &[Move]) -> String {
+    let mut tokens = create_movetext_tokens(initial_board, moves);
+    tokens.push(tags.result.clone());
+    format!(
+        "{}\n{}\n",
+        create_tags(tags, initial_board),
+        wrap_tokens(&tokens)
+    )
+}
+
+// Parses a tag pair like [Event "F/S Return Match"].
+fn parse_tag(line: &str) -> Result<(&str, &str), PgnError> {
+    let invalid = || PgnError::InvalidTag(line.to_string());
+    let content = line
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .ok_or_else(invalid)?;
+    let (name, value) = content.split_once(' ').ok_or_else(invalid)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+    Ok((name, value))
+}
+
+fn set_tag(tags: &mut Tags, name: &str, value: &str) {
+    let value = value.to_string();
+    match name {
+        "Event" => tags.event = value,
+        "Site" => tags.site = value,
+        "Date" => tags.date = value,
+        "Round" => tags.round = value,
+        "White" => tags.white = value,
+        "Black" => tags.black = value,
+        "Result" => tags.result = value,
+        _ => {} // Other tags are ignored.
+    }
+}
+
+// Removes comments, variations and annotations glyphs, and splits the rest into tokens.
+fn tokenize_movetext(movetext: &str) -> Result<Vec<String>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut chars = movetext.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                chars
+                    .by_ref()
+                    .find(|&c| c == '}')
+                    .ok_or(PgnError::UnterminatedComment)?;
+            }
+            ';' => {
+                // Comment until the end of the line.
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '(' => {
+                // Variations can be nested.
+                let mut level = 1;
+                while level > 0 {
+                    match chars.next() {
+                        Some('(') => level += 1,
+                        Some(')') => level -= 1,
+                        Some(_) => {}
+                        None => return Err(PgnError::UnterminatedComment),
+                    }
+                }
+            }
+            _ if c.is_whitespace() => {}
+            _ => {
+                current.push(c);
+                continue;
+            }
+        }
+        if !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    // NAGs like $1.
+    tokens.retain(|token| !token.starts_with('$'));
+    Ok(tokens)
+}
+
+// Removes the check and annotation symbols, so that "Nf3+!?" and "Nf3" match.
+fn strip_san_suffix(san: &str) -> &str {
+    san.trim_end_matches(['+', '#', '!', '?'])
+}
+
+// Finds the legal move matching that SAN string.
+pub fn find_move(board: &Board, san: &str) -> Result<Move, PgnError> {
+    // Some PGNs use zeros for castling.
+    let san = strip_san_suffix(san).replace('0', "O");
+    board
+        .generate_legal_moves()
+        .into_iter()
+        .find(|mv| strip_san_suffix(&mv.to_san(board)) == san)
+        .ok_or(PgnError::InvalidMove(san))
+}
+
+// Parses a PGN string with a single game.
+// Returns the tags, the initial position and the moves.
+pub fn parse(pgn: &str) -> Result<(Tags, Board, Vec<Move>), PgnError> {
+    let mut tags = Tags::default();
+    let mut initial_board = Board::initial_board();
+    let mut movetext = String::new();
+    for line in pgn.lines() {
+        let line = line.trim();
+        if line.starts_with('[') && movetext.trim().is_empty() {
+            let (name, value) = parse_tag(line)?;
+            if name == "FEN" {
+                // The SetUp tag is redundant, we just use the FEN one if present.
+                initial_board = Board::try_from_fen(value).map_err(PgnError::InvalidFen)?;
+            } else {
+                set_tag(&mut tags, name, value);
+            }
+        } else {
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+    }
+
+    let mut board = initial_board;
+    let mut moves = Vec::new();
+    for token in tokenize_movetext(&movetext)? {
+        if RESULTS.contains(&token.as_str()) {
+            tags.result = token;
+            break;
+        }
+        // Move numbers, possibly attached to the move like "1.e4".
+        let san = match token.find(|c: char| !c.is_ascii_digit() && c != '.') {
+            Some(pos) if token[..pos].contains('.') => &token[pos..],
+            None if token.contains('.') => continue,
+            _ => &token,
+        };
+        let mv = find_move(&board, san)?;
+        board.update_by_move(mv);
+        moves.push(mv);
+    }
+
+    Ok((tags, initial_board, moves))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_default_tags() {
+        let pgn = create(&Tags::default(), &Board::initial_board(), &[]);
+        assert_eq!(
+            pgn,
+            r#"[Event "?"]
+[Site "?"]
+[Date "????.??.??"]
+[Round "?"]
+[White "?"]
+[Black "?"]
+[Result "*"]
+
+*
+"#
+        );
+    }
+
+    #[test]
+    fn test_create_from_position() {
+        let fen = "4k3/8/8/8/8/8/8/4K2R b K - 0 1";
+        let board: Board = fen.into();
+        let moves = [
+            board.new_move_from_pure("e8d7"),
+            Move::KING_TO_KING_SIDE_CASTLING[0],
+        ];
+        let pgn = create(&Tags::default(), &board, &moves);
+        assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/4K2R b K - 0 1\"]\n"));
+        assert!(pgn.ends_with("\n1... Kd7 2. O-O *\n"));
+
+        // Round trip.
+        assert_eq!(parse(&pgn), Ok((Tags::default(), board, moves.to_vec())));
+    }
+
+    #[test]
+    fn test_parse() {
+        let pgn = r#"[Event "F/S Return Match"]
+[Site "Belgrade, Serbia JUG"]
+[Date "1992.11.04"]
+[Round "29"]
+[White "Fischer, Robert J."]
+[Black "Spassky, Boris V."]
+[Result "1/2-1/2"]
+[ECO "C95"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 {This opening is called the Ruy Lopez.} 3... a6
+4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7
+11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15. Nb1 h6 16. Bh4 c5 17. dxe5
+Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 21. Nc4 Nxc4 22. Bxc4 Nb6
+23. Ne5 Rae8 24. Bxf7+ Rxf7 25. Nxf7 Rxe1+ 26. Qxe1 Kxf7 27. Qe3 Qg5 28. Qxg5
+hxg5 29. b3 Ke6 30. a3 Kd6 31. axb4 cxb4 32. Ra5 Nd5 33. f3 Bc8 34. Kf2 Bf5
+35. Ra7 g6 36. Ra6+ Kc5 37. Ke1 Nf4 38. g3 Nxh3 39. Kd2 Kb5 40. Rd6 Kc5 41. Ra6
+Nf2 42. g4 Bd3 43. Re6 1/2-1/2
+"#;
+        let (tags, board, moves) = parse(pgn).unwrap();
+        assert_eq!(tags.event, "F/S Return Match");
+        assert_eq!(tags.black, "Spassky, Boris V.");
+        assert_eq!(tags.result, "1/2-1/2");
+        assert_eq!(board, Board::initial_board());
+        assert_eq!(moves.len(), 85);
+        assert_eq!(moves[0], board.new_move_from_pure("e2e4"));
+        assert_eq!(moves[8], Move::KING_TO_KING_SIDE_CASTLING[0]);
+
+        // Round trip.
+        let (tags2, _, moves2) = parse(&create(&tags, &board, &moves)).unwrap();
+        assert_eq!(tags2, tags);
+        assert_eq!(moves2, moves);
+    }
+
+    #[test]
+    fn test_parse_movetext_variants() {
+        let (tags, _, moves) =
+            parse("1.e4 $1 e5 (1... c5 2. Nf3 (2. c3)) 2.Nf3 ; Comment\n Nc6 3. Bc4 Bc5 4. 0-0 *")
+                .unwrap();
+        assert_eq!(tags.result, "*");
+        assert_eq!(moves.len(), 7);
+        assert_eq!(moves[6], Move::KING_TO_KING_SIDE_CASTLING[0]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("[Event \"x\"\n1. e4"),
+            Err(PgnError::InvalidTag("[Event \"x\"".to_string()))
+        );
+        assert_eq!(
+            parse("1. e4 e5 2. Ke3"),
+            Err(PgnError::InvalidMove("Ke3".to_string()))
+        );
+        assert_eq!(
+            parse("1. e4 {Unterminated"),
+            Err(PgnError::UnterminatedComment)
+        );
+        assert_eq!(
+            parse("[FEN \"8/8/8/8/8/8/8 w - - 0 1\"]"),
+            Err(PgnError::InvalidFen(FenError::InvalidPiecePlacement))
+        );
+    }
+
+    #[test]
+    fn test_parse_random_input() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let valid = "[White \"a\"]\n1. e4 e5 2. Nf3 {x} Nc6 (2... d6) 3. Bb5 a6 4. O-O 1-0";
+        for _ in 0..1000 {
+            // Random bytes.
+            let len = rng.gen_range(0..64);
+            let bytes: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let _ = parse(&String::from_utf8_lossy(&bytes));
+
+            // Valid PGN with some bytes changed.
+            let mut bytes = valid.as_bytes().to_vec();
+            for _ in 0..rng.gen_range(1..4) {
+                let i = rng.gen_range(0..bytes.len());
+                bytes[i] = rng.gen();
+            }
+            let _ = parse(&String::from_utf8_lossy(&bytes));
+        }
+    }
+
+    #[test]
+    fn test_wrap_tokens() {
+        let tokens = vec!["abcdefghij".to_string(); 10];
+        let wrapped = wrap_tokens(&tokens);
+        let lines: Vec<&str> = wrapped.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 76); // 7 tokens with spaces.
+        assert_eq!(lines[1].len(), 32);
+    }
+}
@@ -0,0 +1,960 @@
+//! Search of the best move.
+//! Negamax with alpha-beta pruning <https://www.chessprogramming.org/Alpha-Beta#Negamax_Framework>
+//! Iterative deepening <https://www.chessprogramming.org/Iterative_Deepening>
+//! Quiescence search <https://www.chessprogramming.org/Quiescence_Search>
+//! Null move pruning <https://www.chessprogramming.org/Null_Move_Pruning>
+//! Late move reductions <https://www.chessprogramming.org/Late_Move_Reductions>
+//! Aspiration windows <https://www.chessprogramming.org/Aspiration_Windows>
+//! Triangular PV table <https://www.chessprogramming.org/Triangular_PV-Table>
+//! Transposition table <https://www.chessprogramming.org/Transposition_Table>
+
+mod config;
+mod info;
+mod ordering;
+mod parallel;
+mod time;
+
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use ordering::{MoveOrderer, MAX_PLY};
+
+use crate::{
+    board::{Board, GameHistory},
+    common::Piece,
+    eval::{evaluate, see},
+    moves::Move,
+    outputln,
+    tt::{Bound, TranspositionTable},
+};
+
+pub use config::SearchConfig;
+pub use info::SearchInfo;
+pub use parallel::search_parallel;
+pub use time::TimeControl;
+
+// Bigger than any score, used as initial alpha-beta window.
+pub const INFINITY: i32 = 32_000;
+// Score of being mated. Adjusted by the distance to the root, so that the shorter mates are preferred.
+pub const MATE_SCORE: i32 = 31_000;
+// Maximum depth of iterative deepening, when only limited by time.
+pub const MAX_DEPTH: u8 = 32;
+// How often the time is checked, in nodes.
+const TIME_CHECK_INTERVAL: u64 = 1024;
+
+// Result of an iterative deepening search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    // Depth of the last completed iteration.
+    pub depth: u8,
+    pub score: i32,
+    // Principal variation, empty if there are no legal moves.
+    pub pv: Vec<Move>,
+}
+
+impl SearchResult {
+    pub fn best_move(&self) -> Option<Move> {
+        self.pv.first().copied()
+    }
+}
+
+// State shared by all the nodes of a search.
+struct Searcher<'a> {
+    history: &'a mut GameHistory,
+    orderer: MoveOrderer,
+    config: &'a SearchConfig,
+    // How many times the score was outside the aspiration window.
+    aspiration_fails: u32,
+    // Triangular PV table: The row of each ply starts with the best move at that ply
+    // and continues with the best line found after it.
+    pv_table: Vec<[Option<Move>; MAX_PLY]>,
+    pv_length: [usize; MAX_PLY],
+    // Positions already searched, reached through other move orders or in previous iterations.
+    tt: TranspositionTable,
+    // Number of positions evaluated.
+    nodes: u64,
+    // When the search must stop, and if it did.
+    deadline: Option<Instant>,
+    // Set by another thread to stop the search.
+    stop_flag: Option<Arc<AtomicBool>>,
+    stopped: bool,
+}
+
+// Searches the board to the specified depth.
+// Returns the best score and the best move found, None if there are no legal moves.
+// The history must contain the positions of the game up to and including the board.
+pub fn search(
+    board: &mut Board,
+    history: &mut GameHistory,
+    config: &SearchConfig,
+    depth: u8,
+    alpha: i32,
+    beta: i32,
+) -> (i32, Option<Move>) {
+    let mut searcher = Searcher::new(history, config);
+    searcher.negamax(board, depth, 0, alpha, beta, None, true)
+}
+
+// Searches with increasing depths, until max_depth is reached or the time limit is exceeded.
+// The first iteration is always completed, so that we have a move to play.
+// After that, an iteration that exceeds the time limit is stopped and its result discarded.
+// After each iteration, an UCI info line is written to the info writer.
+pub fn iterative_deepening<W: Write>(
+    board: &mut Board,
+    history: &mut GameHistory,
+    config: &SearchConfig,
+    max_depth: u8,
+    time_limit: Option<Duration>,
+    info_writer: &mut W,
+) -> SearchResult {
+    let mut searcher = Searcher::new(history, config);
+    searcher.deepen(board, max_depth, time_limit, |_, info| {
+        outputln!(info_writer, "info {info}");
+    })
+}
+
+impl<'a> Searcher<'a> {
+    fn new(history: &'a mut GameHistory, config: &'a SearchConfig) -> Self {
+        Self {
+            history,
+            orderer: MoveOrderer::new(),
+            config,
+            aspiration_fails: 0,
+            pv_table: vec![[None; MAX_PLY]; MAX_PLY],
+            pv_length: [0; MAX_PLY],
+            tt: TranspositionTable::new(config.tt_size_mb),
+            nodes: 0,
+            deadline: None,
+            stop_flag: None,
+            stopped: false,
+        }
+    }
+
+    fn check_time(&mut self) {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() > deadline)
+            || self
+                .stop_flag
+                .as_ref()
+                .is_some_and(|stop| stop.load(Ordering::Relaxed))
+        {
+            self.stopped = true;
+        }
+    }
+
+    // The iterative deepening loop, calling on_iteration with the result of each completed iteration.
+    // Killers and history are kept between iterations.
+    fn deepen(
+        &mut self,
+        board: &mut Board,
+        max_depth: u8,
+        time_limit: Option<Duration>,
+        mut on_iteration: impl FnMut(&SearchResult, SearchInfo),
+    ) -> SearchResult {
+        let start = Instant::now();
+        let mut best = (0, None);
+        let mut result = SearchResult {
+            depth: 0,
+            score: 0,
+            pv: Vec::new(),
+        };
+        for depth in 1..=max_depth {
+            best = if depth == 1 {
+                self.negamax(board, depth, 0, -INFINITY, INFINITY, None, true)
+            } else {
+                self.aspiration_search(board, depth, best)
+            };
+            if self.stopped {
+                break;
+            }
+            result = SearchResult {
+                depth,
+                score: best.0,
+                pv: self.pv(),
+            };
+            let info = SearchInfo {
+                depth,
+                score: result.score,
+                nodes: self.nodes,
+                time: start.elapsed(),
+                pv: result.pv.clone(),
+            };
+            on_iteration(&result, info);
+
+            if best.1.is_none() || time_limit.is_some_and(|limit| start.elapsed() > limit) {
+                break;
+            }
+            self.deadline = time_limit.map(|limit| start + limit);
+        }
+        result
+    }
+
+    // The principal variation of the last search.
+    fn pv(&self) -> Vec<Move> {
+        self.pv_table[0][..self.pv_length[0]]
+            .iter()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    // The move is the new best at that ply, followed by the best line of the next ply.
+    fn update_pv(&mut self, ply: usize, mv: Move) {
+        if ply + 1 >= MAX_PLY {
+            return;
+        }
+        self.pv_table[ply][ply] = Some(mv);
+        let next_length = self.pv_length[ply + 1];
+        let (current, next) = self.pv_table.split_at_mut(ply + 1);
+        current[ply][ply + 1..next_length].copy_from_slice(&next[0][ply + 1..next_length]);
+        self.pv_length[ply] = next_length;
+    }
+
+    // Searches with a window around the previous iteration result, as the score shouldn't change much.
+    // If the score falls outside, the window is widened, and the second time we use the full window.
+    fn aspiration_search(
+        &mut self,
+        board: &mut Board,
+        depth: u8,
+        previous: (i32, Option<Move>),
+    ) -> (i32, Option<Move>) {
+        let (previous_score, pv_move) = previous;
+        let mut window = self.config.aspiration_window;
+        let mut widenings = 0;
+        loop {
+            let (alpha, beta) = if window > 0 && widenings < 2 {
+                (previous_score - window, previous_score + window)
+            } else {
+                (-INFINITY, INFINITY)
+            };
+            // Best move of the previous iteration is searched first.
+            let result = self.negamax(board, depth, 0, alpha, beta, pv_move, true);
+            if self.stopped {
+                return result;
+            }
+            let fail = if result.0 <= alpha && alpha > -INFINITY {
+                "fail low"
+            } else if result.0 >= beta && beta < INFINITY {
+                "fail high"
+            } else {
+                return result;
+            };
+            if self.config.log_aspiration {
+                info!("depth {depth} {fail} window [{alpha}, {beta}]");
+            }
+            self.aspiration_fails += 1;
+            widenings += 1;
+            window *= 4;
+        }
+    }
+
+    // Makes the move, searches the resulting position and undoes the move.
+    // Returns the score from the point of view of the side that made the move.
+    fn search_move(
+        &mut self,
+        board: &mut Board,
+        mv: Move,
+        depth: u8,
+        ply: u8,
+        alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        let state = board.make_move(mv);
+        self.history.push(board.hash());
+        let score = -self
+            .negamax(board, depth, ply + 1, -beta, -alpha, None, true)
+            .0;
+        self.history.pop();
+        board.unmake_move(mv, state);
+        score
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(
+        &mut self,
+        board: &mut Board,
+        depth: u8,
+        ply: u8,
+        mut alpha: i32,
+        beta: i32,
+        pv_move: Option<Move>,
+        is_null_move_allowed: bool,
+    ) -> (i32, Option<Move>) {
+        // The result doesn't matter, it will be discarded.
+        if self.stopped {
+            return (0, None);
+        }
+        if let Some(length) = self.pv_length.get_mut(ply as usize) {
+            *length = ply as usize;
+        }
+        let mut moves = board.generate_legal_moves();
+        if moves.is_empty() {
+            let score = if board.is_in_check() {
+                -MATE_SCORE + i32::from(ply)
+            } else {
+                0 // Stalemate
+            };
+            return (score, None);
+        }
+        // Checkmate has priority over the fifty-move rule, so this is checked after.
+        // At the root we still need a move to play.
+        // In the search, a single repetition is enough to consider the position a draw,
+        // as the side that could avoid it would have done it the first time.
+        if ply > 0 && (self.history.count(board.hash()) > 1 || board.is_drawn(self.history)) {
+            return (0, None);
+        }
+
+        if depth == 0 {
+            return (self.quiescence(board, alpha, beta), None);
+        }
+
+        // A previous search of this position at least as deep may be enough to return.
+        // Not at the root, as we need the PV and a move to play.
+        let entry = self.tt.probe(board.hash()).copied();
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth >= depth) {
+            let score = score_from_tt(entry.score, ply);
+            let cutoff = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+            if cutoff {
+                return (score, Some(entry.best_move));
+            }
+        }
+        // Otherwise its best move is tried first.
+        let pv_move = pv_move.or(entry.map(|entry| entry.best_move));
+
+        let in_check = board.is_in_check();
+
+        // Null move pruning: If passing the turn is still good enough for a cutoff, a real move will likely be too.
+        // Not done twice in a row, when in check, or with only pawns left, where zugzwang is common.
+        if is_null_move_allowed
+            && ply > 0
+            && depth > self.config.null_move_reduction
+            && !in_check
+            && has_pieces(board)
+        {
+            let state = board.make_null_move();
+            self.history.push(board.hash());
+            let score = -self
+                .negamax(
+                    board,
+                    depth - 1 - self.config.null_move_reduction,
+                    ply + 1,
+                    -beta,
+                    -beta + 1,
+                    None,
+                    false,
+                )
+                .0;
+            self.history.pop();
+            board.unmake_null_move(state);
+            if score >= beta {
+                return (beta, None);
+            }
+        }
+
+        self.orderer.score_moves(&mut moves, ply, pv_move);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for (index, mv) in moves.into_iter().enumerate() {
+            // Late move reduction: Quiet moves late in the ordering are first searched with a reduced depth
+            // and a null window. If they turn out to be better than alpha, they are searched again normally.
+            let reduce = !in_check
+                && !mv.is_capture()
+                && mv.get_promotion().is_none()
+                && self.config.can_reduce(index, depth);
+            let score = if reduce {
+                let reduced_depth = depth - 1 - self.config.lmr_reduction;
+                let score = self.search_move(board, mv, reduced_depth, ply, alpha, alpha + 1);
+                if score > alpha {
+                    self.search_move(board, mv, depth - 1, ply, alpha, beta)
+                } else {
+                    score
+                }
+            } else {
+                self.search_move(board, mv, depth - 1, ply, alpha, beta)
+            };
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv);
+            }
+            if score > alpha {
+                alpha = score;
+                self.update_pv(ply as usize, mv);
+            }
+            if alpha >= beta {
+                self.orderer.add_cutoff(mv, ply, depth);
+                break; // Beta cutoff
+            }
+        }
+
+        if let Some(mv) = best_move {
+            self.store_tt(board, depth, ply, (original_alpha, beta), best_score, mv);
+        }
+        (best_score, best_move)
+    }
+
+    // Stores the result of a node, with the bound given by the window it was searched with.
+    fn store_tt(
+        &mut self,
+        board: &Board,
+        depth: u8,
+        ply: u8,
+        (alpha, beta): (i32, i32),
+        score: i32,
+        mv: Move,
+    ) {
+        // The result of a stopped search is incomplete.
+        if self.stopped {
+            return;
+        }
+        let bound = if score >= beta {
+            Bound::Lower
+        } else if score > alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.tt
+            .store(board.hash(), depth, score_to_tt(score, ply), bound, mv);
+    }
+
+    // Searches only the captures, until the position is quiet.
+    // This avoids stopping the search in the middle of an exchange.
+    fn quiescence(&mut self, board: &mut Board, mut alpha: i32, beta: i32) -> i32 {
+        if self.stopped {
+            return 0;
+        }
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(TIME_CHECK_INTERVAL) {
+            self.check_time();
+        }
+        // Stand pat: The side to move isn't forced to capture.
+        let stand_pat = evaluate(board);
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        // Losing captures are skipped, the others are searched best first.
+        let mut captures: Vec<(i32, Move)> = board
+            .generate_captures()
+            .into_iter()
+            .map(|mv| (see(board, mv), mv))
+            .filter(|&(gain, _)| gain >= 0)
+            .collect();
+        captures.sort_by_key(|&(gain, _)| -gain);
+
+        let mut best_score = stand_pat;
+        for (_, mv) in captures {
+            let state = board.make_move(mv);
+            let score = -self.quiescence(board, -beta, -alpha);
+            board.unmake_move(mv, state);
+
+            best_score = best_score.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best_score
+    }
+}
+
+// Mate scores depend on the distance to the root. In the transposition table,
+// they are stored as the distance to the position instead, so that they can be used from any ply.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE_SCORE - MAX_PLY as i32
+}
+
+fn score_to_tt(score: i32, ply: u8) -> i32 {
+    if is_mate_score(score) {
+        score + score.signum() * i32::from(ply)
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: u8) -> i32 {
+    if is_mate_score(score) {
+        score - score.signum() * i32::from(ply)
+    } else {
+        score
+    }
+}
+
+// If the side to move has other pieces than pawns and king.
+fn has_pieces(board: &Board) -> bool {
+    let color = board.get_side_to_move();
+    [
+        Piece::get_knight_of(color),
+        Piece::get_bishop_of(color),
+        Piece::get_rook_of(color),
+        Piece::get_queen_of(color),
+    ]
+    .into_iter()
+    .any(|piece| board.pieces_of(piece) != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use itertools::Itertools;
+
+    use super::*;
+
+    fn assert_is_mate(board: &Board, mv: Move) {
+        let mut board = *board;
+        board.make_move(mv);
+        assert!(board.is_in_check());
+        assert!(board.generate_legal_moves().is_empty());
+    }
+
+    #[test]
+    fn test_mate_in_one() {
+        let mut board: Board = "k7/8/1K6/8/8/8/8/7R w - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        let (score, best_move) = search(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            1,
+            -INFINITY,
+            INFINITY,
+        );
+        assert_eq!(score, MATE_SCORE - 1);
+        assert_is_mate(&board, best_move.unwrap());
+
+        let mut board: Board = "r5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        let (score, best_move) = search(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            3,
+            -INFINITY,
+            INFINITY,
+        );
+        assert_eq!(score, MATE_SCORE - 1);
+        assert_is_mate(&board, best_move.unwrap());
+    }
+
+    #[test]
+    fn test_search_wins_material() {
+        // Black queen is hanging.
+        let mut board: Board = "4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        let (score, best_move) = search(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            2,
+            -INFINITY,
+            INFINITY,
+        );
+        assert_eq!(best_move, Some(board.new_move_from_pure("d1d5")));
+        assert!((450..550).contains(&score)); // Rook up
+    }
+
+    #[test]
+    fn test_iterative_deepening() {
+        let mut board: Board = "4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        let result = iterative_deepening(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            3,
+            None,
+            &mut io::sink(),
+        );
+        assert_eq!(result.depth, 3);
+        assert_eq!(result.best_move(), Some(board.new_move_from_pure("d1d5")));
+        assert!(result.score > 400);
+
+        // Same result as a direct search.
+        let mut board: Board = crate::fen::KIWIPETE.into();
+        let mut history = GameHistory::new(&board);
+        let original = board;
+        let result = iterative_deepening(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            2,
+            None,
+            &mut io::sink(),
+        );
+        assert_eq!(
+            (result.score, result.best_move()),
+            search(
+                &mut board,
+                &mut history,
+                &SearchConfig::default(),
+                2,
+                -INFINITY,
+                INFINITY
+            )
+        );
+        assert_eq!(board, original);
+
+        let mut board: Board = "R6k/6pp/8/8/8/8/8/6K1 b - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        let result = iterative_deepening(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            3,
+            None,
+            &mut io::sink(),
+        );
+        assert_eq!((result.score, result.best_move()), (-MATE_SCORE, None));
+    }
+
+    #[test]
+    fn test_iterative_deepening_info() {
+        let mut board: Board = crate::fen::KIWIPETE.into();
+        let mut history = GameHistory::new(&board);
+        let mut output = Vec::new();
+        let result = iterative_deepening(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            3,
+            None,
+            &mut output,
+        );
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+
+        let mut previous_nodes = 0;
+        for (line, depth) in lines.iter().zip(1..) {
+            let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+            assert_eq!(tokens[..3], ["info", "depth", &depth.to_string()]);
+            assert_eq!(tokens[3..5], ["score", "cp"]);
+            assert!(tokens[5].parse::<i32>().is_ok());
+            assert_eq!(tokens[6], "nodes");
+            let nodes: u64 = tokens[7].parse().unwrap();
+            assert!(nodes > previous_nodes);
+            previous_nodes = nodes;
+            assert_eq!(tokens[8], "time");
+            assert!(tokens[9].parse::<u128>().is_ok());
+            assert_eq!(tokens[10], "pv");
+            assert!(board
+                .generate_legal_moves()
+                .contains(&board.new_move_from_pure(tokens[11])));
+        }
+        assert!(lines[2].ends_with(&result.pv.iter().map(Move::pure).join(" ")));
+    }
+
+    #[test]
+    fn test_iterative_deepening_time_limit() {
+        // Time limit already exceeded after the first iteration.
+        let mut board = Board::initial_board();
+        let mut history = GameHistory::new(&board);
+        let result = iterative_deepening(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            20,
+            Some(Duration::ZERO),
+            &mut io::sink(),
+        );
+        assert_eq!(result.depth, 1);
+        assert!(result.best_move().is_some());
+    }
+
+    #[test]
+    fn test_iterative_deepening_stops_in_iteration() {
+        let mut board: Board = crate::fen::KIWIPETE.into();
+        let mut history = GameHistory::new(&board);
+        let config = SearchConfig::default();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut searcher = Searcher::new(&mut history, &config);
+        searcher.stop_flag = Some(Arc::clone(&stop));
+        let mut iterations = Vec::new();
+        // Stopped from the start of the depth 4 iteration, which has many more nodes than the check interval.
+        let result = searcher.deepen(&mut board, MAX_DEPTH, None, |result, _| {
+            iterations.push(result.depth);
+            if result.depth == 3 {
+                stop.store(true, Ordering::Relaxed);
+            }
+        });
+        // The interrupted iteration is discarded.
+        assert_eq!(iterations, [1, 2, 3]);
+        assert_eq!(result.depth, 3);
+        assert_pv_is_legal(&board, &result.pv);
+    }
+
+    #[test]
+    fn test_transposition_table() {
+        let mut board: Board = crate::fen::KIWIPETE.into();
+        let mut history = GameHistory::new(&board);
+        let config = SearchConfig::default();
+        let mut searcher = Searcher::new(&mut history, &config);
+        let first = searcher.negamax(&mut board, 4, 0, -INFINITY, INFINITY, None, true);
+        let first_nodes = searcher.nodes;
+        assert!(searcher
+            .tt
+            .probe(board.hash())
+            .is_some_and(|entry| entry.depth == 4
+                && entry.bound == Bound::Exact
+                && Some(entry.best_move) == first.1));
+
+        // Same search again, the positions after the root moves are found in the table.
+        let second = searcher.negamax(&mut board, 4, 0, -INFINITY, INFINITY, None, true);
+        assert_eq!(second.0, first.0);
+        assert!(searcher.nodes - first_nodes < first_nodes / 10);
+    }
+
+    #[test]
+    fn test_mate_score_tt() {
+        // Mated 5 plies from the root, found from a position at ply 2.
+        let score = -MATE_SCORE + 5;
+        assert_eq!(score_to_tt(score, 2), -MATE_SCORE + 3);
+        // Same position reached at ply 4, it's now mated 7 plies from the root.
+        assert_eq!(score_from_tt(score_to_tt(score, 2), 4), -MATE_SCORE + 7);
+        assert_eq!(
+            score_from_tt(score_to_tt(MATE_SCORE - 5, 2), 4),
+            MATE_SCORE - 7
+        );
+        assert_eq!(score_to_tt(150, 2), 150);
+        assert_eq!(score_from_tt(-150, 2), -150);
+    }
+
+    #[test]
+    fn test_quiescence() {
+        let config = SearchConfig::default();
+        // Quiet position, nothing to capture.
+        let mut board = Board::initial_board();
+        let mut history = GameHistory::new(&board);
+        let mut searcher = Searcher::new(&mut history, &config);
+        assert_eq!(
+            searcher.quiescence(&mut board, -INFINITY, INFINITY),
+            evaluate(&board)
+        );
+        assert_eq!(searcher.nodes, 1);
+
+        // The queen took a pawn defended by another pawn.
+        let mut board: Board = "4k3/8/4p3/3Q4/8/8/8/4K3 b - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        let mut searcher = Searcher::new(&mut history, &config);
+        assert!(evaluate(&board) < -800);
+        assert!(searcher.quiescence(&mut board, -INFINITY, INFINITY) > 0);
+        assert_eq!(board, "4k3/8/4p3/3Q4/8/8/8/4K3 b - - 0 1".into());
+    }
+
+    #[test]
+    fn test_search_avoids_defended_pawn() {
+        // Without quiescence, taking the pawn looks good at depth 1.
+        let mut board: Board = "4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        let (score, best_move) = search(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            1,
+            -INFINITY,
+            INFINITY,
+        );
+        assert_ne!(best_move, Some(board.new_move_from_pure("d1d5")));
+        assert!(score > 600);
+    }
+
+    #[test]
+    fn test_search_restores_board() {
+        let mut board: Board = crate::fen::KIWIPETE.into();
+        let mut history = GameHistory::new(&board);
+        let original = board;
+        search(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            2,
+            -INFINITY,
+            INFINITY,
+        );
+        assert_eq!(board, original);
+    }
+
+    #[test]
+    fn test_no_legal_moves() {
+        // Checkmated.
+        let mut board: Board = "R6k/6pp/8/8/8/8/8/6K1 b - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        assert_eq!(
+            search(
+                &mut board,
+                &mut history,
+                &SearchConfig::default(),
+                2,
+                -INFINITY,
+                INFINITY
+            ),
+            (-MATE_SCORE, None)
+        );
+
+        // Stalemate.
+        let mut board: Board = "k7/8/1Q6/8/8/8/8/7K b - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        assert_eq!(
+            search(
+                &mut board,
+                &mut history,
+                &SearchConfig::default(),
+                2,
+                -INFINITY,
+                INFINITY
+            ),
+            (0, None)
+        );
+    }
+
+    #[test]
+    fn test_search_repetition_is_draw() {
+        // White is a queen up, but all the positions after its moves were already seen.
+        let mut board: Board = "4k3/8/8/8/8/8/8/3QK3 w - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        for mv in board.generate_legal_moves() {
+            history.push(board.copy_with_move(mv).unwrap().hash());
+        }
+        history.push(board.hash());
+        assert_eq!(
+            search(
+                &mut board,
+                &mut history,
+                &SearchConfig::default(),
+                3,
+                -INFINITY,
+                INFINITY
+            )
+            .0,
+            0
+        );
+    }
+
+    #[test]
+    fn test_has_pieces() {
+        assert!(has_pieces(&Board::initial_board()));
+        // Only pawns for white, black has a knight.
+        let board: Board = "4k3/4p3/8/8/8/2n5/4P3/4K3 w - - 0 1".into();
+        assert!(!has_pieces(&board));
+        let board: Board = "4k3/4p3/8/8/8/2n5/4P3/4K3 b - - 0 1".into();
+        assert!(has_pieces(&board));
+    }
+
+    #[test]
+    fn test_search_with_null_move_finds_mate() {
+        // Mate in 2 starting with a quiet move: Kb6 Kb8 Rh8#
+        let mut board: Board = "k7/8/2K5/8/8/8/8/7R w - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        let (score, _) = search(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            4,
+            -INFINITY,
+            INFINITY,
+        );
+        assert_eq!(score, MATE_SCORE - 3);
+    }
+
+    #[test]
+    fn test_lmr_finds_same_mate() {
+        // Mate in 3: Rh7 Kb8 Kb6 Kc8 (or Ka8) Rh8#
+        let board: Board = "k7/8/3K4/8/8/8/8/7R w - - 0 1".into();
+        let no_lmr = SearchConfig {
+            lmr_reduction: 0,
+            ..SearchConfig::default()
+        };
+        let mut results = Vec::new();
+        for config in [SearchConfig::default(), no_lmr] {
+            let mut board = board;
+            let mut history = GameHistory::new(&board);
+            results.push(search(&mut board, &mut history, &config, 6, -INFINITY, INFINITY).0);
+        }
+        assert_eq!(results[0], results[1]);
+        assert!(results[0] >= MATE_SCORE - 5);
+    }
+
+    #[test]
+    fn test_aspiration_window_fails() {
+        let config = SearchConfig {
+            log_aspiration: true,
+            ..SearchConfig::default()
+        };
+
+        // Actual score is much higher than the previous one, so it fails high twice.
+        let mut board: Board = "4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        let mut searcher = Searcher::new(&mut history, &config);
+        let (score, best_move) = searcher.aspiration_search(&mut board, 2, (0, None));
+        assert_eq!(searcher.aspiration_fails, 2);
+        assert_eq!(best_move, Some(board.new_move_from_pure("d1d5")));
+        assert!(score > 400);
+
+        // Around the right score, no re-search is needed.
+        let mut searcher = Searcher::new(&mut history, &config);
+        let result = searcher.aspiration_search(&mut board, 2, (score, best_move));
+        assert_eq!(searcher.aspiration_fails, 0);
+        assert_eq!(result, (score, best_move));
+    }
+
+    fn assert_pv_is_legal(board: &Board, pv: &[Move]) {
+        let mut board = *board;
+        for &mv in pv {
+            assert!(board.generate_legal_moves().contains(&mv), "{mv} not legal");
+            board.update_by_move(mv);
+        }
+    }
+
+    #[test]
+    fn test_pv_is_legal() {
+        let mut board: Board = crate::fen::KIWIPETE.into();
+        let mut history = GameHistory::new(&board);
+        let result = iterative_deepening(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            4,
+            None,
+            &mut io::sink(),
+        );
+        assert!(!result.pv.is_empty());
+        assert_pv_is_legal(&board, &result.pv);
+
+        // Mate in 2, the PV goes until the mate.
+        let mut board: Board = "k7/8/2K5/8/8/8/8/7R w - - 0 1".into();
+        let mut history = GameHistory::new(&board);
+        let result = iterative_deepening(
+            &mut board,
+            &mut history,
+            &SearchConfig::default(),
+            4,
+            None,
+            &mut io::sink(),
+        );
+        assert_eq!(result.score, MATE_SCORE - 3);
+        assert_eq!(result.pv.len(), 3);
+        assert_pv_is_legal(&board, &result.pv);
+        let mut mated = board;
+        for &mv in &result.pv {
+            mated.update_by_move(mv);
+        }
+        assert!(mated.is_checkmate());
+    }
+}
@@ -0,0 +1,68 @@
+//! Parameters of the search, so that they can be tuned without changing the code.
+
+use crate::tt::TranspositionTable;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchConfig {
+    // Depth reduction of the null move search.
+    pub null_move_reduction: u8,
+    // Late move reductions: The moves after the first ones are searched with a reduced depth.
+    // <https://www.chessprogramming.org/Late_Move_Reductions>
+    // How many moves are searched at full depth before reducing.
+    pub lmr_full_depth_moves: usize,
+    // Minimum remaining depth for reducing.
+    pub lmr_min_depth: u8,
+    // By how much the depth is reduced, 0 to disable LMR.
+    pub lmr_reduction: u8,
+    // Initial half size of the aspiration window in centipawns, 0 to always search with a full window.
+    // <https://www.chessprogramming.org/Aspiration_Windows>
+    pub aspiration_window: i32,
+    // Logs when the score falls outside of the aspiration window.
+    pub log_aspiration: bool,
+    // Size of the transposition table in MB.
+    pub tt_size_mb: usize,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            null_move_reduction: 2,
+            lmr_full_depth_moves: 3,
+            lmr_min_depth: 3,
+            lmr_reduction: 1,
+            aspiration_window: 50,
+            log_aspiration: false,
+            tt_size_mb: TranspositionTable::DEFAULT_SIZE_MB,
+        }
+    }
+}
+
+impl SearchConfig {
+    // Checks if the move at that index in the ordered list can be searched with a reduced depth.
+    pub fn can_reduce(&self, move_index: usize, depth: u8) -> bool {
+        self.lmr_reduction > 0
+            && move_index >= self.lmr_full_depth_moves
+            && depth >= self.lmr_min_depth
+            && depth > self.lmr_reduction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_reduce() {
+        let config = SearchConfig::default();
+        assert!(!config.can_reduce(0, 5));
+        assert!(!config.can_reduce(2, 5));
+        assert!(config.can_reduce(3, 5));
+        assert!(!config.can_reduce(3, 2));
+
+        let no_lmr = SearchConfig {
+            lmr_reduction: 0,
+            ..SearchConfig::default()
+        };
+        assert!(!no_lmr.can_reduce(10, 10));
+    }
+}
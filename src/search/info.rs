@@ -0,0 +1,88 @@
+//! Search progress, as reported to the UI with the UCI info command.
+
+use std::{fmt::Display, time::Duration};
+
+use itertools::Itertools;
+
+use crate::moves::Move;
+
+use super::{MATE_SCORE, MAX_PLY};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchInfo {
+    pub depth: u8,
+    pub score: i32,
+    // Number of positions evaluated.
+    pub nodes: u64,
+    pub time: Duration,
+    pub pv: Vec<Move>,
+}
+
+// Number of moves until mate, negative if we are getting mated. None if the score isn't a mate.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn mate_in(score: i32) -> Option<i32> {
+    let plies = MATE_SCORE - score.abs();
+    if plies > MAX_PLY as i32 {
+        return None;
+    }
+    Some(if score > 0 {
+        (plies + 1) / 2
+    } else {
+        -plies / 2
+    })
+}
+
+// Formatted as the parameters of an info command, like "depth 5 score cp 30 nodes 1234 time 40 pv e2e4 e7e5".
+impl Display for SearchInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "depth {} score ", self.depth)?;
+        if let Some(moves) = mate_in(self.score) {
+            write!(f, "mate {moves}")?;
+        } else {
+            write!(f, "cp {}", self.score)?;
+        }
+        write!(
+            f,
+            " nodes {} time {} pv {}",
+            self.nodes,
+            self.time.as_millis(),
+            self.pv.iter().map(Move::pure).join(" ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{Piece::*, Square::*};
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let info = SearchInfo {
+            depth: 2,
+            score: 30,
+            nodes: 1234,
+            time: Duration::from_millis(56),
+            pv: vec![
+                Move::quiet(E2, E4, WhitePawn),
+                Move::quiet(E7, E5, BlackPawn),
+            ],
+        };
+        assert_eq!(
+            info.to_string(),
+            "depth 2 score cp 30 nodes 1234 time 56 pv e2e4 e7e5"
+        );
+    }
+
+    #[test]
+    fn test_mate_in() {
+        assert_eq!(mate_in(0), None);
+        assert_eq!(mate_in(-500), None);
+        assert_eq!(mate_in(MATE_SCORE - 1), Some(1));
+        assert_eq!(mate_in(MATE_SCORE - 3), Some(2));
+        // Mated in the current position, or after our next move.
+        assert_eq!(mate_in(-MATE_SCORE), Some(0));
+        assert_eq!(mate_in(-MATE_SCORE + 2), Some(-1));
+    }
+}
@@ -0,0 +1,140 @@
+//! Move ordering, so that alpha-beta gets more cutoffs.
+//! <https://www.chessprogramming.org/Move_Ordering>
+
+use crate::moves::Move;
+
+// Maximum search depth supported by the killer table.
+pub const MAX_PLY: usize = 64;
+
+// Scores used to put the categories in the right order.
+const PV_MOVE_SCORE: i32 = 3_000_000;
+const CAPTURE_SCORE: i32 = 2_000_000;
+const KILLER_SCORE: i32 = 1_000_000;
+const UNDERPROMOTION_SCORE: i32 = -1_000_000;
+
+pub struct MoveOrderer {
+    // Quiet moves that caused a beta cutoff, 2 per ply.
+    // <https://www.chessprogramming.org/Killer_Heuristic>
+    killers: [[Option<Move>; 2]; MAX_PLY],
+    // Indexed by from and to squares.
+    // <https://www.chessprogramming.org/History_Heuristic>
+    history: [[i32; 64]; 64],
+}
+
+// Most Valuable Victim - Least Valuable Attacker.
+// <https://www.chessprogramming.org/MVV-LVA>
+fn mvv_lva(mv: Move) -> i32 {
+    let victim = mv.get_captured_piece().unwrap().piece_type();
+    let attacker = mv.get_piece().piece_type();
+    i32::from(victim as u8) * 10 - i32::from(attacker as u8)
+}
+
+impl MoveOrderer {
+    pub fn new() -> Self {
+        Self {
+            killers: [[None; 2]; MAX_PLY],
+            history: [[0; 64]; 64],
+        }
+    }
+
+    fn score_move(&self, mv: Move, ply: u8, pv_move: Option<Move>) -> i32 {
+        if pv_move == Some(mv) {
+            PV_MOVE_SCORE
+        } else if mv.is_underpromotion() {
+            UNDERPROMOTION_SCORE
+        } else if mv.is_capture() {
+            CAPTURE_SCORE + mvv_lva(mv)
+        } else if self
+            .killers
+            .get(ply as usize)
+            .is_some_and(|killers| killers.contains(&Some(mv)))
+        {
+            KILLER_SCORE
+        } else {
+            self.history[mv.get_from() as usize][mv.get_to() as usize]
+        }
+    }
+
+    // Sorts the moves so that the most promising ones are first:
+    // PV move, captures by MVV-LVA, killers, quiet moves by history, and finally underpromotions.
+    pub fn score_moves(&self, moves: &mut [Move], ply: u8, pv_move: Option<Move>) {
+        moves.sort_by_cached_key(|&mv| -self.score_move(mv, ply, pv_move));
+    }
+
+    // Records a quiet move that caused a beta cutoff.
+    pub fn add_cutoff(&mut self, mv: Move, ply: u8, depth: u8) {
+        if mv.is_capture() {
+            return;
+        }
+        if let Some(killers) = self.killers.get_mut(ply as usize) {
+            if killers[0] != Some(mv) {
+                killers[1] = killers[0];
+                killers[0] = Some(mv);
+            }
+        }
+        let history = &mut self.history[mv.get_from() as usize][mv.get_to() as usize];
+        // Saturating, in case we search for a very long time.
+        *history = history.saturating_add(i32::from(depth) * i32::from(depth));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::Board,
+        common::{Piece::*, Square::*},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_captures_first_by_mvv_lva() {
+        // Pawn and queen can both capture the rook.
+        let board: Board = "4k3/8/8/3r4/2P1p3/8/8/3QK3 w - - 0 1".into();
+        let mut moves = board.generate_legal_moves();
+        let orderer = MoveOrderer::new();
+        orderer.score_moves(&mut moves, 0, None);
+
+        assert_eq!(moves[0], Move::capture(C4, D5, WhitePawn, BlackRook));
+        assert_eq!(moves[1], Move::capture(D1, D5, WhiteQueen, BlackRook));
+        assert!(moves[2..].iter().all(|mv| !mv.is_capture()));
+    }
+
+    #[test]
+    fn test_underpromotions_last() {
+        let board: Board = "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1".into();
+        let mut moves = board.generate_legal_moves();
+        let orderer = MoveOrderer::new();
+        orderer.score_moves(&mut moves, 0, None);
+
+        assert_eq!(
+            moves[0],
+            Move::new(A7, B8, Some(WhiteQueen), WhitePawn, Some(BlackRook))
+        );
+        let count = moves.len();
+        assert!(moves[count - 6..].iter().all(|mv| mv.is_underpromotion()));
+        assert!(moves[..count - 6].iter().all(|mv| !mv.is_underpromotion()));
+    }
+
+    #[test]
+    fn test_pv_and_killers() {
+        let board = Board::initial_board();
+        let mut moves = board.generate_legal_moves();
+        let mut orderer = MoveOrderer::new();
+
+        let killer = Move::quiet(G1, F3, WhiteKnight);
+        orderer.add_cutoff(killer, 1, 3);
+        let pv_move = Move::quiet(E2, E4, WhitePawn);
+
+        orderer.score_moves(&mut moves, 1, Some(pv_move));
+        assert_eq!(moves[0], pv_move);
+        assert_eq!(moves[1], killer);
+
+        // Killers are per ply, but history is shared.
+        let other = Move::quiet(B1, C3, WhiteKnight);
+        orderer.add_cutoff(other, 2, 1);
+        orderer.score_moves(&mut moves, 0, None);
+        assert_eq!(moves[0], killer);
+        assert_eq!(moves[1], other);
+    }
+}
@@ -0,0 +1,229 @@
+//! Search on several threads.
+//! <https://www.chessprogramming.org/Parallel_Search>
+//!
+//! For now each thread runs its own independent iterative deepening, and we keep the deepest result.
+//! The threads only share the stop flag. Lazy SMP would be the next step: Sharing the transposition
+//! table between the threads, and having them search different depths so that they help each other.
+//! <https://www.chessprogramming.org/Lazy_SMP>
+
+use std::{
+    io::Write,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    board::{Board, GameHistory},
+    outputln,
+};
+
+use super::{is_mate_score, SearchConfig, SearchInfo, SearchResult, Searcher};
+
+// Searches on as many threads as there are CPUs, until max_depth is reached by all of them,
+// one of them finds a mate, or the time limit is exceeded.
+// Like iterative_deepening, we wait for the first iteration to be completed even if the time is exceeded.
+// An UCI info line is written to the info writer the first time each depth is completed.
+// Threads that panic are ignored, if all of them did the result is empty.
+pub fn search_parallel<W: Write>(
+    board: &Board,
+    history: &GameHistory,
+    config: &SearchConfig,
+    max_depth: u8,
+    time_limit: Option<Duration>,
+    info_writer: &mut W,
+) -> SearchResult {
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    search_on_threads(
+        board,
+        history,
+        config,
+        max_depth,
+        time_limit,
+        threads,
+        info_writer,
+    )
+}
+
+fn search_on_threads<W: Write>(
+    board: &Board,
+    history: &GameHistory,
+    config: &SearchConfig,
+    max_depth: u8,
+    time_limit: Option<Duration>,
+    threads: usize,
+    info_writer: &mut W,
+) -> SearchResult {
+    let start = Instant::now();
+    let stop = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel::<(SearchResult, SearchInfo)>();
+    // The threads search the same depths, only the first one to complete a depth is reported.
+    let mut reported_depth = 0;
+    let mut report = |info: SearchInfo| {
+        if info.depth > reported_depth {
+            reported_depth = info.depth;
+            outputln!(info_writer, "info {info}");
+        }
+    };
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let stop = Arc::clone(&stop);
+                let sender = sender.clone();
+                let mut board = *board;
+                let mut history = history.clone();
+                scope.spawn(move || {
+                    let mut searcher = Searcher::new(&mut history, config);
+                    searcher.stop_flag = Some(stop);
+                    searcher.deepen(&mut board, max_depth, None, |result, info| {
+                        // The main thread may be gone already.
+                        let _ = sender.send((result.clone(), info));
+                    })
+                })
+            })
+            .collect();
+        // So that the channel is closed when all the threads are done.
+        drop(sender);
+
+        // Until the first iteration is completed, we wait without a timeout.
+        let mut timeout = Duration::MAX;
+        while let Ok((result, info)) = receiver.recv_timeout(timeout) {
+            report(info);
+            let elapsed = start.elapsed();
+            // No point in searching longer once a mate is found.
+            if is_mate_score(result.score) || time_limit.is_some_and(|limit| elapsed > limit) {
+                break;
+            }
+            timeout = time_limit.map_or(Duration::MAX, |limit| limit.saturating_sub(elapsed));
+        }
+        stop.store(true, Ordering::Relaxed);
+
+        let result = handles
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, handle)| {
+                handle
+                    .join()
+                    .inspect_err(|_| error!("Search thread {index} panicked"))
+                    .ok()
+            })
+            // The first of the deepest results.
+            .rev()
+            .max_by_key(|result| result.depth)
+            .unwrap_or(SearchResult {
+                depth: 0,
+                score: 0,
+                pv: Vec::new(),
+            });
+        // Iterations completed before the threads saw the stop flag, including the one of the result.
+        for (_, info) in receiver.try_iter() {
+            report(info);
+        }
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search::{iterative_deepening, MATE_SCORE, MAX_DEPTH};
+
+    use super::*;
+
+    #[test]
+    fn test_search_parallel_finds_mate() {
+        // Mate in 2: Kb6 Kb8 Rh8#
+        let board: Board = "k7/8/2K5/8/8/8/8/7R w - - 0 1".into();
+        let history = GameHistory::new(&board);
+        let result = search_on_threads(
+            &board,
+            &history,
+            &SearchConfig::default(),
+            20,
+            None,
+            4,
+            &mut std::io::sink(),
+        );
+        // Stopped once the mate was found, without searching until depth 20.
+        assert_eq!(result.score, MATE_SCORE - 3);
+        assert!(result.depth < 20);
+        assert_eq!(result.pv.len(), 3);
+    }
+
+    #[test]
+    fn test_search_parallel_max_depth() {
+        let mut board: Board = "4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1".into();
+        let history = GameHistory::new(&board);
+        let result = search_parallel(
+            &board,
+            &history,
+            &SearchConfig::default(),
+            3,
+            None,
+            &mut std::io::sink(),
+        );
+        assert_eq!(result.depth, 3);
+        assert_eq!(result.best_move(), Some(board.new_move_from_pure("d1d5")));
+        // Same as a single threaded search.
+        let expected = iterative_deepening(
+            &mut board,
+            &mut history.clone(),
+            &SearchConfig::default(),
+            3,
+            None,
+            &mut std::io::sink(),
+        );
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_search_parallel_time_limit() {
+        let board: Board = crate::fen::KIWIPETE.into();
+        let history = GameHistory::new(&board);
+        let result = search_on_threads(
+            &board,
+            &history,
+            &SearchConfig::default(),
+            MAX_DEPTH,
+            Some(Duration::from_millis(50)),
+            2,
+            &mut std::io::sink(),
+        );
+        // Stopped by the time limit, reaching the maximum depth would take forever.
+        assert!(result.depth >= 1 && result.depth < MAX_DEPTH);
+        assert!(result.best_move().is_some());
+
+        // The first iteration is completed even when the time is already exceeded.
+        let result = search_on_threads(
+            &board,
+            &history,
+            &SearchConfig::default(),
+            MAX_DEPTH,
+            Some(Duration::ZERO),
+            2,
+            &mut std::io::sink(),
+        );
+        assert!(result.depth >= 1);
+        assert!(result.best_move().is_some());
+    }
+
+    #[test]
+    fn test_search_parallel_no_moves() {
+        let board: Board = "R6k/6pp/8/8/8/8/8/6K1 b - - 0 1".into();
+        let history = GameHistory::new(&board);
+        let result = search_on_threads(
+            &board,
+            &history,
+            &SearchConfig::default(),
+            5,
+            None,
+            2,
+            &mut std::io::sink(),
+        );
+        assert_eq!((result.score, result.best_move()), (-MATE_SCORE, None));
+    }
+}
@@ -0,0 +1,112 @@
+//! Time management: How long to search, based on the clock parameters of the UCI go command.
+//! <https://www.chessprogramming.org/Time_Management>
+
+use std::time::Duration;
+
+use crate::common::Color;
+
+use super::MAX_DEPTH;
+
+// Number of moves we plan for when the remaining moves until the next time control is unknown.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimeControl {
+    // Remaining time on the clocks, indexed by color.
+    pub remaining: [Option<Duration>; 2],
+    // Increment per move, indexed by color.
+    pub increment: [Duration; 2],
+    // Moves until the next time control.
+    pub moves_to_go: Option<u32>,
+    // Exact time to search.
+    pub move_time: Option<Duration>,
+    // Fixed depth to search.
+    pub depth: Option<u8>,
+}
+
+impl TimeControl {
+    pub fn fixed_depth(depth: u8) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+
+    // Depth to search until, at least 1 as the first iteration is needed to get a move.
+    pub fn max_depth(&self) -> u8 {
+        self.depth.unwrap_or(MAX_DEPTH).max(1)
+    }
+
+    // How long the side to move can search, None if there is no time limit.
+    // We never use more than half of the remaining time, so that there is always some left for the next moves.
+    pub fn allocate_time(&self, color: Color) -> Option<Duration> {
+        if self.move_time.is_some() {
+            return self.move_time;
+        }
+        let remaining = self.remaining[color as usize]?;
+        let moves_to_go = self.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let budget = remaining / moves_to_go + self.increment[color as usize];
+        Some(budget.min(remaining / 2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn test_max_depth() {
+        assert_eq!(TimeControl::default().max_depth(), MAX_DEPTH);
+        assert_eq!(TimeControl::fixed_depth(5).max_depth(), 5);
+        assert_eq!(TimeControl::fixed_depth(0).max_depth(), 1);
+    }
+
+    #[test]
+    fn test_allocate_time() {
+        assert_eq!(TimeControl::default().allocate_time(Color::White), None);
+        assert_eq!(
+            TimeControl::fixed_depth(5).allocate_time(Color::White),
+            None
+        );
+
+        let time_control = TimeControl {
+            remaining: [Some(secs(60)), Some(secs(30))],
+            ..Default::default()
+        };
+        assert_eq!(time_control.allocate_time(Color::White), Some(secs(2)));
+        assert_eq!(time_control.allocate_time(Color::Black), Some(secs(1)));
+
+        let time_control = TimeControl {
+            remaining: [Some(secs(60)), Some(secs(60))],
+            increment: [secs(1), secs(0)],
+            moves_to_go: Some(40),
+            ..Default::default()
+        };
+        assert_eq!(
+            time_control.allocate_time(Color::White),
+            Some(Duration::from_millis(2500))
+        );
+        assert_eq!(
+            time_control.allocate_time(Color::Black),
+            Some(Duration::from_millis(1500))
+        );
+
+        // Last move before the time control, keeps some time anyway.
+        let time_control = TimeControl {
+            remaining: [Some(secs(10)), None],
+            moves_to_go: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(time_control.allocate_time(Color::White), Some(secs(5)));
+
+        let time_control = TimeControl {
+            move_time: Some(secs(3)),
+            ..Default::default()
+        };
+        assert_eq!(time_control.allocate_time(Color::Black), Some(secs(3)));
+    }
+}
@@ -0,0 +1,148 @@
+//! Transposition table.
+//! <https://www.chessprogramming.org/Transposition_Table>
+
+use crate::moves::Move;
+
+// How the score stored relates to the real score of the position.
+// <https://www.chessprogramming.org/Node_Types>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    // PV-node, the score is exact.
+    Exact,
+    // Cut-node, a beta cutoff happened so the real score is at least this.
+    Lower,
+    // All-node, no move raised alpha so the real score is at most this.
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TTEntry {
+    pub hash: u64,
+    pub depth: u8,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Move,
+}
+
+pub struct TranspositionTable {
+    entries: Vec<Option<TTEntry>>,
+}
+
+impl TranspositionTable {
+    pub const DEFAULT_SIZE_MB: usize = 16;
+
+    // Creates a table using at most the specified memory size.
+    pub fn new(size_mb: usize) -> Self {
+        let max_entries = size_mb * 1024 * 1024 / std::mem::size_of::<Option<TTEntry>>();
+        // Power of two size, so that the index is a bit mask of the hash.
+        let capacity = if max_entries == 0 {
+            1
+        } else {
+            1 << max_entries.ilog2()
+        };
+        Self {
+            entries: vec![None; capacity],
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn index(&self, hash: u64) -> usize {
+        hash as usize & (self.entries.len() - 1)
+    }
+
+    // Returns the entry for this position, if there is one.
+    pub fn probe(&self, hash: u64) -> Option<&TTEntry> {
+        self.entries[self.index(hash)]
+            .as_ref()
+            .filter(|entry| entry.hash == hash)
+    }
+
+    // Stores an entry, replacing the existing one unless it's for the same position with a deeper search.
+    pub fn store(&mut self, hash: u64, depth: u8, score: i32, bound: Bound, best_move: Move) {
+        let index = self.index(hash);
+        if let Some(entry) = &self.entries[index] {
+            if entry.hash == hash && entry.depth > depth {
+                return;
+            }
+        }
+        self.entries[index] = Some(TTEntry {
+            hash,
+            depth,
+            score,
+            bound,
+            best_move,
+        });
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_SIZE_MB)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{Piece::*, Square::*};
+
+    use super::*;
+
+    #[test]
+    fn test_capacity_is_power_of_two() {
+        let tt = TranspositionTable::new(1);
+        assert!(tt.capacity().is_power_of_two());
+        assert!(tt.capacity() * std::mem::size_of::<Option<TTEntry>>() <= 1024 * 1024);
+
+        assert_eq!(TranspositionTable::new(0).capacity(), 1);
+    }
+
+    #[test]
+    fn test_store_probe() {
+        let mut tt = TranspositionTable::new(1);
+        let mv = Move::quiet(E2, E4, WhitePawn);
+        assert!(tt.probe(1234).is_none());
+
+        tt.store(1234, 3, 50, Bound::Exact, mv);
+        let entry = tt.probe(1234).unwrap();
+        assert_eq!(entry.depth, 3);
+        assert_eq!(entry.score, 50);
+        assert_eq!(entry.bound, Bound::Exact);
+        assert_eq!(entry.best_move, mv);
+
+        // Same index, different position.
+        let other_hash = 1234 + tt.capacity() as u64;
+        assert!(tt.probe(other_hash).is_none());
+
+        tt.clear();
+        assert!(tt.probe(1234).is_none());
+    }
+
+    #[test]
+    fn test_store_replacement() {
+        let mut tt = TranspositionTable::new(1);
+        let mv = Move::quiet(E2, E4, WhitePawn);
+        tt.store(1234, 5, 50, Bound::Exact, mv);
+
+        // Shallower search of the same position doesn't replace.
+        tt.store(1234, 2, 10, Bound::Lower, mv);
+        assert_eq!(tt.probe(1234).unwrap().depth, 5);
+
+        // Deeper does.
+        tt.store(1234, 6, 20, Bound::Upper, mv);
+        assert_eq!(tt.probe(1234).unwrap().score, 20);
+
+        // Other positions always replace.
+        let other_hash = 1234 + tt.capacity() as u64;
+        tt.store(other_hash, 1, 0, Bound::Exact, mv);
+        assert!(tt.probe(1234).is_none());
+        assert_eq!(tt.probe(other_hash).unwrap().depth, 1);
+    }
+}
@@ -2,19 +2,24 @@
 #![allow(clippy::unused_self)]
 
 use std::{
-    collections::VecDeque,
     fmt::Display,
     io::{BufRead, Write},
 };
 
-use itertools::Itertools;
-
 use crate::{
+    book::OpeningBook,
     common::{ENGINE_AUTHOR, ENGINE_NAME},
     game::Game,
     moves::Move,
 };
 
+mod parser;
+
+pub use parser::{GoArgs, PositionArgs, UciCommand, UciError, UciParser};
+
+// Depth used when the go command doesn't specify one.
+const DEFAULT_SEARCH_DEPTH: u8 = 4;
+
 pub struct Uci<R, W>
 where
     W: std::io::Write,
@@ -22,6 +27,7 @@ where
     reader: R,
     writer: W,
     game: Game,
+    book: Option<OpeningBook>,
     debug: bool,
 }
 
@@ -49,10 +55,15 @@ where
             reader,
             writer,
             game: Game::new(),
+            book: None,
             debug: false,
         }
     }
 
+    pub fn set_book(&mut self, book: OpeningBook) {
+        self.book = Some(book);
+    }
+
     pub fn uci_loop(&mut self) {
         loop {
             let mut line = String::new();
@@ -65,30 +76,25 @@ where
 
             info!("< {}", line.trim());
 
-            // Split the input into tokens
-            let mut tokens: VecDeque<_> = line.split_ascii_whitespace().collect();
-            if tokens.is_empty() {
-                continue;
-            }
-
-            while let Some(cmd) = tokens.pop_front() {
-                match cmd.to_lowercase().as_str() {
-                    // Standard commands
-                    "uci" => self.handle_uci_cmd(),
-                    "debug" => self.handle_debug_cmd(&mut tokens),
-                    "isready" => self.handle_isready_cmd(),
-                    "setoptions" => self.handle_setoptions_cmd(&mut tokens),
-                    "ucinewgame" => self.handle_ucinewgame_cmd(),
-                    "position" => self.handle_position_cmd(&mut tokens),
-                    "go" => self.handle_go_cmd(&mut tokens),
-                    "stop" => self.handle_stop_cmd(),
-                    "quit" => return,
-                    "register" | "ponderhit" => {} // Command not implemented
-                    // Non-standard commands
-                    "d" => self.handle_d_cmd(),
-                    _ => continue, // Command was unknown, try next token.
+            match UciParser::parse_line(&line) {
+                // Standard commands
+                Ok(UciCommand::Uci) => self.handle_uci_cmd(),
+                Ok(UciCommand::Debug(debug)) => self.debug = debug,
+                Ok(UciCommand::IsReady) => self.handle_isready_cmd(),
+                Ok(UciCommand::UciNewGame) => self.handle_ucinewgame_cmd(),
+                Ok(UciCommand::Position(args)) => self.handle_position_cmd(&args),
+                Ok(UciCommand::Go(args)) => self.handle_go_cmd(&args),
+                Ok(UciCommand::Stop) => self.handle_stop_cmd(),
+                Ok(UciCommand::Quit) => return,
+                // Commands not implemented
+                Ok(UciCommand::SetOption { .. } | UciCommand::Register | UciCommand::PonderHit)
+                | Err(UciError::EmptyLine) => {}
+                // Non-standard commands
+                Ok(UciCommand::Display) => self.handle_d_cmd(),
+                Err(err) => {
+                    warn!("{err}");
+                    outputln!(&mut self.writer, "info string {err}");
                 }
-                break; // Command was handled.
             }
         }
     }
@@ -104,49 +110,56 @@ where
         // Would a call to flush be needed? self.writer.flush();
     }
 
-    fn handle_debug_cmd(&mut self, tokens: &mut VecDeque<&str>) {
-        let val = *tokens.front().expect("No debug value provided");
-        let debug = match val {
-            "on" => true,
-            "off" => false,
-            _ => panic!("Invalid debug value"),
-        };
-        self.debug = debug;
-    }
-
     fn handle_isready_cmd(&mut self) {
         // Ready to start
         outputln!(&mut self.writer, "readyok");
     }
 
-    fn handle_setoptions_cmd(&mut self, _tokens: &mut VecDeque<&str>) {}
-
     fn handle_ucinewgame_cmd(&mut self) {
         // Not mandatory to be sent by UIs, but most should support it.
         self.game.new_game();
     }
 
-    fn handle_position_cmd(&mut self, tokens: &mut VecDeque<&str>) {
-        let pos = *tokens.front().expect("No position provided");
-        if pos == "startpos" {
-            tokens.pop_front().unwrap();
+    fn handle_position_cmd(&mut self, args: &PositionArgs) {
+        if let Some(fen) = &args.fen {
+            self.game.set_to_fen(fen);
+        } else {
             self.game.set_to_startpos();
-        } else if pos == "fen" {
-            tokens.pop_front().unwrap();
-            // FEN string is always 6 tokens.
-            // Not great to split the string to join it again..
-            let fen = tokens.drain(0..6).join(" ");
-            self.game.set_to_fen(&fen);
         }
+        let moves: Vec<&str> = args.moves.iter().map(String::as_str).collect();
+        self.game.apply_moves(&moves);
+    }
 
-        if matches!(tokens.pop_front(), Some("moves")) {
-            self.game.apply_moves(tokens.make_contiguous());
+    fn handle_go_cmd(&mut self, args: &GoArgs) {
+        if let Some(mv) = self
+            .book
+            .as_ref()
+            .and_then(|book| book.lookup(&self.game.get_board()))
+        {
+            outputln!(&mut self.writer, "info string book move");
+            self.send_best_move(Some(mv), None);
+            return;
+        }
+
+        let mut time_control = args.time_control;
+        // Without any limit, we would search forever.
+        // As the stop command isn't supported yet, this includes infinite searches.
+        if time_control.depth.is_none()
+            && time_control
+                .allocate_time(self.game.get_board().get_side_to_move())
+                .is_none()
+        {
+            time_control.depth = Some(DEFAULT_SEARCH_DEPTH);
         }
-    }
 
-    fn handle_go_cmd(&mut self, _tokens: &mut VecDeque<&str>) {
-        let best_move = self.game.start_search();
-        self.send_best_move(best_move, None);
+        if let Some(reason) = self.game.draw_reason() {
+            outputln!(&mut self.writer, "info string draw by {reason}");
+        }
+        let result = self
+            .game
+            .start_parallel_search(&time_control, &mut self.writer);
+        // The reply expected in the PV is the move to ponder on.
+        self.send_best_move(result.best_move(), result.pv.get(1).copied());
     }
 
     fn handle_stop_cmd(&mut self) {}
@@ -204,7 +217,7 @@ impl Display for Info {
 mod tests {
     use std::io::Cursor;
 
-    use crate::board::Board;
+    use crate::{board::Board, fen};
 
     use super::*;
 
@@ -222,7 +235,19 @@ mod tests {
 
     #[test]
     fn test_uci_loop_position_fen() {
-        let input = "position fen r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1\nquit\n";
+        let input = format!("position fen {}\nquit\n", fen::KIWIPETE);
+        let mut reader = Cursor::new(input);
+        let mut writer = Vec::new();
+        let mut uci = Uci::new(&mut reader, &mut writer);
+
+        uci.uci_loop();
+
+        assert_eq!(uci.game.get_board(), Board::from_fen(fen::KIWIPETE));
+    }
+
+    #[test]
+    fn test_uci_loop_position_moves() {
+        let input = "position startpos moves e2e4 e7e5\nquit\n";
         let mut reader = Cursor::new(input);
         let mut writer = Vec::new();
         let mut uci = Uci::new(&mut reader, &mut writer);
@@ -231,22 +256,161 @@ mod tests {
 
         assert_eq!(
             uci.game.get_board(),
-            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            Board::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2")
         );
     }
 
     #[test]
-    fn test_uci_loop_position_moves() {
-        let input = "position startpos moves e2e4 e7e5\nquit\n";
+    fn test_uci_loop_go_depth() {
+        // Not a mate, as the search stops as soon as it finds one.
+        let input = "position fen 4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1\ngo depth 2\nquit\n";
         let mut reader = Cursor::new(input);
         let mut writer = Vec::new();
         let mut uci = Uci::new(&mut reader, &mut writer);
 
         uci.uci_loop();
 
+        let output = String::from_utf8(writer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("info depth 1 score cp "));
+        assert!(lines[0].ends_with(" pv d1d5"));
+        assert!(lines[1].starts_with("info depth 2 score cp "));
+        assert!(lines[1].contains(" pv d1d5 "));
+        assert!(lines[2].starts_with("bestmove d1d5 ponder "));
+    }
+
+    #[test]
+    fn test_uci_loop_go_depth_zero() {
+        let input = "position startpos\ngo depth 0\nquit\n";
+        let mut reader = Cursor::new(input);
+        let mut writer = Vec::new();
+        let mut uci = Uci::new(&mut reader, &mut writer);
+
+        uci.uci_loop();
+
+        // Searched to depth 1 instead.
+        let output = String::from_utf8(writer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("info depth 1 "));
+        assert!(lines[1].starts_with("bestmove "));
+        assert_ne!(lines[1], "bestmove (none)");
+    }
+
+    #[test]
+    fn test_uci_loop_go_time() {
+        let input =
+            "position startpos\ngo wtime 2000 btime 2000 winc 0 binc 0 movestogo 20\nquit\n";
+        let mut reader = Cursor::new(input);
+        let mut writer = Vec::new();
+        let mut uci = Uci::new(&mut reader, &mut writer);
+
+        // 100 ms allocated, so this doesn't search until the maximum depth.
+        uci.uci_loop();
+
+        let output = String::from_utf8(writer).unwrap();
+        assert!(output.starts_with("info depth 1 "));
+        assert!(output.lines().last().unwrap().starts_with("bestmove "));
+    }
+
+    #[test]
+    fn test_uci_loop_go_book() {
+        let input =
+            "position startpos\ngo depth 3\nposition startpos moves d2d4\ngo depth 1\nquit\n";
+        let mut reader = Cursor::new(input);
+        let mut writer = Vec::new();
+        let mut uci = Uci::new(&mut reader, &mut writer);
+        let book = format!("{} e2e4\n", fen::START_POSITION);
+        uci.set_book(OpeningBook::load(book.as_bytes()).unwrap());
+
+        uci.uci_loop();
+
+        let output = String::from_utf8(writer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "info string book move");
+        assert_eq!(lines[1], "bestmove e2e4");
+        // Out of the book, we search.
+        assert!(lines[2].starts_with("info depth 1 "));
+        assert!(lines[3].starts_with("bestmove "));
+    }
+
+    #[test]
+    fn test_uci_loop_go_draw() {
+        let input = "position fen 4k3/8/8/8/8/8/8/2B1K3 w - - 0 1\ngo depth 1\nquit\n";
+        let mut reader = Cursor::new(input);
+        let mut writer = Vec::new();
+        let mut uci = Uci::new(&mut reader, &mut writer);
+
+        uci.uci_loop();
+
+        let output = String::from_utf8(writer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "info string draw by insufficient material");
+        assert!(lines.last().unwrap().starts_with("bestmove "));
+    }
+
+    #[test]
+    fn test_uci_loop_invalid_command() {
+        let input = "position fen 8/8/8 w - - 0 1\nisready\nquit\n";
+        let mut reader = Cursor::new(input);
+        let mut writer = Vec::new();
+        let mut uci = Uci::new(&mut reader, &mut writer);
+
+        uci.uci_loop();
+        // The position wasn't changed.
+        assert_eq!(uci.game.get_board(), Board::initial_board());
+
+        let output = String::from_utf8(writer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "info string Invalid FEN: Invalid piece placement",
+                "readyok"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_uci_loop_position_invalid_moves() {
+        let input = "position startpos moves e2e4\nposition startpos moves zz\nposition startpos moves e2e5\nisready\nquit\n";
+        let mut reader = Cursor::new(input);
+        let mut writer = Vec::new();
+        let mut uci = Uci::new(&mut reader, &mut writer);
+
+        uci.uci_loop();
+        // The position of the last valid command is kept.
         assert_eq!(
             uci.game.get_board(),
-            Board::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 1")
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
         );
+
+        let output = String::from_utf8(writer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "info string Invalid move: zz",
+                "info string Invalid move: e2e5",
+                "readyok"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_uci_loop_go_no_moves() {
+        let input = "position fen k7/8/1Q6/8/8/8/8/7K b - - 0 1\ngo\nquit\n";
+        let mut reader = Cursor::new(input);
+        let mut writer = Vec::new();
+        let mut uci = Uci::new(&mut reader, &mut writer);
+
+        uci.uci_loop();
+
+        let output = String::from_utf8(writer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("info depth 1 score cp 0 nodes 0 time "));
+        assert_eq!(lines[1], "bestmove (none)");
     }
 }
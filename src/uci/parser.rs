@@ -0,0 +1,388 @@
+//! Parsing of the commands received from the UI.
+//! <https://backscattering.de/chess/uci/>
+
+use std::{fmt, time::Duration};
+
+use crate::{board::Board, common::Color, fen::FenError, moves::Move, search::TimeControl};
+
+// Arguments of the position command. The FEN string is None for the start position.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PositionArgs {
+    pub fen: Option<String>,
+    pub moves: Vec<String>,
+}
+
+// Arguments of the go command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GoArgs {
+    pub time_control: TimeControl,
+    // Search until the stop command.
+    pub infinite: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciCommand {
+    Uci,
+    Debug(bool),
+    IsReady,
+    SetOption { name: String, value: Option<String> },
+    Register,
+    UciNewGame,
+    Position(PositionArgs),
+    Go(GoArgs),
+    Stop,
+    PonderHit,
+    Quit,
+    // Non-standard command showing the board.
+    Display,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciError {
+    EmptyLine,
+    UnknownCommand(String),
+    InvalidDebugValue(String),
+    MissingOptionName,
+    MissingPosition,
+    InvalidPosition(String),
+    InvalidFen(FenError),
+    // A move of the position command that is malformed or illegal.
+    InvalidMove(String),
+    // Parameter of the go command, and its value.
+    InvalidGoValue(String, String),
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UciError::EmptyLine => write!(f, "Empty line"),
+            UciError::UnknownCommand(cmd) => write!(f, "Unknown command: {cmd}"),
+            UciError::InvalidDebugValue(val) => write!(f, "Invalid debug value: {val}"),
+            UciError::MissingOptionName => write!(f, "Missing option name"),
+            UciError::MissingPosition => write!(f, "Missing position"),
+            UciError::InvalidPosition(pos) => write!(f, "Invalid position: {pos}"),
+            UciError::InvalidFen(err) => write!(f, "Invalid FEN: {err}"),
+            UciError::InvalidMove(mv) => write!(f, "Invalid move: {mv}"),
+            UciError::InvalidGoValue(param, val) => {
+                write!(f, "Invalid value for {param}: {val}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UciError {}
+
+pub struct UciParser;
+
+impl UciParser {
+    // Parses one line of input.
+    // As the protocol requires, unknown tokens before the command are skipped.
+    pub fn parse_line(s: &str) -> Result<UciCommand, UciError> {
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+        let Some(first) = tokens.first() else {
+            return Err(UciError::EmptyLine);
+        };
+        for (i, cmd) in tokens.iter().enumerate() {
+            let args = &tokens[i + 1..];
+            let command = match cmd.to_lowercase().as_str() {
+                "uci" => UciCommand::Uci,
+                "debug" => parse_debug(args)?,
+                "isready" => UciCommand::IsReady,
+                "setoption" => parse_setoption(args)?,
+                "register" => UciCommand::Register,
+                "ucinewgame" => UciCommand::UciNewGame,
+                "position" => UciCommand::Position(parse_position(args)?),
+                "go" => UciCommand::Go(parse_go(args)?),
+                "stop" => UciCommand::Stop,
+                "ponderhit" => UciCommand::PonderHit,
+                "quit" => UciCommand::Quit,
+                "d" => UciCommand::Display,
+                _ => continue,
+            };
+            return Ok(command);
+        }
+        Err(UciError::UnknownCommand((*first).to_string()))
+    }
+}
+
+// Without a value, debug is turned on.
+fn parse_debug(args: &[&str]) -> Result<UciCommand, UciError> {
+    match args.first() {
+        None | Some(&"on") => Ok(UciCommand::Debug(true)),
+        Some(&"off") => Ok(UciCommand::Debug(false)),
+        Some(val) => Err(UciError::InvalidDebugValue((*val).to_string())),
+    }
+}
+
+// setoption name <id> [value <x>], where both the name and the value may contain spaces.
+fn parse_setoption(args: &[&str]) -> Result<UciCommand, UciError> {
+    if args.first() != Some(&"name") {
+        return Err(UciError::MissingOptionName);
+    }
+    let args = &args[1..];
+    let value_pos = args.iter().position(|&t| t == "value");
+    let name = args[..value_pos.unwrap_or(args.len())].join(" ");
+    if name.is_empty() {
+        return Err(UciError::MissingOptionName);
+    }
+    let value = value_pos.map(|pos| args[pos + 1..].join(" "));
+    Ok(UciCommand::SetOption { name, value })
+}
+
+// position [fen <fenstring> | startpos] moves <move1> ... <movei>
+// The moves are checked to be legal, so that they can be applied safely.
+fn parse_position(args: &[&str]) -> Result<PositionArgs, UciError> {
+    let moves_pos = args.iter().position(|&t| t == "moves");
+    let (position, moves) = match moves_pos {
+        Some(pos) => (&args[..pos], &args[pos + 1..]),
+        None => (args, &[][..]),
+    };
+    let (fen, mut board) = match position.split_first() {
+        None => return Err(UciError::MissingPosition),
+        Some((&"startpos", _)) => (None, Board::initial_board()),
+        Some((&"fen", fields)) => {
+            let fen = fields.join(" ");
+            let board = Board::try_from_fen(&fen).map_err(UciError::InvalidFen)?;
+            (Some(fen), board)
+        }
+        Some((pos, _)) => return Err(UciError::InvalidPosition((*pos).to_string())),
+    };
+    for mv in moves {
+        board = Move::from_uci(mv, &board)
+            .and_then(|m| board.apply_move(m))
+            .map_err(|_| UciError::InvalidMove((*mv).to_string()))?;
+    }
+    Ok(PositionArgs {
+        fen,
+        moves: moves.iter().map(|mv| (*mv).to_string()).collect(),
+    })
+}
+
+// Parameters we don't support, like searchmoves, are ignored.
+fn parse_go(args: &[&str]) -> Result<GoArgs, UciError> {
+    let mut go_args = GoArgs::default();
+    let time_control = &mut go_args.time_control;
+    let mut tokens = args.iter();
+    while let Some(&param) = tokens.next() {
+        match param {
+            "infinite" => {
+                go_args.infinite = true;
+                continue;
+            }
+            "depth" | "wtime" | "btime" | "winc" | "binc" | "movestogo" | "movetime" => {}
+            _ => continue,
+        }
+        let value = tokens.next().copied().unwrap_or_default();
+        let number: u64 = value
+            .parse()
+            .map_err(|_| UciError::InvalidGoValue(param.to_string(), value.to_string()))?;
+        let millis = Duration::from_millis(number);
+        match param {
+            "depth" => time_control.depth = Some(u8::try_from(number).unwrap_or(u8::MAX)),
+            "wtime" => time_control.remaining[Color::White as usize] = Some(millis),
+            "btime" => time_control.remaining[Color::Black as usize] = Some(millis),
+            "winc" => time_control.increment[Color::White as usize] = millis,
+            "binc" => time_control.increment[Color::Black as usize] = millis,
+            "movestogo" => time_control.moves_to_go = u32::try_from(number).ok(),
+            _ => time_control.move_time = Some(millis),
+        }
+    }
+    Ok(go_args)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fen;
+
+    use super::*;
+
+    #[test]
+    fn test_simple_commands() {
+        assert_eq!(UciParser::parse_line("uci"), Ok(UciCommand::Uci));
+        assert_eq!(UciParser::parse_line("isready\n"), Ok(UciCommand::IsReady));
+        assert_eq!(
+            UciParser::parse_line("ucinewgame"),
+            Ok(UciCommand::UciNewGame)
+        );
+        assert_eq!(UciParser::parse_line("stop"), Ok(UciCommand::Stop));
+        assert_eq!(UciParser::parse_line("QUIT"), Ok(UciCommand::Quit));
+        assert_eq!(
+            UciParser::parse_line("debug off"),
+            Ok(UciCommand::Debug(false))
+        );
+    }
+
+    #[test]
+    fn test_extra_whitespace() {
+        assert_eq!(
+            UciParser::parse_line("  \t isready  \r\n"),
+            Ok(UciCommand::IsReady)
+        );
+        assert_eq!(
+            UciParser::parse_line("position   startpos  moves  e2e4\te7e5 "),
+            Ok(UciCommand::Position(PositionArgs {
+                fen: None,
+                moves: vec!["e2e4".to_string(), "e7e5".to_string()],
+            }))
+        );
+    }
+
+    #[test]
+    fn test_unknown_commands() {
+        assert_eq!(UciParser::parse_line(""), Err(UciError::EmptyLine));
+        assert_eq!(UciParser::parse_line("   \n"), Err(UciError::EmptyLine));
+        assert_eq!(
+            UciParser::parse_line("hello world"),
+            Err(UciError::UnknownCommand("hello".to_string()))
+        );
+        // Example from the protocol description.
+        assert_eq!(
+            UciParser::parse_line("joho debug on"),
+            Ok(UciCommand::Debug(true))
+        );
+        assert_eq!(
+            UciParser::parse_line("debug maybe"),
+            Err(UciError::InvalidDebugValue("maybe".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_setoption() {
+        assert_eq!(
+            UciParser::parse_line("setoption name Clear Hash"),
+            Ok(UciCommand::SetOption {
+                name: "Clear Hash".to_string(),
+                value: None
+            })
+        );
+        assert_eq!(
+            UciParser::parse_line("setoption name Hash value 32"),
+            Ok(UciCommand::SetOption {
+                name: "Hash".to_string(),
+                value: Some("32".to_string())
+            })
+        );
+        assert_eq!(
+            UciParser::parse_line("setoption value 32"),
+            Err(UciError::MissingOptionName)
+        );
+    }
+
+    #[test]
+    fn test_position() {
+        assert_eq!(
+            UciParser::parse_line(&format!("position fen {}", fen::KIWIPETE)),
+            Ok(UciCommand::Position(PositionArgs {
+                fen: Some(fen::KIWIPETE.to_string()),
+                moves: vec![],
+            }))
+        );
+        assert_eq!(
+            UciParser::parse_line(&format!("position fen {} moves e1g1", fen::KIWIPETE)),
+            Ok(UciCommand::Position(PositionArgs {
+                fen: Some(fen::KIWIPETE.to_string()),
+                moves: vec!["e1g1".to_string()],
+            }))
+        );
+        assert_eq!(
+            UciParser::parse_line("position"),
+            Err(UciError::MissingPosition)
+        );
+        assert_eq!(
+            UciParser::parse_line("position endpos"),
+            Err(UciError::InvalidPosition("endpos".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_position_invalid_moves() {
+        assert_eq!(
+            UciParser::parse_line("position startpos moves e2e4 zz"),
+            Err(UciError::InvalidMove("zz".to_string()))
+        );
+        // Pawns can't move 3 squares.
+        assert_eq!(
+            UciParser::parse_line("position startpos moves e2e5"),
+            Err(UciError::InvalidMove("e2e5".to_string()))
+        );
+        // Legal only if it's white to move.
+        assert_eq!(
+            UciParser::parse_line("position startpos moves e2e4 d2d4"),
+            Err(UciError::InvalidMove("d2d4".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_position_malformed_fen() {
+        // Missing the clocks.
+        assert_eq!(
+            UciParser::parse_line("position fen 4k3/8/8/8/8/8/8/4K3 w - moves e1e2"),
+            Err(UciError::InvalidFen(FenError::WrongFieldCount))
+        );
+        assert_eq!(
+            UciParser::parse_line("position fen 4k3/8/8/8/8/8/4K3 w - - 0 1"),
+            Err(UciError::InvalidFen(FenError::InvalidPiecePlacement))
+        );
+        assert_eq!(
+            UciParser::parse_line("position fen 4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+            Err(UciError::InvalidFen(FenError::InvalidSideToMove))
+        );
+        assert_eq!(
+            UciParser::parse_line("position fen"),
+            Err(UciError::InvalidFen(FenError::WrongFieldCount))
+        );
+    }
+
+    #[test]
+    fn test_go() {
+        assert_eq!(
+            UciParser::parse_line("go"),
+            Ok(UciCommand::Go(GoArgs::default()))
+        );
+        assert_eq!(
+            UciParser::parse_line("go depth 6"),
+            Ok(UciCommand::Go(GoArgs {
+                time_control: TimeControl::fixed_depth(6),
+                infinite: false,
+            }))
+        );
+
+        let Ok(UciCommand::Go(go_args)) =
+            UciParser::parse_line("go ponder wtime 1500 btime 2500 winc 10 binc 20 movestogo 5")
+        else {
+            panic!("Go command not parsed");
+        };
+        let time_control = go_args.time_control;
+        assert_eq!(
+            time_control.remaining,
+            [
+                Some(Duration::from_millis(1500)),
+                Some(Duration::from_millis(2500))
+            ]
+        );
+        assert_eq!(
+            time_control.increment,
+            [Duration::from_millis(10), Duration::from_millis(20)]
+        );
+        assert_eq!(time_control.moves_to_go, Some(5));
+
+        assert_eq!(
+            UciParser::parse_line("go infinite"),
+            Ok(UciCommand::Go(GoArgs {
+                infinite: true,
+                ..Default::default()
+            }))
+        );
+        assert_eq!(
+            UciParser::parse_line("go movetime soon"),
+            Err(UciError::InvalidGoValue(
+                "movetime".to_string(),
+                "soon".to_string()
+            ))
+        );
+        assert_eq!(
+            UciParser::parse_line("go depth"),
+            Err(UciError::InvalidGoValue("depth".to_string(), String::new()))
+        );
+    }
+}